@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use ansi_term::Style;
-use ansi_term::Colour::{Red, Green, Yellow, Blue, Cyan, Purple, Fixed};
+use ansi_term::Colour;
+use ansi_term::Colour::{Black, Red, Green, Yellow, Blue, Cyan, Purple, White, Fixed};
 
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Colours {
     pub filetypes:  FileTypes,
     pub perms:      Permissions,
@@ -12,14 +15,21 @@ pub struct Colours {
     pub git:        Git,
 
     pub punctuation:  Style,
+    pub tree:         Style,
     pub date:         Style,
     pub inode:        Style,
     pub blocks:       Style,
     pub header:       Style,
+    pub highlight:    Style,
 
     pub symlink_path:     Style,
     pub broken_arrow:     Style,
     pub broken_filename:  Style,
+
+    /// Styles for regular files whose names end with particular suffixes,
+    /// such as `.tar.gz`, which take precedence over their type's style.
+    /// The suffixes are stored in lowercase.
+    pub extensions: HashMap<String, Style>,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -57,12 +67,16 @@ pub struct Permissions {
     pub other_execute: Style,
 
     pub attribute:  Style,
+    pub octal:      Style,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Size {
     pub numbers: Style,
     pub unit: Style,
+    pub allocated: Style,
+    pub large: Style,
+    pub binary: Style,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -86,6 +100,8 @@ pub struct Git {
     pub deleted: Style,
     pub renamed: Style,
     pub typechange: Style,
+    pub ignored: Style,
+    pub commit: Style,
 }
 
 impl Colours {
@@ -125,11 +141,15 @@ impl Colours {
                 other_write:         Red.normal(),
                 other_execute:       Green.normal(),
                 attribute:           Style::default(),
+                octal:               Purple.normal(),
             },
 
             size: Size {
-                numbers:  Green.bold(),
-                unit:     Green.normal(),
+                numbers:    Green.bold(),
+                unit:       Green.normal(),
+                allocated:  Yellow.normal(),
+                large:      Red.bold(),
+                binary:     Cyan.normal(),
             },
 
             users: Users {
@@ -150,17 +170,634 @@ impl Colours {
                 deleted:     Red.normal(),
                 renamed:     Yellow.normal(),
                 typechange:  Purple.normal(),
+                ignored:     Fixed(244).normal(),
+                commit:      Yellow.normal(),
             },
 
             punctuation:  Fixed(244).normal(),
+            tree:         Fixed(244).normal(),
             date:         Blue.normal(),
             inode:        Purple.normal(),
             blocks:       Cyan.normal(),
             header:       Style::default().underline(),
+            highlight:    Black.on(Yellow),
 
             symlink_path:     Cyan.normal(),
             broken_arrow:     Red.normal(),
-            broken_filename:  Red.underline()
+            broken_filename:  Red.underline(),
+            extensions:       HashMap::new(),
         }
     }
+
+    /// A copy of these colours with every style made faint, for painting
+    /// the parts of the output that should stand back from the rest.
+    pub fn dimmed(&self) -> Colours {
+        Colours {
+            filetypes: FileTypes {
+                normal:      self.filetypes.normal.dimmed(),
+                directory:   self.filetypes.directory.dimmed(),
+                symlink:     self.filetypes.symlink.dimmed(),
+                special:     self.filetypes.special.dimmed(),
+                executable:  self.filetypes.executable.dimmed(),
+                image:       self.filetypes.image.dimmed(),
+                video:       self.filetypes.video.dimmed(),
+                music:       self.filetypes.music.dimmed(),
+                lossless:    self.filetypes.lossless.dimmed(),
+                crypto:      self.filetypes.crypto.dimmed(),
+                document:    self.filetypes.document.dimmed(),
+                compressed:  self.filetypes.compressed.dimmed(),
+                temp:        self.filetypes.temp.dimmed(),
+                immediate:   self.filetypes.immediate.dimmed(),
+                compiled:    self.filetypes.compiled.dimmed(),
+            },
+
+            perms: Permissions {
+                user_read:           self.perms.user_read.dimmed(),
+                user_write:          self.perms.user_write.dimmed(),
+                user_execute_file:   self.perms.user_execute_file.dimmed(),
+                user_execute_other:  self.perms.user_execute_other.dimmed(),
+                group_read:          self.perms.group_read.dimmed(),
+                group_write:         self.perms.group_write.dimmed(),
+                group_execute:       self.perms.group_execute.dimmed(),
+                other_read:          self.perms.other_read.dimmed(),
+                other_write:         self.perms.other_write.dimmed(),
+                other_execute:       self.perms.other_execute.dimmed(),
+                attribute:           self.perms.attribute.dimmed(),
+                octal:               self.perms.octal.dimmed(),
+            },
+
+            size: Size {
+                numbers:    self.size.numbers.dimmed(),
+                unit:       self.size.unit.dimmed(),
+                allocated:  self.size.allocated.dimmed(),
+                large:      self.size.large.dimmed(),
+                binary:     self.size.binary.dimmed(),
+            },
+
+            users: Users {
+                user_you:           self.users.user_you.dimmed(),
+                user_someone_else:  self.users.user_someone_else.dimmed(),
+                group_yours:        self.users.group_yours.dimmed(),
+                group_not_yours:    self.users.group_not_yours.dimmed(),
+            },
+
+            links: Links {
+                normal:          self.links.normal.dimmed(),
+                multi_link_file: self.links.multi_link_file.dimmed(),
+            },
+
+            git: Git {
+                new:         self.git.new.dimmed(),
+                modified:    self.git.modified.dimmed(),
+                deleted:     self.git.deleted.dimmed(),
+                renamed:     self.git.renamed.dimmed(),
+                typechange:  self.git.typechange.dimmed(),
+                ignored:     self.git.ignored.dimmed(),
+                commit:      self.git.commit.dimmed(),
+            },
+
+            punctuation:  self.punctuation.dimmed(),
+            tree:         self.tree.dimmed(),
+            date:         self.date.dimmed(),
+            inode:        self.inode.dimmed(),
+            blocks:       self.blocks.dimmed(),
+            header:       self.header.dimmed(),
+            highlight:    self.highlight.dimmed(),
+
+            symlink_path:     self.symlink_path.dimmed(),
+            broken_arrow:     self.broken_arrow.dimmed(),
+            broken_filename:  self.broken_filename.dimmed(),
+            extensions:       self.extensions.iter().map(|(ext, style)| (ext.clone(), style.dimmed())).collect(),
+        }
+    }
+
+    /// Override some of these colours using the value of `LS_COLORS`: a
+    /// colon-separated list of `key=codes` pairs, where the codes are
+    /// semicolon-separated ANSI style numbers.
+    ///
+    /// Only the keys that dircolors itself uses are looked at, as other
+    /// programs give their own meanings to the rest (`tw`, for example,
+    /// is a sticky other-writable directory to ls, not a permission bit).
+    pub fn apply_ls_colors(&mut self, spec: &str) {
+        self.apply_spec(spec, Colours::ls_field);
+    }
+
+    /// Override some of these colours using the value of `EXA_COLORS`,
+    /// which is in the same format as `LS_COLORS`, but also has keys for
+    /// everything exa displays that ls doesn't.
+    pub fn apply_exa_colors(&mut self, spec: &str) {
+        self.apply_spec(spec, Colours::field);
+    }
+
+    /// Apply each pair in a colours specification, using the given
+    /// function to find the style a key refers to.
+    ///
+    /// Keys of the form `*.ext` set the style of files with that suffix.
+    /// Keys that don't correspond to anything exa displays, or that have
+    /// codes that can't be parsed, are ignored.
+    fn apply_spec<F>(&mut self, spec: &str, field: F)
+    where F: for<'a> Fn(&'a mut Colours, &str) -> Option<&'a mut Style> {
+        for pair in spec.split(':') {
+            let mut parts = pair.splitn(2, '=');
+
+            let (key, codes) = match (parts.next(), parts.next()) {
+                (Some(key), Some(codes))  => (key, codes),
+                _                         => continue,
+            };
+
+            let style = match parse_style(codes) {
+                Some(style)  => style,
+                None         => continue,
+            };
+
+            if key.starts_with('*') {
+                let _ = self.extensions.insert(key[1..].to_lowercase(), style);
+            }
+            else if let Some(field) = field(self, key) {
+                *field = style;
+            }
+        }
+    }
+
+    /// Override some of these colours with the styles from a theme file.
+    pub fn apply_theme(&mut self, theme: &Theme) {
+        for &(ref name, style) in theme.styles.iter() {
+            if let Some(field) = self.named_field(name) {
+                *field = style;
+            }
+        }
+
+        for &(ref suffix, style) in theme.extensions.iter() {
+            let _ = self.extensions.insert(suffix.to_lowercase(), style);
+        }
+    }
+
+    /// The style for a file with the given name, based on the longest
+    /// suffix that has one, ignoring case, if any do.
+    pub fn extension_style(&self, name: &str) -> Option<Style> {
+        let name = name.to_lowercase();
+
+        self.extensions.iter()
+            .filter(|&(suffix, _)| name.ends_with(&**suffix))
+            .fold(None, |best: Option<(&String, &Style)>, (suffix, style)| match best {
+                Some((longest, _)) if longest.len() >= suffix.len() => best,
+                _ => Some((suffix, style)),
+            })
+            .map(|(_, style)| *style)
+    }
+
+    /// Get a mutable reference to the style with the given dircolors key,
+    /// if it's one that exa has a style for.
+    fn ls_field(&mut self, key: &str) -> Option<&mut Style> {
+        let field = match key {
+            "fi" => &mut self.filetypes.normal,
+            "di" => &mut self.filetypes.directory,
+            "ln" => &mut self.filetypes.symlink,
+            "pi" | "so" | "bd" | "cd" => &mut self.filetypes.special,
+            "ex" => &mut self.filetypes.executable,
+            "or" => &mut self.broken_filename,
+            _    => return None,
+        };
+
+        Some(field)
+    }
+
+    /// Get a mutable reference to the style with the given two-letter key,
+    /// which is either one of exa's own or a dircolors key.
+    fn field(&mut self, key: &str) -> Option<&mut Style> {
+        let field = match key {
+            "ur" => &mut self.perms.user_read,
+            "uw" => &mut self.perms.user_write,
+            "ux" => &mut self.perms.user_execute_file,
+            "ue" => &mut self.perms.user_execute_other,
+            "gr" => &mut self.perms.group_read,
+            "gw" => &mut self.perms.group_write,
+            "gx" => &mut self.perms.group_execute,
+            "tr" => &mut self.perms.other_read,
+            "tw" => &mut self.perms.other_write,
+            "tx" => &mut self.perms.other_execute,
+            "xa" => &mut self.perms.attribute,
+            "oc" => &mut self.perms.octal,
+
+            "sn" => &mut self.size.numbers,
+            "sb" => &mut self.size.unit,
+            "sl" => &mut self.size.large,
+
+            "uu" => &mut self.users.user_you,
+            "un" => &mut self.users.user_someone_else,
+            "gu" => &mut self.users.group_yours,
+            "gn" => &mut self.users.group_not_yours,
+
+            "lc" => &mut self.links.normal,
+            "lm" => &mut self.links.multi_link_file,
+
+            "ga" => &mut self.git.new,
+            "gm" => &mut self.git.modified,
+            "gd" => &mut self.git.deleted,
+            "gv" => &mut self.git.renamed,
+            "gt" => &mut self.git.typechange,
+            "gi" => &mut self.git.ignored,
+            "gc" => &mut self.git.commit,
+
+            "xx" => &mut self.punctuation,
+            "da" => &mut self.date,
+            "in" => &mut self.inode,
+            "bl" => &mut self.blocks,
+            "hd" => &mut self.header,
+            "hl" => &mut self.highlight,
+            "lp" => &mut self.symlink_path,
+            _    => return self.ls_field(key),
+        };
+
+        Some(field)
+    }
+
+    /// Get a mutable reference to the style with the given name, as used
+    /// in theme files: the name of the field, after the name of the group
+    /// it's in and a dot, if it's in one.
+    fn named_field(&mut self, name: &str) -> Option<&mut Style> {
+        let field = match name {
+            "filetypes.normal"      => &mut self.filetypes.normal,
+            "filetypes.directory"   => &mut self.filetypes.directory,
+            "filetypes.symlink"     => &mut self.filetypes.symlink,
+            "filetypes.special"     => &mut self.filetypes.special,
+            "filetypes.executable"  => &mut self.filetypes.executable,
+            "filetypes.image"       => &mut self.filetypes.image,
+            "filetypes.video"       => &mut self.filetypes.video,
+            "filetypes.music"       => &mut self.filetypes.music,
+            "filetypes.lossless"    => &mut self.filetypes.lossless,
+            "filetypes.crypto"      => &mut self.filetypes.crypto,
+            "filetypes.document"    => &mut self.filetypes.document,
+            "filetypes.compressed"  => &mut self.filetypes.compressed,
+            "filetypes.temp"        => &mut self.filetypes.temp,
+            "filetypes.immediate"   => &mut self.filetypes.immediate,
+            "filetypes.compiled"    => &mut self.filetypes.compiled,
+
+            "perms.user_read"           => &mut self.perms.user_read,
+            "perms.user_write"          => &mut self.perms.user_write,
+            "perms.user_execute_file"   => &mut self.perms.user_execute_file,
+            "perms.user_execute_other"  => &mut self.perms.user_execute_other,
+            "perms.group_read"          => &mut self.perms.group_read,
+            "perms.group_write"         => &mut self.perms.group_write,
+            "perms.group_execute"       => &mut self.perms.group_execute,
+            "perms.other_read"          => &mut self.perms.other_read,
+            "perms.other_write"         => &mut self.perms.other_write,
+            "perms.other_execute"       => &mut self.perms.other_execute,
+            "perms.attribute"           => &mut self.perms.attribute,
+            "perms.octal"               => &mut self.perms.octal,
+
+            "size.numbers"    => &mut self.size.numbers,
+            "size.unit"       => &mut self.size.unit,
+            "size.allocated"  => &mut self.size.allocated,
+            "size.large"      => &mut self.size.large,
+            "size.binary"     => &mut self.size.binary,
+
+            "users.user_you"           => &mut self.users.user_you,
+            "users.user_someone_else"  => &mut self.users.user_someone_else,
+            "users.group_yours"        => &mut self.users.group_yours,
+            "users.group_not_yours"    => &mut self.users.group_not_yours,
+
+            "links.normal"           => &mut self.links.normal,
+            "links.multi_link_file"  => &mut self.links.multi_link_file,
+
+            "git.new"         => &mut self.git.new,
+            "git.modified"    => &mut self.git.modified,
+            "git.deleted"     => &mut self.git.deleted,
+            "git.renamed"     => &mut self.git.renamed,
+            "git.typechange"  => &mut self.git.typechange,
+            "git.ignored"     => &mut self.git.ignored,
+            "git.commit"      => &mut self.git.commit,
+
+            "punctuation"      => &mut self.punctuation,
+            "tree"             => &mut self.tree,
+            "date"             => &mut self.date,
+            "inode"            => &mut self.inode,
+            "blocks"           => &mut self.blocks,
+            "header"           => &mut self.header,
+            "highlight"        => &mut self.highlight,
+            "symlink_path"     => &mut self.symlink_path,
+            "broken_arrow"     => &mut self.broken_arrow,
+            "broken_filename"  => &mut self.broken_filename,
+            _                  => return None,
+        };
+
+        Some(field)
+    }
+}
+
+
+/// The styles read from a theme file, ready to be applied on top of the
+/// default colours.
+///
+/// Theme files use a small subset of TOML: each line is either a
+/// `[group]` header, or a `name = "codes"` pair, where the codes are the
+/// same semicolon-separated ANSI style numbers that `LS_COLORS` uses.
+/// Styles in the `[extensions]` group are named by file suffix, such as
+/// `".txt"`. Blank lines and lines starting with `#` are skipped.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct Theme {
+    styles: Vec<(String, Style)>,
+    extensions: Vec<(String, Style)>,
+}
+
+impl Theme {
+
+    /// Parse the contents of a theme file. Every line that doesn't make
+    /// sense, including those with names that don't match any style, gets
+    /// listed in the error, rather than being skipped.
+    pub fn parse(contents: &str) -> Result<Theme, String> {
+        let mut theme = Theme::default();
+        let mut problems = Vec::new();
+        let mut group = String::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                group = line[1 .. line.len() - 1].trim().to_string();
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '=');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value))  => (unquote(key), unquote(value)),
+                _ => {
+                    problems.push(format!("line {}: expected `name = \"codes\"`", number + 1));
+                    continue;
+                },
+            };
+
+            let style = match parse_style(value) {
+                Some(style)  => style,
+                None         => {
+                    problems.push(format!("line {}: invalid style {:?} for {}", number + 1, value, key));
+                    continue;
+                },
+            };
+
+            if group == "extensions" {
+                theme.extensions.push((key.to_string(), style));
+                continue;
+            }
+
+            let name = if group.is_empty() { key.to_string() }
+                                      else { format!("{}.{}", group, key) };
+
+            if Colours::plain().named_field(&name).is_some() {
+                theme.styles.push((name, style));
+            }
+            else {
+                problems.push(format!("line {}: unknown style {}", number + 1, name));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(theme)
+        }
+        else {
+            let mut message = String::new();
+            for (index, problem) in problems.iter().enumerate() {
+                if index > 0 { message.push_str("; ") }
+                message.push_str(problem);
+            }
+
+            Err(message)
+        }
+    }
+}
+
+/// Trim the whitespace from around a name or value in a theme file, and
+/// the quotes around it, if it has any.
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+
+    if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        &text[1 .. text.len() - 1]
+    }
+    else {
+        text
+    }
+}
+
+
+/// Parse a list of semicolon-separated ANSI style codes, such as `01;34`,
+/// into a style. Returns `None` if any of the codes aren't understood.
+fn parse_style(codes: &str) -> Option<Style> {
+    let mut foreground = None;
+    let mut background = None;
+    let mut bold = false;
+    let mut underline = false;
+
+    let mut numbers = codes.split(';');
+    while let Some(code) = numbers.next() {
+        let number: u8 = match code.parse() {
+            Ok(n)   => n,
+            Err(_)  => return None,
+        };
+
+        match number {
+            0        => {},
+            1        => bold = true,
+            4        => underline = true,
+            30 ... 37  => foreground = Some(basic_colour(number - 30)),
+            40 ... 47  => background = Some(basic_colour(number - 40)),
+            38 | 48  => {
+                // 256-colour codes take the form `38;5;N`, and 24-bit ones
+                // `38;2;R;G;B`. ansi_term can't emit 24-bit colours, so those
+                // get the nearest of the 256 instead.
+                let colour = match numbers.next() {
+                    Some("5") => match numbers.next().and_then(|n| n.parse().ok()) {
+                        Some(n)  => Fixed(n),
+                        None     => return None,
+                    },
+                    Some("2") => {
+                        let mut component = || numbers.next().and_then(|n| n.parse().ok());
+                        match (component(), component(), component()) {
+                            (Some(r), Some(g), Some(b))  => Fixed(nearest_fixed(r, g, b)),
+                            _                            => return None,
+                        }
+                    },
+                    _ => return None,
+                };
+
+                if number == 38 { foreground = Some(colour) }
+                           else { background = Some(colour) }
+            },
+            // Codes for things exa can't display, such as blinking, get
+            // skipped rather than throwing away the rest of the style.
+            _        => {},
+        }
+    }
+
+    let mut style = match foreground {
+        Some(colour)  => colour.normal(),
+        None          => Style::default(),
+    };
+
+    if bold      { style = style.bold() }
+    if underline { style = style.underline() }
+    if let Some(colour) = background { style = style.on(colour) }
+
+    Some(style)
+}
+
+/// The index of the 256-colour palette entry closest to the given 24-bit
+/// colour. Greys use the palette's greyscale ramp, which is finer-grained
+/// than the colour cube's diagonal.
+fn nearest_fixed(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0 ... 7      => 16,
+            239 ... 255  => 231,
+            grey         => 232 + (grey - 8) / 10,
+        };
+    }
+
+    // The cube's six levels are 0, 95, 135, 175, 215, and 255.
+    let level = |v: u8| match v {
+        0 ... 47   => 0,
+        48 ... 114 => 1,
+        v          => (v - 35) / 40,
+    };
+
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+fn basic_colour(number: u8) -> Colour {
+    match number {
+        0 => Black,
+        1 => Red,
+        2 => Green,
+        3 => Yellow,
+        4 => Blue,
+        5 => Purple,
+        6 => Cyan,
+        _ => White,
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::{Colours, Theme, parse_style};
+    use ansi_term::Style;
+    use ansi_term::Colour::*;
+
+    #[test]
+    fn bold_blue() {
+        assert_eq!(parse_style("01;34"), Some(Blue.bold()))
+    }
+
+    #[test]
+    fn fixed() {
+        assert_eq!(parse_style("38;5;208"), Some(Fixed(208).normal()))
+    }
+
+    #[test]
+    fn truecolour() {
+        assert_eq!(parse_style("38;2;255;0;0"), Some(Fixed(196).normal()));
+        assert_eq!(parse_style("01;48;2;0;0;0"), Some(Style::default().bold().on(Fixed(16))));
+    }
+
+    #[test]
+    fn truecolour_grey() {
+        assert_eq!(parse_style("38;2;128;128;128"), Some(Fixed(244).normal()));
+    }
+
+    #[test]
+    fn truecolour_missing_component() {
+        assert_eq!(parse_style("38;2;255;0"), None);
+    }
+
+    #[test]
+    fn nonsense() {
+        assert_eq!(parse_style("blue"), None)
+    }
+
+    #[test]
+    fn unknown_codes() {
+        assert_eq!(parse_style("05;01;34"), Some(Blue.bold()))
+    }
+
+    #[test]
+    fn ls_colors_keys() {
+        let mut colours = Colours::plain();
+        colours.apply_ls_colors("di=34:tw=30;42:lc=31:sn=33");
+
+        assert_eq!(colours.filetypes.directory, Blue.normal());
+        assert_eq!(colours.perms.other_write, Style::default());
+        assert_eq!(colours.links.normal, Style::default());
+        assert_eq!(colours.size.numbers, Style::default());
+    }
+
+    #[test]
+    fn spec() {
+        let mut colours = Colours::plain();
+        colours.apply_exa_colors("di=34:ex=01;32:sn=33:zz=31:*.txt=35");
+
+        assert_eq!(colours.filetypes.directory, Blue.normal());
+        assert_eq!(colours.filetypes.executable, Green.bold());
+        assert_eq!(colours.size.numbers, Yellow.normal());
+        assert_eq!(colours.filetypes.normal, Style::default());
+        assert_eq!(colours.extension_style("notes.txt"), Some(Purple.normal()));
+    }
+
+    #[test]
+    fn longest_extension() {
+        let mut colours = Colours::plain();
+        colours.apply_ls_colors("*.gz=31:*.tar.gz=01;31");
+
+        assert_eq!(colours.extension_style("backup.TAR.GZ"), Some(Red.bold()));
+        assert_eq!(colours.extension_style("log.gz"), Some(Red.normal()));
+        assert_eq!(colours.extension_style("tar"), None);
+    }
+
+    #[test]
+    fn theme() {
+        let theme = Theme::parse("# A theme\n\
+                                  date = \"33\"\n\
+                                  \n\
+                                  [filetypes]\n\
+                                  directory = \"01;34\"\n\
+                                  [perms]\n\
+                                  user_read = \"38;5;208\"\n\
+                                  [extensions]\n\
+                                  \".TXT\" = \"35\"\n").unwrap();
+
+        let mut colours = Colours::colourful();
+        colours.apply_theme(&theme);
+
+        assert_eq!(colours.date, Yellow.normal());
+        assert_eq!(colours.filetypes.directory, Blue.bold());
+        assert_eq!(colours.perms.user_read, Fixed(208).normal());
+        assert_eq!(colours.filetypes.executable, Colours::colourful().filetypes.executable);
+        assert_eq!(colours.extension_style("notes.txt"), Some(Purple.normal()));
+    }
+
+    #[test]
+    fn theme_unknown_keys() {
+        let result = Theme::parse("[perms]\nuser_reed = \"33\"\n[size]\nnumbers = \"32\"\ncolour = \"31\"\n");
+        assert_eq!(result, Err("line 2: unknown style perms.user_reed; line 5: unknown style size.colour".to_string()));
+    }
+
+    #[test]
+    fn theme_bad_values() {
+        let result = Theme::parse("date = \"blue\"\ntree\n");
+        assert_eq!(result, Err("line 1: invalid style \"blue\" for date; line 2: expected `name = \"codes\"`".to_string()));
+    }
+
+    #[test]
+    fn dimmed() {
+        let mut colours = Colours::colourful();
+        let _ = colours.extensions.insert(".txt".to_string(), Purple.normal());
+        let dimmed = colours.dimmed();
+
+        assert_eq!(dimmed.date, Blue.normal().dimmed());
+        assert_eq!(dimmed.size.numbers, Green.bold().dimmed());
+        assert_eq!(dimmed.users.user_someone_else, Style::default().dimmed());
+        assert_eq!(dimmed.extension_style("notes.txt"), Some(Purple.normal().dimmed()));
+    }
 }
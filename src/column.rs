@@ -1,21 +1,47 @@
 use ansi_term::Style;
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use options::{SizeFormat, TimeType};
+use options::{ChecksumAlgorithm, PermissionsFormat, SizeOptions, TimeType};
 
 
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum Column {
-    Permissions,
-    FileSize(SizeFormat),
+    Rank,
+    Permissions(PermissionsFormat),
+    Access,
+    FileSize(SizeOptions),
+    SizeBar,
+    SizePercent,
+    FsPercent,
+    Sparseness,
+    LargeFlag(u64),
+    LineCount,
+    Checksum(ChecksumAlgorithm),
     Timestamp(TimeType),
+    LastActivity,
+    MetaChanged,
+    Lifetime,
+    AgeDays,
+    AgeGradient,
     Blocks,
     User,
     Group,
     HardLinks,
+    LinkDepth,
     Inode,
+    ParentInode,
+    Mount,
+    XattrCount,
+    Capabilities,
+    Descendants,
+    SubdirCount,
+    MimeType,
+    Interpreter,
 
     GitStatus,
+    GitSummary,
+    GitIgnored,
+    GitLastCommit,
 }
 
 /// Each column can pick its own **Alignment**. Usually, numbers are
@@ -30,30 +56,185 @@ impl Column {
     /// Get the alignment this column should use.
     pub fn alignment(&self) -> Alignment {
         match *self {
+            Column::Rank        => Alignment::Right,
             Column::FileSize(_) => Alignment::Right,
             Column::HardLinks   => Alignment::Right,
+            Column::LinkDepth   => Alignment::Right,
             Column::Inode       => Alignment::Right,
+            Column::ParentInode => Alignment::Right,
             Column::Blocks      => Alignment::Right,
+            Column::XattrCount  => Alignment::Right,
+            Column::Descendants => Alignment::Right,
+            Column::SubdirCount => Alignment::Right,
+            Column::LineCount   => Alignment::Right,
+            Column::SizePercent => Alignment::Right,
+            Column::FsPercent   => Alignment::Right,
+            Column::Sparseness  => Alignment::Right,
+            Column::AgeDays     => Alignment::Right,
+            Column::MetaChanged => Alignment::Right,
+            Column::Lifetime    => Alignment::Right,
             Column::GitStatus   => Alignment::Right,
             _                   => Alignment::Left,
         }
     }
 
+    /// Get the name used to refer to this type of column on the command
+    /// line. Every timestamp column shares the name `date`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Column::Rank          => "rank",
+            Column::Permissions(_) => "permissions",
+            Column::Access        => "access",
+            Column::FileSize(_)   => "size",
+            Column::SizeBar       => "size-bar",
+            Column::SizePercent   => "size-percent",
+            Column::FsPercent     => "fs-percent",
+            Column::Sparseness    => "sparseness",
+            Column::LargeFlag(_)  => "large",
+            Column::LineCount     => "lines",
+            Column::Checksum(_)   => "checksum",
+            Column::Timestamp(_)  => "date",
+            Column::LastActivity  => "last-activity",
+            Column::MetaChanged   => "meta-changed",
+            Column::Lifetime      => "lifetime",
+            Column::AgeDays       => "age",
+            Column::AgeGradient   => "age-gradient",
+            Column::Blocks        => "blocks",
+            Column::User          => "user",
+            Column::Group         => "group",
+            Column::HardLinks     => "links",
+            Column::LinkDepth     => "link-depth",
+            Column::Inode         => "inode",
+            Column::ParentInode   => "parent-inode",
+            Column::Mount         => "mount",
+            Column::XattrCount    => "xattrs",
+            Column::Capabilities  => "capabilities",
+            Column::Descendants   => "total",
+            Column::SubdirCount   => "subdirs",
+            Column::MimeType      => "mime",
+            Column::Interpreter   => "interpreter",
+            Column::GitStatus     => "git",
+            Column::GitSummary    => "git-summary",
+            Column::GitIgnored    => "git-ignored",
+            Column::GitLastCommit => "git-commit",
+        }
+    }
+
+    /// Whether the given word is the name of any type of column, as
+    /// returned by `name`.
+    pub fn is_name(word: &str) -> bool {
+        Column::one_of_each().iter().any(|column| column.name() == word)
+    }
+
+    /// One column of every type, with whatever options, so the names can
+    /// be looked up without being written out a second time.
+    fn one_of_each() -> Vec<Column> {
+        vec![
+            Column::Rank, Column::Permissions(PermissionsFormat::Symbolic), Column::Access,
+            Column::FileSize(SizeOptions::default()), Column::SizeBar, Column::SizePercent,
+            Column::FsPercent, Column::Sparseness, Column::LargeFlag(0), Column::LineCount,
+            Column::Checksum(ChecksumAlgorithm::Crc32), Column::Timestamp(TimeType::FileModified),
+            Column::LastActivity, Column::MetaChanged, Column::Lifetime, Column::AgeDays,
+            Column::AgeGradient, Column::Blocks, Column::User, Column::Group, Column::HardLinks,
+            Column::LinkDepth, Column::Inode, Column::ParentInode, Column::Mount,
+            Column::XattrCount, Column::Capabilities, Column::Descendants, Column::SubdirCount,
+            Column::MimeType, Column::Interpreter,
+            Column::GitStatus, Column::GitSummary, Column::GitIgnored, Column::GitLastCommit,
+        ]
+    }
+
     /// Get the text that should be printed at the top, when the user elects
     /// to have a header row printed.
     pub fn header(&self) -> &'static str {
         match *self {
-            Column::Permissions   => "Permissions",
+            Column::Rank          => "#",
+            Column::Permissions(_) => "Permissions",
+            Column::Access        => "Access",
             Column::FileSize(_)   => "Size",
+            Column::SizeBar       => "Size",
+            Column::SizePercent   => "Share",
+            Column::FsPercent     => "Disk",
+            Column::Sparseness    => "Sparse",
+            Column::LargeFlag(_)  => "Large",
+            Column::LineCount     => "Lines",
+            Column::Checksum(_)   => "Checksum",
             Column::Timestamp(t)  => t.header(),
+            Column::LastActivity  => "Last Activity",
+            Column::MetaChanged   => "Meta",
+            Column::Lifetime      => "Lifetime",
+            Column::AgeDays       => "Age",
+            Column::AgeGradient   => "Age",
             Column::Blocks        => "Blocks",
             Column::User          => "User",
             Column::Group         => "Group",
             Column::HardLinks     => "Links",
+            Column::LinkDepth     => "Hops",
             Column::Inode         => "inode",
+            Column::ParentInode   => "parent",
+            Column::Mount         => "Mount",
+            Column::XattrCount    => "Xattrs",
+            Column::Capabilities  => "Capabilities",
+            Column::Descendants   => "Total",
+            Column::SubdirCount   => "Dirs",
+            Column::MimeType      => "Type",
+            Column::Interpreter   => "Interpreter",
             Column::GitStatus     => "Git",
+            Column::GitSummary    => "Git",
+            Column::GitIgnored    => "Ignored",
+            Column::GitLastCommit => "Commit",
+        }
+    }
+
+    /// Get how willing this column is to be truncated when a row doesn't
+    /// fit into the terminal. Columns with lower numbers get cut first; the
+    /// permissions are kept for as long as possible.
+    pub fn truncation_priority(&self) -> usize {
+        match *self {
+            Column::Timestamp(_)  => 0,
+            Column::LastActivity  => 0,
+            Column::MetaChanged   => 0,
+            Column::Lifetime      => 0,
+            Column::AgeDays       => 0,
+            Column::AgeGradient   => 0,
+            Column::Group         => 1,
+            Column::MimeType      => 1,
+            Column::Interpreter   => 1,
+            Column::Blocks        => 2,
+            Column::HardLinks     => 2,
+            Column::LinkDepth     => 2,
+            Column::Inode         => 2,
+            Column::ParentInode   => 2,
+            Column::Mount         => 2,
+            Column::XattrCount    => 2,
+            Column::Capabilities  => 2,
+            Column::Descendants   => 2,
+            Column::SubdirCount   => 2,
+            Column::GitStatus     => 2,
+            Column::GitSummary    => 2,
+            Column::GitIgnored    => 2,
+            Column::GitLastCommit => 1,
+            Column::Rank          => 1,
+            Column::User          => 3,
+            Column::Access        => 3,
+            Column::FileSize(_)   => 4,
+            Column::SizeBar       => 1,
+            Column::SizePercent   => 1,
+            Column::FsPercent     => 1,
+            Column::Sparseness    => 1,
+            Column::LargeFlag(_)  => 1,
+            Column::LineCount     => 1,
+            Column::Checksum(_)   => 1,
+            Column::Permissions(_) => 5,
         }
     }
+
+    /// Order the given columns by their truncation priority. Columns with
+    /// the same priority are truncated from the right.
+    pub fn truncation_order(columns: &[Column]) -> Vec<Column> {
+        let mut order: Vec<Column> = columns.iter().rev().cloned().collect();
+        order.sort_by(|a, b| a.truncation_priority().cmp(&b.truncation_priority()));
+        order
+    }
 }
 
 
@@ -71,6 +252,13 @@ impl Cell {
         }
     }
 
+    /// A cell containing nothing but the given number of spaces.
+    pub fn blank(width: usize) -> Cell {
+        let mut cell = Cell::empty();
+        cell.add_spaces(width);
+        cell
+    }
+
     pub fn paint(style: Style, string: &str) -> Cell {
         Cell {
             text: style.paint(string).to_string(),
@@ -89,4 +277,117 @@ impl Cell {
         self.length += other.length;
         self.text.push_str(&*other.text);
     }
+
+    /// Shorten this cell so it takes up at most the given number of
+    /// characters, replacing the last one with an ellipsis if anything had
+    /// to be cut off.
+    ///
+    /// The text may contain ANSI escape codes, which take up no width, so
+    /// these get copied across instead of being counted. If any were
+    /// present, the style gets reset at the end, in case the cut happened
+    /// in the middle of a coloured section.
+    pub fn truncate(&mut self, width: usize) {
+        if self.length <= width {
+            return;
+        }
+
+        let mut text = String::new();
+        let mut length = 0;
+        let mut in_escape = false;
+        let mut styled = false;
+        let available = if width == 0 { 0 } else { width - 1 };
+
+        for c in self.text.chars() {
+            if in_escape {
+                text.push(c);
+                if c == 'm' { in_escape = false; }
+            }
+            else if c == '\x1B' {
+                text.push(c);
+                in_escape = true;
+                styled = true;
+            }
+            else {
+                let char_width = UnicodeWidthChar::width(c).unwrap_or(0);
+                if length + char_width > available { break; }
+
+                text.push(c);
+                length += char_width;
+            }
+        }
+
+        if width != 0 {
+            text.push('…');
+            length += 1;
+        }
+
+        if styled {
+            text.push_str("\x1B[0m");
+        }
+
+        self.text = text;
+        self.length = length;
+    }
+
+    /// This cell's text without any of its ANSI escape codes, as it
+    /// appears on the terminal.
+    pub fn plain_text(&self) -> String {
+        let mut text = String::new();
+        let mut in_escape = false;
+
+        for c in self.text.chars() {
+            if in_escape {
+                if c == 'm' { in_escape = false; }
+            }
+            else if c == '\x1B' {
+                in_escape = true;
+            }
+            else {
+                text.push(c);
+            }
+        }
+
+        text
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::{Cell, Column};
+    use ansi_term::Colour::Red;
+
+    #[test]
+    fn names() {
+        assert!(Column::is_name("total"));
+        assert!(Column::is_name("git-commit"));
+        assert!(!Column::is_name("name"));
+    }
+
+    #[test]
+    fn truncate_plain() {
+        let mut cell = Cell { text: "abcdef".to_string(), length: 6 };
+        cell.truncate(4);
+        assert_eq!(cell, Cell { text: "abc…".to_string(), length: 4 });
+    }
+
+    #[test]
+    fn truncate_fits() {
+        let mut cell = Cell { text: "abc".to_string(), length: 3 };
+        cell.truncate(3);
+        assert_eq!(cell, Cell { text: "abc".to_string(), length: 3 });
+    }
+
+    #[test]
+    fn plain_text() {
+        let cell = Cell::paint(Red.bold(), "abc");
+        assert_eq!(cell.plain_text(), "abc".to_string());
+    }
+
+    #[test]
+    fn truncate_styled() {
+        let mut cell = Cell::paint(Red.normal(), "abcdef");
+        cell.truncate(3);
+        assert_eq!(cell, Cell { text: "\x1B[31mab…\x1B[0m".to_string(), length: 3 });
+    }
 }
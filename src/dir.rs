@@ -1,5 +1,6 @@
 use std::io;
 use std::fs;
+use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
 use std::slice::Iter as SliceIter;
 
@@ -21,6 +22,10 @@ pub struct Dir {
     /// The path that was read.
     pub path: PathBuf,
 
+    /// The inode of the directory itself, if it could be found out, which
+    /// gets looked up once here rather than for every file in it.
+    pub inode: Option<u64>,
+
     /// Holds a `Git` object if scanning for Git repositories is switched on,
     /// and this directory happens to contain one.
     git: Option<Git>,
@@ -32,14 +37,19 @@ impl Dir {
     /// pointed to by the given path. Fails if the directory can't be read, or
     /// isn't actually a directory, or if there's an IO error that occurs
     /// while scanning.
-    pub fn read_dir(path: &Path, git: bool) -> io::Result<Dir> {
+    ///
+    /// If `git` is true, the directory's Git repository gets scanned for
+    /// statuses. If `ignored` is also true, the files it ignores get found
+    /// too, and if `commits` is, each file's last commit.
+    pub fn read_dir(path: &Path, git: bool, ignored: bool, commits: bool) -> io::Result<Dir> {
         let reader = try!(fs::read_dir(path));
         let contents = try!(reader.map(|e| e.map(|e| e.path())).collect());
 
         Ok(Dir {
             contents: contents,
             path: path.to_path_buf(),
-            git: if git { Git::scan(path).ok() } else { None },
+            inode: fs::metadata(path).ok().map(|m| m.ino()),
+            git: if git { Git::scan(path, ignored, commits).ok() } else { None },
         })
     }
 
@@ -75,6 +85,22 @@ impl Dir {
             (&None, _)               => fields::Git::empty()
         }
     }
+
+    /// Whether the given file is ignored by this directory's Git repository.
+    pub fn git_ignored(&self, path: &Path) -> fields::GitIgnored {
+        match self.git {
+            Some(ref git) => fields::GitIgnored(git.ignored(path)),
+            None          => fields::GitIgnored(false),
+        }
+    }
+
+    /// Get the last commit to have touched the given file, if there is one.
+    pub fn git_last_commit(&self, path: &Path, prefix_lookup: bool) -> Option<fields::GitCommit> {
+        match self.git {
+            Some(ref git) => git.last_commit(path, prefix_lookup),
+            None          => None,
+        }
+    }
 }
 
 
@@ -0,0 +1,93 @@
+//! Finding out when files were created.
+//!
+//! The `stat` family of calls has no field for a file's birth time, so on
+//! Linux it has to be asked for with `statx`, which only kernels from 4.11
+//! onwards have, and only some filesystems fill in. Older C libraries
+//! don't have a wrapper for it, so it gets made as a raw system call.
+
+use std::path::Path;
+
+
+/// The time the file at the given path was created, in seconds and
+/// nanoseconds, or `None` if the system or filesystem doesn't record it.
+/// Symlinks aren't followed.
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn birth_time(path: &Path) -> Option<(i64, i64)> {
+    use std::mem;
+    use libc::{c_char, c_int, c_long, c_uint};
+
+    #[cfg(target_arch = "x86_64")]  const SYS_STATX: c_long = 332;
+    #[cfg(target_arch = "aarch64")] const SYS_STATX: c_long = 291;
+
+    const AT_FDCWD: c_int = -100;
+    const AT_SYMLINK_NOFOLLOW: c_int = 0x100;
+    const STATX_BTIME: c_uint = 0x800;
+
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct StatxTimestamp {
+        tv_sec:     i64,
+        tv_nsec:    u32,
+        __reserved: i32,
+    }
+
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct Statx {
+        stx_mask:            u32,
+        stx_blksize:         u32,
+        stx_attributes:      u64,
+        stx_nlink:           u32,
+        stx_uid:             u32,
+        stx_gid:             u32,
+        stx_mode:            u16,
+        __spare0:            u16,
+        stx_ino:             u64,
+        stx_size:            u64,
+        stx_blocks:          u64,
+        stx_attributes_mask: u64,
+        stx_atime:           StatxTimestamp,
+        stx_btime:           StatxTimestamp,
+        stx_ctime:           StatxTimestamp,
+        stx_mtime:           StatxTimestamp,
+        stx_rdev_major:      u32,
+        stx_rdev_minor:      u32,
+        stx_dev_major:       u32,
+        stx_dev_minor:       u32,
+        __spare2:            [u64; 14],
+    }
+
+    // The kernel's struct is 256 bytes, and `transmute` only compiles
+    // between types of the same size, so this stops the build if the
+    // layout above is ever wrong.
+    #[allow(dead_code)]
+    fn statx_is_256_bytes(stats: Statx) -> [u8; 256] {
+        unsafe { mem::transmute(stats) }
+    }
+
+    extern "C" {
+        fn syscall(number: c_long, ...) -> c_long;
+    }
+
+    let c_path = match path.as_os_str().to_cstring() {
+        Some(c_path)  => c_path,
+        None          => return None,
+    };
+
+    let mut stats: Statx = unsafe { mem::zeroed() };
+    let result = unsafe {
+        syscall(SYS_STATX, AT_FDCWD, c_path.as_ptr() as *const c_char, AT_SYMLINK_NOFOLLOW, STATX_BTIME, &mut stats as *mut Statx)
+    };
+
+    // The mask says which fields the filesystem actually filled in.
+    if result != 0 || stats.stx_mask & STATX_BTIME == 0 {
+        return None;
+    }
+
+    Some((stats.stx_btime.tv_sec, stats.stx_btime.tv_nsec as i64))
+}
+
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+pub fn birth_time(_: &Path) -> Option<(i64, i64)> {
+    None
+}
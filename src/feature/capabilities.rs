@@ -0,0 +1,159 @@
+//! Linux file capabilities.
+//!
+//! Capabilities split the powers of root up into pieces, so a program can
+//! be given only the ones it needs, such as binding to a low port, instead
+//! of being made setuid. They don't show up in a file's permission bits:
+//! the kernel keeps them in the `security.capability` extended attribute,
+//! as a `vfs_cap_data` structure.
+
+use std::path::Path;
+
+#[cfg(target_os = "linux")]
+use feature::xattr;
+
+
+/// The extended attribute that Linux keeps a file's capabilities in, as a
+/// NUL-terminated name.
+#[cfg(target_os = "linux")]
+const CAPABILITY_ATTRIBUTE: &'static [u8] = b"security.capability\0";
+
+/// The names of the capabilities, without their `cap_` prefixes, in the
+/// order of their bit numbers.
+const NAMES: &'static [&'static str] = &[
+    "chown", "dac_override", "dac_read_search", "fowner", "fsetid", "kill",
+    "setgid", "setuid", "setpcap", "linux_immutable", "net_bind_service",
+    "net_broadcast", "net_admin", "net_raw", "ipc_lock", "ipc_owner",
+    "sys_module", "sys_rawio", "sys_chroot", "sys_ptrace", "sys_pacct",
+    "sys_admin", "sys_boot", "sys_nice", "sys_resource", "sys_time",
+    "sys_tty_config", "mknod", "lease", "audit_write", "audit_control",
+    "setfcap", "mac_override", "mac_admin", "syslog", "wake_alarm",
+    "block_suspend", "audit_read", "perfmon", "bpf", "checkpoint_restore",
+];
+
+/// Some capabilities that all get granted in the same way, such as
+/// `cap_net_raw,cap_net_admin=ep`, which is how `getcap` groups them.
+#[derive(PartialEq, Debug, Clone)]
+pub struct CapabilitySet {
+    pub names: Vec<String>,
+
+    /// Which of the effective, inheritable, and permitted sets these
+    /// capabilities are in, as the letters `e`, `i`, and `p`.
+    pub flags: String,
+}
+
+/// The capabilities the file at the given path grants, which is empty if
+/// it doesn't have any or they can't be read.
+#[cfg(target_os = "linux")]
+pub fn file_capabilities(path: &Path) -> Vec<CapabilitySet> {
+    match xattr::read_attr(path, CAPABILITY_ATTRIBUTE) {
+        Some(value)  => decode(&value),
+        None         => Vec::new(),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn file_capabilities(_: &Path) -> Vec<CapabilitySet> {
+    Vec::new()
+}
+
+/// Read a little-endian 32-bit number from the start of the given bytes.
+fn le_u32(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32) | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24
+}
+
+/// Decode a `vfs_cap_data` value. Its first word holds the revision and
+/// the effective flag; the first revision has one pair of permitted and
+/// inheritable words after it, and later ones have two. Values that are
+/// too short or of an unknown revision decode to nothing.
+fn decode(value: &[u8]) -> Vec<CapabilitySet> {
+    if value.len() < 4 {
+        return Vec::new();
+    }
+
+    let magic = le_u32(value);
+    let words = match magic & 0xFF00_0000 {
+        0x0100_0000              => 1,
+        0x0200_0000 | 0x0300_0000 => 2,
+        _                        => return Vec::new(),
+    };
+
+    if value.len() < 4 + words * 8 {
+        return Vec::new();
+    }
+
+    let effective = magic & 1 != 0;
+    let mut permitted = 0u64;
+    let mut inheritable = 0u64;
+
+    for word in 0 .. words {
+        let offset = 4 + word * 8;
+        permitted   |= (le_u32(&value[offset ..])     as u64) << (32 * word);
+        inheritable |= (le_u32(&value[offset + 4 ..]) as u64) << (32 * word);
+    }
+
+    let mut sets: Vec<CapabilitySet> = Vec::new();
+
+    for bit in 0 .. 64 {
+        let mask = 1u64 << bit;
+        if (permitted | inheritable) & mask == 0 {
+            continue;
+        }
+
+        let mut flags = String::new();
+        if effective && permitted & mask != 0 { flags.push('e') }
+        if inheritable & mask != 0            { flags.push('i') }
+        if permitted & mask != 0              { flags.push('p') }
+
+        let name = match NAMES.get(bit) {
+            Some(name)  => format!("cap_{}", name),
+            None        => format!("cap_{}", bit),
+        };
+
+        match sets.iter().position(|set| set.flags == flags) {
+            Some(index)  => sets[index].names.push(name),
+            None         => sets.push(CapabilitySet { names: vec![ name ], flags: flags }),
+        }
+    }
+
+    sets
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::{decode, CapabilitySet};
+
+    fn set(names: &[&str], flags: &str) -> CapabilitySet {
+        CapabilitySet { names: names.iter().map(|n| n.to_string()).collect(), flags: flags.to_string() }
+    }
+
+    #[test]
+    fn net_bind_service() {
+        // What `setcap cap_net_bind_service=ep` writes.
+        let value = [ 0x01, 0, 0, 0x02,  0, 0x04, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0 ];
+        assert_eq!(decode(&value), vec![ set(&[ "cap_net_bind_service" ], "ep") ]);
+    }
+
+    #[test]
+    fn grouped_by_flags() {
+        let value = [ 0, 0, 0, 0x02,  0x01, 0x20, 0, 0,  0x01, 0, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0 ];
+        assert_eq!(decode(&value), vec![ set(&[ "cap_chown" ], "ip"), set(&[ "cap_net_raw" ], "p") ]);
+    }
+
+    #[test]
+    fn high_word() {
+        let value = [ 0, 0, 0, 0x02,  0, 0, 0, 0,  0, 0, 0, 0,  0x04, 0, 0, 0,  0, 0, 0, 0 ];
+        assert_eq!(decode(&value), vec![ set(&[ "cap_syslog" ], "p") ]);
+    }
+
+    #[test]
+    fn unknown_revision() {
+        let value = [ 0, 0, 0, 0x09,  0xFF, 0, 0, 0,  0, 0, 0, 0 ];
+        assert_eq!(decode(&value), Vec::new());
+    }
+
+    #[test]
+    fn too_short() {
+        assert_eq!(decode(&[ 0x01, 0, 0, 0x02,  0x01 ]), Vec::new());
+    }
+}
@@ -0,0 +1,238 @@
+//! Checksums of file contents, for telling files apart at a glance.
+//!
+//! Both algorithms are implemented here rather than pulled in from a crate,
+//! as they're short, and only used to show a file's fingerprint: neither
+//! is any good for security.
+
+use std::io::{self, Read};
+
+use options::ChecksumAlgorithm;
+
+
+/// Compute the checksum of everything the given reader has to read with
+/// the given algorithm, as a string of lowercase hexadecimal digits. The
+/// contents get read a buffer at a time, so they never all have to be in
+/// memory at once.
+pub fn hex_digest<R: Read>(algorithm: ChecksumAlgorithm, reader: R) -> io::Result<String> {
+    match algorithm {
+        ChecksumAlgorithm::Crc32  => {
+            let mut crc = !0u32;
+            try!(each_chunk(reader, |bytes| crc = crc32_update(crc, bytes)));
+            Ok(format!("{:08x}", !crc))
+        },
+        ChecksumAlgorithm::Md5    => {
+            let mut md5 = Md5::new();
+            try!(each_chunk(reader, |bytes| md5.update(bytes)));
+
+            let mut hex = String::new();
+            for byte in md5.finish().iter() {
+                hex.push_str(&format!("{:02x}", byte));
+            }
+            Ok(hex)
+        },
+    }
+}
+
+/// Read everything from the given reader into a fixed-size buffer, one
+/// bufferful at a time, passing each one to the given function.
+fn each_chunk<R: Read, F: FnMut(&[u8])>(mut reader: R, mut f: F) -> io::Result<()> {
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0)   => return Ok(()),
+            Ok(n)   => f(&buffer[.. n]),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+            Err(e)  => return Err(e),
+        }
+    }
+}
+
+/// Carry on a CRC-32 with some more bytes, using the same polynomial as
+/// zip and gzip do. The CRC starts off with every bit set, and gets them
+/// all flipped at the end.
+fn crc32_update(mut crc: u32, bytes: &[u8]) -> u32 {
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0 .. 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 }
+                             else { crc >> 1 };
+        }
+    }
+
+    crc
+}
+
+/// An MD5 digest in progress, as described in RFC 1321. Bytes are
+/// processed in 64-byte blocks, so any left over at the end of an update
+/// are kept until there's a whole block of them.
+struct Md5 {
+    state: [u32; 4],
+    pending: Vec<u8>,
+    length: u64,
+    constants: Vec<u32>,
+}
+
+impl Md5 {
+    fn new() -> Md5 {
+        Md5 {
+            state: [ 0x67452301u32, 0xefcdab89, 0x98badcfe, 0x10325476 ],
+            pending: Vec::new(),
+            length: 0,
+
+            // Each round's constant is taken from the sine of its number.
+            constants: (0 .. 64).map(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32).collect(),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.length = self.length.wrapping_add(bytes.len() as u64);
+        self.push(bytes);
+    }
+
+    /// Add bytes to the message without counting them towards its length,
+    /// processing every whole block there is.
+    fn push(&mut self, bytes: &[u8]) {
+        self.pending.extend(bytes.iter().cloned());
+
+        let whole = self.pending.len() / 64 * 64;
+        for block in self.pending[.. whole].chunks(64) {
+            md5_block(&mut self.state, &self.constants, block);
+        }
+
+        self.pending = self.pending[whole ..].to_vec();
+    }
+
+    /// Pad the message with a single set bit, then zeroes, until it's
+    /// eight bytes short of a multiple of 64, then its length in bits,
+    /// and return the digest.
+    fn finish(mut self) -> [u8; 16] {
+        let bit_length = self.length.wrapping_mul(8);
+
+        let mut padding = vec![ 0x80u8 ];
+        while (self.pending.len() + padding.len()) % 64 != 56 {
+            padding.push(0);
+        }
+
+        for i in 0 .. 8 {
+            padding.push((bit_length >> (8 * i)) as u8);
+        }
+
+        self.push(&padding);
+
+        let mut digest = [0u8; 16];
+        for (i, byte) in digest.iter_mut().enumerate() {
+            *byte = (self.state[i / 4] >> (8 * (i % 4))) as u8;
+        }
+
+        digest
+    }
+}
+
+/// Run one 64-byte block through the MD5 rounds, adding the result to the
+/// given state.
+fn md5_block(state: &mut [u32; 4], constants: &[u32], block: &[u8]) {
+    const SHIFTS: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+        5,  9, 14, 20, 5,  9, 14, 20, 5,  9, 14, 20, 5,  9, 14, 20,
+        4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
+        6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+
+    let mut words = [0u32; 16];
+    for (i, word) in words.iter_mut().enumerate() {
+        *word = (block[i * 4] as u32)
+              | (block[i * 4 + 1] as u32) << 8
+              | (block[i * 4 + 2] as u32) << 16
+              | (block[i * 4 + 3] as u32) << 24;
+    }
+
+    let (mut a, mut b, mut c, mut d) = (state[0], state[1], state[2], state[3]);
+
+    for i in 0 .. 64 {
+        let (f, g) = match i {
+            0  ... 15 => ((b & c) | (!b & d), i),
+            16 ... 31 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            32 ... 47 => (b ^ c ^ d,          (3 * i + 5) % 16),
+            _         => (c ^ (b | !d),       (7 * i) % 16),
+        };
+
+        let f = f.wrapping_add(a).wrapping_add(constants[i]).wrapping_add(words[g]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(f.rotate_left(SHIFTS[i]));
+    }
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::hex_digest;
+    use options::ChecksumAlgorithm::{self, Crc32, Md5};
+    use std::io::{self, Read};
+
+    fn digest(algorithm: ChecksumAlgorithm, bytes: &[u8]) -> String {
+        hex_digest(algorithm, bytes).unwrap()
+    }
+
+    /// A reader that only gives out a few bytes at a time, so digests get
+    /// split across many reads.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = [ 7, buf.len(), self.0.len() ].iter().cloned().min().unwrap();
+            for (to, from) in buf.iter_mut().zip(self.0[.. count].iter()) {
+                *to = *from;
+            }
+
+            self.0 = &self.0[count ..];
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(digest(Crc32, b"123456789"), "cbf43926");
+    }
+
+    #[test]
+    fn crc32_empty() {
+        assert_eq!(digest(Crc32, b""), "00000000");
+    }
+
+    #[test]
+    fn md5_empty() {
+        assert_eq!(digest(Md5, b""), "d41d8cd98f00b204e9800998ecf8427e");
+    }
+
+    #[test]
+    fn md5_abc() {
+        assert_eq!(digest(Md5, b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+    }
+
+    #[test]
+    fn md5_several_chunks() {
+        let bytes: Vec<u8> = b"1234567890".iter().cycle().take(80).cloned().collect();
+        assert_eq!(digest(Md5, &bytes), "57edf4a22be3c955ac49da2e2107b67a");
+    }
+
+    #[test]
+    fn split_across_reads() {
+        let bytes: Vec<u8> = b"1234567890".iter().cycle().take(80).cloned().collect();
+        assert_eq!(hex_digest(Md5, Trickle(&bytes)).unwrap(), "57edf4a22be3c955ac49da2e2107b67a");
+        assert_eq!(hex_digest(Crc32, Trickle(b"123456789")).unwrap(), "cbf43926");
+    }
+
+    #[test]
+    fn larger_than_buffer() {
+        let bytes = vec![ b'a'; 20_000 ];
+        assert_eq!(digest(Md5, &bytes), hex_digest(Md5, Trickle(&bytes)).unwrap());
+    }
+}
@@ -0,0 +1,98 @@
+//! Looking up users' full names.
+//!
+//! The users crate only keeps a user's login name from their password
+//! entry, so the GECOS field has to be looked up separately. By
+//! convention, its first comma-separated part is the user's full name,
+//! and the rest is their office, phone numbers, and so on.
+
+use std::ffi::CStr;
+use std::mem;
+use std::ptr;
+
+use libc::{c_char, c_int, size_t};
+#[cfg(target_os = "macos")] use libc::time_t;
+use users::{gid_t, uid_t};
+
+
+/// The layout of the C library's `struct passwd`, which the libc crate
+/// doesn't have.
+#[repr(C)]
+#[allow(dead_code, non_camel_case_types)]
+pub struct c_passwd {
+    pub pw_name:    *const c_char,
+    pub pw_passwd:  *const c_char,
+    pub pw_uid:     uid_t,
+    pub pw_gid:     gid_t,
+    #[cfg(target_os = "macos")] pub pw_change: time_t,
+    #[cfg(target_os = "macos")] pub pw_class:  *const c_char,
+    pub pw_gecos:   *const c_char,
+    pub pw_dir:     *const c_char,
+    pub pw_shell:   *const c_char,
+    #[cfg(target_os = "macos")] pub pw_expire: time_t,
+}
+
+extern "C" {
+    fn getpwuid_r(uid: uid_t, pwd: *mut c_passwd, buf: *mut c_char, buflen: size_t, result: *mut *mut c_passwd) -> c_int;
+}
+
+/// How big a buffer to give `getpwuid_r` to put the entry's strings in.
+/// Entries that don't fit are treated as missing.
+const BUFFER_SIZE: usize = 4096;
+
+
+/// Look up the full name of the user with the given ID, returning `None`
+/// if there's no such user or their GECOS field has no name in it. This
+/// goes through the C library, so users from LDAP and the like get found
+/// too. The re-entrant call is used, as files get examined on more than
+/// one thread at once.
+pub fn full_name(uid: uid_t) -> Option<String> {
+    let mut passwd: c_passwd = unsafe { mem::zeroed() };
+    let mut buffer: Vec<c_char> = vec![ 0; BUFFER_SIZE ];
+    let mut result = ptr::null_mut();
+
+    let gecos = unsafe {
+        let error = getpwuid_r(uid, &mut passwd, buffer.as_mut_ptr(), BUFFER_SIZE as size_t, &mut result);
+        if error != 0 || result.is_null() || passwd.pw_gecos.is_null() {
+            return None;
+        }
+
+        CStr::from_ptr(passwd.pw_gecos).to_string_lossy().into_owned()
+    };
+
+    parse_gecos(&gecos)
+}
+
+/// Take the name out of a GECOS field, which is everything before the
+/// first comma.
+fn parse_gecos(gecos: &str) -> Option<String> {
+    let name = gecos.split(',').next().unwrap_or("").trim();
+
+    if name.is_empty() {
+        None
+    }
+    else {
+        Some(name.to_string())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::parse_gecos;
+
+    #[test]
+    fn name_only() {
+        assert_eq!(parse_gecos("Enoch Root"), Some("Enoch Root".to_string()));
+    }
+
+    #[test]
+    fn name_and_office() {
+        assert_eq!(parse_gecos("Enoch Root,Room 101,555-0100,"), Some("Enoch Root".to_string()));
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(parse_gecos(""), None);
+        assert_eq!(parse_gecos(",,,"), None);
+    }
+}
@@ -1,4 +1,9 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Once, ONCE_INIT};
 
 use git2;
 
@@ -8,24 +13,58 @@ use file::fields;
 /// Container of Git statuses for all the files in this folder's Git repository.
 pub struct Git {
     statuses: Vec<(PathBuf, git2::Status)>,
+
+    /// The paths that the repository ignores, if these were asked for when
+    /// scanning. Everything inside an ignored directory is ignored too.
+    ignored: HashSet<PathBuf>,
+
+    /// The most recent commit to have touched each file, if these were
+    /// asked for when scanning. Every directory in a repository shares
+    /// the same map.
+    commits: Arc<CommitMap>,
 }
 
+/// The most recent commit to have touched each file, keyed by its path.
+type CommitMap = HashMap<PathBuf, fields::GitCommit>;
+
 impl Git {
 
     /// Discover a Git repository on or above this directory, scanning it for
-    /// the files' statuses if one is found.
-    pub fn scan(path: &Path) -> Result<Git, git2::Error> {
+    /// the files' statuses if one is found. Ignored files are only looked
+    /// for if `ignored` is true, and the history is only searched for each
+    /// file's last commit if `commits` is true, as both of these are slow.
+    pub fn scan(path: &Path, ignored: bool, commits: bool) -> Result<Git, git2::Error> {
         let repo = try!(git2::Repository::discover(path));
         let workdir = match repo.workdir() {
             Some(w) => w,
-            None => return Ok(Git { statuses: vec![] }),  // bare repo
+            None => return Ok(Git { statuses: vec![], ignored: HashSet::new(), commits: Arc::new(HashMap::new()) }),  // bare repo
         };
 
-        let statuses = try!(repo.statuses(None)).iter()
-                                                .map(|e| (workdir.join(Path::new(e.path().unwrap())), e.status()))
-                                                .collect();
+        // Ignored files get kept apart from the rest, so they can be told
+        // apart from unmodified ones without making any directory's status
+        // look changed. Their contents aren't recursed into.
+        let mut opts = git2::StatusOptions::new();
+        let _ = opts.include_ignored(ignored);
+
+        let mut statuses = Vec::new();
+        let mut ignored_paths = HashSet::new();
+        for entry in try!(repo.statuses(Some(&mut opts))).iter() {
+            // Ignored directories come with a trailing slash, which gets
+            // taken off by putting the path back together from its parts.
+            let path: PathBuf = workdir.join(Path::new(entry.path().unwrap())).components().collect();
+
+            if entry.status().contains(git2::STATUS_IGNORED) {
+                let _ = ignored_paths.insert(path);
+            }
+            else {
+                statuses.push((path, entry.status()));
+            }
+        }
+
+        let commits = if commits { try!(cached_last_commits(&repo, workdir)) }
+                              else { Arc::new(HashMap::new()) };
 
-        Ok(Git { statuses: statuses })
+        Ok(Git { statuses: statuses, ignored: ignored_paths, commits: commits })
     }
 
     /// Get the status for the file at the given path, if present.
@@ -48,6 +87,119 @@ impl Git {
 
         fields::Git { staged: index_status(s), unstaged: working_tree_status(s) }
     }
+
+    /// Whether the file at the given path is ignored by the repository,
+    /// either directly or because it lives inside an ignored directory.
+    pub fn ignored(&self, path: &Path) -> bool {
+        let mut current = Some(path);
+        while let Some(path) = current {
+            if self.ignored.contains(path) {
+                return true;
+            }
+
+            current = path.parent();
+        }
+
+        false
+    }
+
+    /// Get the most recent commit that touched the file at the given path,
+    /// or, for a directory, the most recent commit that touched any file
+    /// inside it. Returns `None` for files that have never been committed.
+    pub fn last_commit(&self, path: &Path, prefix_lookup: bool) -> Option<fields::GitCommit> {
+        if prefix_lookup {
+            self.commits.iter()
+                        .filter(|&(p, _)| p.starts_with(path))
+                        .fold(None, |newest: Option<&fields::GitCommit>, (_, c)| match newest {
+                            Some(n) if n.time >= c.time => Some(n),
+                            _                           => Some(c),
+                        })
+                        .cloned()
+        }
+        else {
+            self.commits.get(path).cloned()
+        }
+    }
+}
+
+/// Find the last commits of the repository with the given working
+/// directory, or reuse them if another of its directories has already been
+/// scanned, as finding them means walking through its history.
+fn cached_last_commits(repo: &git2::Repository, workdir: &Path) -> Result<Arc<CommitMap>, git2::Error> {
+    static INIT: Once = ONCE_INIT;
+    static mut CACHE: *const Mutex<HashMap<PathBuf, Arc<CommitMap>>> = 0 as *const Mutex<HashMap<PathBuf, Arc<CommitMap>>>;
+
+    let cache = unsafe {
+        INIT.call_once(|| CACHE = mem::transmute(Box::new(Mutex::new(HashMap::new()))));
+        &*CACHE
+    };
+
+    // The lock is held while the history gets walked, so directories being
+    // scanned at the same time wait for the one walk, rather than each
+    // doing their own.
+    let mut cache = cache.lock().unwrap();
+    if let Some(commits) = cache.get(workdir) {
+        return Ok(commits.clone());
+    }
+
+    let commits = Arc::new(try!(last_commits(repo, workdir)));
+    let _ = cache.insert(workdir.to_path_buf(), commits.clone());
+    Ok(commits)
+}
+
+/// Find the most recent commit to have touched every file in the repository,
+/// by walking back through the history from `HEAD` and diffing each commit
+/// against its first parent.
+///
+/// This is *expensive*, as it can visit and diff every commit in the
+/// repository's history, so it should only be done when the last-commit
+/// column is actually going to be displayed. The walk stops as soon as
+/// every file in the index has been found, as nothing older can be the
+/// last commit to have touched any of them.
+fn last_commits(repo: &git2::Repository, workdir: &Path) -> Result<CommitMap, git2::Error> {
+    let mut commits = HashMap::new();
+
+    let mut remaining: HashSet<PathBuf> = try!(repo.index()).iter()
+                                              .map(|entry| workdir.join(OsStr::from_bytes(&entry.path)))
+                                              .collect();
+    if remaining.is_empty() {
+        return Ok(commits);  // nothing's tracked
+    }
+
+    let mut revwalk = try!(repo.revwalk());
+    revwalk.set_sorting(git2::SORT_TIME);
+    if revwalk.push_head().is_err() {
+        return Ok(commits);  // no commits yet
+    }
+
+    for oid in revwalk {
+        let commit = try!(repo.find_commit(oid));
+        let tree = try!(commit.tree());
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(try!(parent.tree())),
+            Err(_)     => None,
+        };
+
+        let diff = try!(repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None));
+        let id = commit.id().to_string();
+
+        for delta in diff.deltas() {
+            if let Some(path) = delta.new_file().path() {
+                let path = workdir.join(path);
+                let _ = remaining.remove(&path);
+                let _ = commits.entry(path).or_insert_with(|| fields::GitCommit {
+                    id:   id[.. 7].to_string(),
+                    time: commit.time().seconds(),
+                });
+            }
+        }
+
+        if remaining.is_empty() {
+            break;
+        }
+    }
+
+    Ok(commits)
 }
 
 /// The character to display if the file has been modified, but not staged.
@@ -0,0 +1,88 @@
+//! Finding which users have each group as their primary group.
+//!
+//! A group's entry only lists the users who are in it as a supplementary
+//! group, so the users whose primary group it is have to be found by
+//! going through every user instead. This goes through the C library, so
+//! users from LDAP and the like get found too, as long as the system is
+//! set up to let them be listed.
+
+use std::collections::HashMap;
+use std::ffi::CStr;
+
+use users::gid_t;
+
+use feature::gecos::c_passwd;
+
+
+extern "C" {
+    fn setpwent();
+    fn getpwent() -> *mut c_passwd;
+    fn endpwent();
+}
+
+
+/// Go through every user in the password database, returning a map of
+/// group IDs to the names of the users whose primary group they are. If
+/// the database can't be read, the map is empty.
+///
+/// The C library only keeps one place in the database at a time, so this
+/// mustn't be called from more than one thread at once; the table it's
+/// called from is only ever used by one thread at a time.
+pub fn primary_members() -> HashMap<gid_t, Vec<String>> {
+    let mut users = Vec::new();
+
+    unsafe {
+        setpwent();
+
+        loop {
+            let passwd = getpwent();
+            if passwd.is_null() {
+                break;
+            }
+
+            if !(*passwd).pw_name.is_null() {
+                let name = CStr::from_ptr((*passwd).pw_name).to_string_lossy().into_owned();
+                users.push(((*passwd).pw_gid, name));
+            }
+        }
+
+        endpwent();
+    }
+
+    group_members(users)
+}
+
+/// Collect the names of the given users by their primary group, keeping
+/// them in the order they came in.
+fn group_members<I>(users: I) -> HashMap<gid_t, Vec<String>>
+where I: IntoIterator<Item=(gid_t, String)> {
+    let mut members = HashMap::new();
+
+    for (gid, name) in users {
+        members.entry(gid).or_insert_with(Vec::new).push(name);
+    }
+
+    members
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::group_members;
+
+    #[test]
+    fn primary_groups() {
+        let members = group_members(vec![ (0,   "root".to_string()),
+                                          (100, "ann".to_string()),
+                                          (100, "bob".to_string()) ]);
+
+        assert_eq!(members[&0], vec![ "root".to_string() ]);
+        assert_eq!(members[&100], vec![ "ann".to_string(), "bob".to_string() ]);
+        assert_eq!(members.len(), 2);
+    }
+
+    #[test]
+    fn no_users() {
+        assert!(group_members(Vec::new()).is_empty());
+    }
+}
@@ -1,6 +1,24 @@
 // Extended attribute support
 pub mod xattr;
 
+// Mount table support
+pub mod mounts;
+
+// Primary group support
+pub mod groups;
+
+// Full name support
+pub mod gecos;
+
+// File birth time support
+pub mod birth;
+
+// Linux file capability support
+pub mod capabilities;
+
+// File checksum support
+pub mod checksum;
+
 // Git support
 
 #[cfg(feature="git")] mod git;
@@ -12,7 +30,7 @@ pub mod xattr;
 
 #[cfg(not(feature="git"))]
 impl Git {
-    pub fn scan(_: &Path) -> Result<Git, ()> {
+    pub fn scan(_: &Path, _: bool, _: bool) -> Result<Git, ()> {
         Err(())
     }
 
@@ -23,4 +41,12 @@ impl Git {
     pub fn dir_status(&self, path: &Path) -> fields::Git {
         self.status(path)
     }
+
+    pub fn ignored(&self, _: &Path) -> bool {
+        panic!("Tried to access a Git repo without Git support!");
+    }
+
+    pub fn last_commit(&self, _: &Path, _: bool) -> Option<fields::GitCommit> {
+        panic!("Tried to access a Git repo without Git support!");
+    }
 }
@@ -0,0 +1,178 @@
+//! Finding which mount point each device is mounted on.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+
+/// Read the system's mount table, returning a map of device numbers to the
+/// paths they're mounted on. When a device is mounted in more than one
+/// place, the first entry in the table wins. If the table can't be read,
+/// the map is empty.
+#[cfg(target_os = "linux")]
+pub fn mount_points() -> HashMap<u64, String> {
+    use std::fs::File;
+    use std::io::Read;
+
+    let mut contents = String::new();
+    match File::open("/proc/self/mountinfo").and_then(|mut f| f.read_to_string(&mut contents)) {
+        Ok(_)   => parse_mount_info(&contents),
+        Err(_)  => HashMap::new(),
+    }
+}
+
+/// Parse the contents of `/proc/self/mountinfo`. Each line already has the
+/// `major:minor` number of the device in its third field, and the mount
+/// point in its fifth, so none of the mount points need to be examined
+/// (which could hang on an unresponsive network filesystem).
+#[cfg(target_os = "linux")]
+fn parse_mount_info(contents: &str) -> HashMap<u64, String> {
+    let mut mounts = HashMap::new();
+
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split(' ').collect();
+        if fields.len() < 5 {
+            continue;
+        }
+
+        let mut numbers = fields[2].splitn(2, ':');
+        let device = match (numbers.next().and_then(|n| n.parse().ok()), numbers.next().and_then(|n| n.parse().ok())) {
+            (Some(major), Some(minor))  => make_device(major, minor),
+            _                           => continue,
+        };
+
+        let _ = mounts.entry(device).or_insert(unescape(fields[4]));
+    }
+
+    mounts
+}
+
+/// Combine a major and minor device number into the form that `stat`
+/// returns, the same way glibc's `makedev` does.
+#[cfg(target_os = "linux")]
+fn make_device(major: u64, minor: u64) -> u64 {
+    ((major & 0xfffff000) << 32) | ((major & 0xfff) << 8)
+        | ((minor & 0xffffff00) << 12) | (minor & 0xff)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn mount_points() -> HashMap<u64, String> {
+    HashMap::new()
+}
+
+/// The layout of glibc's `struct statvfs64`, which libc doesn't have a
+/// binding for. The block and file counts are always 64 bits wide, so big
+/// filesystems don't overflow them on 32-bit systems, where there's also an
+/// extra `int` after the filesystem ID. On 64-bit systems, this is the same
+/// as `struct statvfs`.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[allow(dead_code)]
+struct StatVfs {
+    f_bsize:    ::libc::c_ulong,
+    f_frsize:   ::libc::c_ulong,
+    f_blocks:   u64,
+    f_bfree:    u64,
+    f_bavail:   u64,
+    f_files:    u64,
+    f_ffree:    u64,
+    f_favail:   u64,
+    f_fsid:     ::libc::c_ulong,
+    #[cfg(target_pointer_width = "32")]
+    f_unused:   ::libc::c_int,
+    f_flag:     ::libc::c_ulong,
+    f_namemax:  ::libc::c_ulong,
+    f_spare:    [::libc::c_int; 6],
+}
+
+#[cfg(target_os = "linux")]
+extern "C" {
+    fn statvfs64(path: *const ::libc::c_char, buf: *mut StatVfs) -> ::libc::c_int;
+}
+
+/// The total capacity, in bytes, of the filesystem the given path is on,
+/// or `None` if it couldn't be found out.
+#[cfg(target_os = "linux")]
+pub fn filesystem_size(path: &Path) -> Option<u64> {
+    use std::mem;
+
+    let c_path = match path.as_os_str().to_cstring() {
+        Some(c_path)  => c_path,
+        None          => return None,
+    };
+
+    let mut stats: StatVfs = unsafe { mem::zeroed() };
+    if unsafe { statvfs64(c_path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+
+    Some(stats.f_blocks * stats.f_frsize as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn filesystem_size(_: &Path) -> Option<u64> {
+    None
+}
+
+/// The kernel escapes spaces, tabs, newlines, and backslashes in the mount
+/// table as three-digit octal sequences such as `\040`.
+#[cfg(target_os = "linux")]
+fn unescape(field: &str) -> String {
+    let mut result = String::new();
+    let mut chars = field.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let digits: String = chars.by_ref().take(3).collect();
+            match u8::from_str_radix(&digits, 8) {
+                Ok(byte)  => result.push(byte as char),
+                Err(_)    => { result.push(c); result.push_str(&digits); },
+            }
+        }
+        else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use super::{unescape, parse_mount_info, make_device, StatVfs};
+    use std::mem;
+
+    #[test]
+    fn spaces() {
+        assert_eq!(unescape("/media/my\\040disk"), "/media/my disk")
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(unescape("/home"), "/home")
+    }
+
+    #[test]
+    fn device_numbers() {
+        assert_eq!(make_device(8, 1), 0x801);
+        assert_eq!(make_device(259, 300), 0x11_032c);
+    }
+
+    #[test]
+    fn mount_info() {
+        let mounts = parse_mount_info("22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw\n\
+                                       40 22 8:17 / /media/my\\040disk rw shared:2 - ext4 /dev/sdb1 rw\n\
+                                       41 22 8:1 /srv /mnt/bind rw shared:1 - ext4 /dev/sda1 rw\n\
+                                       nonsense\n");
+
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(mounts.get(&0x801).map(|s| &s[..]), Some("/"));
+        assert_eq!(mounts.get(&0x811).map(|s| &s[..]), Some("/media/my disk"));
+    }
+
+    #[test]
+    fn statvfs_layout() {
+        let size = if cfg!(target_pointer_width = "64") { 112 } else { 96 };
+        assert_eq!(mem::size_of::<StatVfs>(), size);
+    }
+}
@@ -34,6 +34,51 @@ impl FileAttributes for Path {
     }
 }
 
+/// The extended attribute that Linux keeps a file's POSIX access control
+/// list in, as a NUL-terminated name.
+#[cfg(target_os = "linux")]
+const ACL_ATTRIBUTE: &'static [u8] = b"system.posix_acl_access\0";
+
+/// Whether the file at the given path has an access control list beyond
+/// its permission bits, which `ls` marks with a `+`.
+#[cfg(target_os = "linux")]
+pub fn has_acl(path: &Path) -> bool {
+    match path.as_os_str().to_cstring() {
+        Some(c_path)  => lister::Lister::new(FollowSymlinks::Yes).getxattr(&c_path, ACL_ATTRIBUTE) > 0,
+        None          => false,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn has_acl(_: &Path) -> bool {
+    false
+}
+
+/// Read the value of the extended attribute with the given NUL-terminated
+/// name from the file at the given path, or `None` if it doesn't have one.
+#[cfg(target_os = "linux")]
+pub fn read_attr(path: &Path, name: &[u8]) -> Option<Vec<u8>> {
+    let c_path = match path.as_os_str().to_cstring() {
+        Some(c_path)  => c_path,
+        None          => return None,
+    };
+
+    let lister = lister::Lister::new(FollowSymlinks::Yes);
+    let size = lister.getxattr(&c_path, name);
+    if size <= 0 {
+        return None;
+    }
+
+    let mut buf = vec![0u8; size as usize];
+    let size = lister.getxattr_value(&c_path, name, &mut buf);
+    if size < 0 {
+        return None;
+    }
+
+    buf.truncate(size as usize);
+    Some(buf)
+}
+
 /// Attributes which can be passed to `Attribute::list_with_flags`
 #[cfg(any(target_os = "macos", target_os = "linux"))]
 #[derive(Copy, Clone)]
@@ -248,5 +293,21 @@ mod lister {
                 )
             }
         }
+
+        pub fn getxattr_value(&self, c_path: &CString, name: &[u8], value: &mut Vec<u8>) -> ssize_t {
+            let getxattr = match self.follow_symlinks {
+                FollowSymlinks::Yes => getxattr,
+                FollowSymlinks::No  => lgetxattr,
+            };
+
+            unsafe {
+                getxattr(
+                    c_path.as_ptr(),
+                    name.as_ptr() as *const c_char,
+                    value.as_mut_ptr() as *mut c_void,
+                    value.len() as size_t
+                )
+            }
+        }
     }
 }
@@ -4,14 +4,20 @@ use std::ascii::AsciiExt;
 use std::env::current_dir;
 use std::fs;
 use std::io;
+use std::io::Read;
 use std::os::unix;
 use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::raw::time_t;
 use std::path::{Component, Path, PathBuf};
 
 use unicode_width::UnicodeWidthStr;
 
 use dir::Dir;
-use options::TimeType;
+use feature::birth;
+use feature::capabilities::{self, CapabilitySet};
+use feature::checksum;
+use feature::xattr::FileAttributes;
+use options::{ChecksumAlgorithm, TimeType};
 
 use self::fields as f;
 
@@ -90,8 +96,8 @@ impl<'dir> File<'dir> {
     ///
     /// Returns an IO error upon failure, but this shouldn't be used to check
     /// if a `File` is a directory or not! For that, just use `is_directory()`.
-    pub fn to_dir(&self, scan_for_git: bool) -> io::Result<Dir> {
-        Dir::read_dir(&*self.path, scan_for_git)
+    pub fn to_dir(&self, scan_for_git: bool, scan_for_ignored: bool, scan_for_commits: bool) -> io::Result<Dir> {
+        Dir::read_dir(&*self.path, scan_for_git, scan_for_ignored, scan_for_commits)
     }
 
     /// Whether this file is a regular file on the filesystem - that is, not a
@@ -154,6 +160,16 @@ impl<'dir> File<'dir> {
         path_prefix
     }
 
+    /// This file's path, made absolute by joining it onto the current
+    /// directory. If the current directory can't be found, the path is
+    /// left relative to `.` instead.
+    pub fn absolute_path(&self) -> PathBuf {
+        match current_dir() {
+            Err(_)  => Path::new(".").join(&self.path),
+            Ok(dir) => dir.join(&self.path),
+        }
+    }
+
     /// The Unicode 'display width' of the filename.
     ///
     /// This is related to the number of graphemes in the string: most
@@ -197,6 +213,22 @@ impl<'dir> File<'dir> {
         }
     }
 
+    /// Assuming the current file is a symlink, follows the whole chain of
+    /// links, returning a File object for the real file at the end of it,
+    /// with its fully-canonicalised path.
+    ///
+    /// If any link in the chain is broken, returns the path that this link
+    /// points to directly, as it would appear in a `->` arrow.
+    pub fn canonical_target(&self) -> Result<File<'dir>, String> {
+        match fs::canonicalize(&self.path) {
+            Ok(path) => File::from_path(&path, None).map_err(|_| path.to_string_lossy().into_owned()),
+            Err(_)   => match fs::read_link(&self.path) {
+                Ok(path)  => Err(path.to_string_lossy().into_owned()),
+                Err(_)    => Err(self.name.clone()),
+            },
+        }
+    }
+
     /// This file's number of hard links.
     ///
     /// It also reports whether this is both a regular file, and a file with
@@ -218,6 +250,32 @@ impl<'dir> File<'dir> {
         f::Inode(self.metadata.ino())
     }
 
+    /// The inode of the directory this file was listed from, if it was
+    /// listed from one, rather than being named on its own.
+    pub fn parent_inode(&self) -> Option<f::Inode> {
+        self.dir.and_then(|dir| dir.inode).map(f::Inode)
+    }
+
+    /// The name of the interpreter named in this file's shebang line, such
+    /// as `bash` for `#!/bin/bash`, if it's an executable script. Only the
+    /// first few bytes are read, and any errors reading them are ignored.
+    pub fn interpreter(&self) -> f::Interpreter {
+        if !self.is_executable_file() {
+            return f::Interpreter(None);
+        }
+
+        let mut buffer = Vec::new();
+        match fs::File::open(&self.path).and_then(|f| f.take(SHEBANG_LIMIT).read_to_end(&mut buffer)) {
+            Ok(_)   => f::Interpreter(shebang_interpreter(&buffer)),
+            Err(_)  => f::Interpreter(None),
+        }
+    }
+
+    /// The device this file resides on.
+    pub fn device(&self) -> f::Device {
+        f::Device(self.metadata.dev())
+    }
+
     /// This file's number of filesystem blocks.
     ///
     /// (Not the size of each block, which we don't actually report on)
@@ -230,6 +288,30 @@ impl<'dir> File<'dir> {
         }
     }
 
+    /// The number of extended attributes this file has.
+    ///
+    /// This queries the filesystem separately from the xattr child rows, so
+    /// it works whether or not those are being displayed.
+    pub fn xattr_count(&self) -> f::XattrCount {
+        match self.path.attributes() {
+            Ok(attrs)  => f::XattrCount::Some(attrs.len()),
+            Err(_)     => f::XattrCount::Unreadable,
+        }
+    }
+
+    /// The time this file was created, if the system keeps track of it.
+    /// Despite its name, the `FileCreated` time type is the time the
+    /// file's metadata last changed, as that's all Unix used to have.
+    pub fn birth_time(&self) -> Option<f::Time> {
+        birth::birth_time(&self.path).map(|(seconds, nanoseconds)| f::Time(seconds as time_t, nanoseconds))
+    }
+
+    /// The capabilities this file grants to the programs it runs, on
+    /// Linux, where they're kept in an extended attribute.
+    pub fn capabilities(&self) -> Vec<CapabilitySet> {
+        capabilities::file_capabilities(&self.path)
+    }
+
     /// The ID of the user that own this file.
     pub fn user(&self) -> f::User {
         f::User(self.metadata.uid())
@@ -254,15 +336,120 @@ impl<'dir> File<'dir> {
         }
     }
 
-    /// One of this file's timestamps, as a number in seconds.
+    /// Whether this file's size or modified time is different from the
+    /// other file's, which is how two versions of a file get told apart
+    /// without reading either of them.
+    pub fn differs_from(&self, other: &File) -> bool {
+        self.metadata.len() != other.metadata.len()
+            || self.metadata.mtime() != other.metadata.mtime()
+            || self.metadata.mtime_nsec() != other.metadata.mtime_nsec()
+    }
+
+    /// The total apparent size of the files under this directory, found by
+    /// walking it like `du` does. Symlinks aren't followed, and the walk
+    /// stops at a certain depth, so the total can be an underestimate;
+    /// the result says whether it is.
+    pub fn recursive_size(&self) -> f::DirSize {
+        let mut total = f::DirSize { bytes: 0, truncated: false, approximate: false };
+        add_dir_size(&self.path, DIR_SIZE_DEPTH_LIMIT, &mut total);
+        total
+    }
+
+    /// The number of entries immediately inside this file, if it's a
+    /// directory that can be read.
+    pub fn entry_count(&self) -> f::EntryCount {
+        match self.to_dir(false, false, false) {
+            Ok(dir)  => f::EntryCount::Some(dir.files().count()),
+            Err(_)   => f::EntryCount::Unreadable,
+        }
+    }
+
+    /// The number of directories immediately inside this file, if it's a
+    /// directory that can be read. Symlinks to directories don't count.
+    pub fn subdir_count(&self) -> f::EntryCount {
+        if !self.is_directory() {
+            return f::EntryCount::Unreadable;
+        }
+
+        match self.to_dir(false, false, false) {
+            Ok(dir)  => f::EntryCount::Some(dir.files().filter(|f| f.as_ref().map(|f| f.is_directory()).unwrap_or(false)).count()),
+            Err(_)   => f::EntryCount::Unreadable,
+        }
+    }
+
+    /// The number of lines in this file, counted as the number of newline
+    /// bytes it contains. Only regular files up to a certain size get read;
+    /// directories, larger files, files that can't be read, and files that
+    /// look like binaries (because they contain NUL bytes) have no count.
+    pub fn line_count(&self) -> f::LineCount {
+        if !self.is_file() || self.metadata.len() > LINE_COUNT_LIMIT {
+            return f::LineCount::Unknown;
+        }
+
+        match fs::File::open(&self.path).ok().and_then(|f| count_lines(f, LINE_COUNT_LIMIT)) {
+            Some(count)  => f::LineCount::Some(count),
+            None         => f::LineCount::Unknown,
+        }
+    }
+
+    /// How many symlinks have to be followed from this one to get to a file
+    /// that isn't a symlink, counting this one. Chains that go on for too
+    /// long are taken to be loops.
+    pub fn link_depth(&self) -> f::LinkDepth {
+        if !self.is_link() {
+            return f::LinkDepth::NotLink;
+        }
+
+        link_depth(&self.path)
+    }
+
+    /// The amount of space this file actually takes up on disk, based on
+    /// the number of 512-byte blocks allocated to it. For sparse files, this
+    /// will be less than their apparent size.
+    ///
+    /// Directories have no size, for the same reason as above.
+    pub fn allocated_size(&self) -> f::Size {
+        if self.is_directory() {
+            f::Size::None
+        }
+        else {
+            f::Size::Some(self.metadata.blocks() * 512)
+        }
+    }
+
+    /// The checksum of this file's contents, using the given algorithm.
+    /// As with line counts, only regular files up to a certain size get
+    /// read, so listing a directory of huge files doesn't take forever.
+    pub fn checksum(&self, algorithm: ChecksumAlgorithm) -> f::Checksum {
+        if !self.is_file() || self.metadata.len() > CHECKSUM_LIMIT {
+            return f::Checksum::Unknown;
+        }
+
+        match fs::File::open(&self.path).and_then(|f| checksum::hex_digest(algorithm, f.take(CHECKSUM_LIMIT))) {
+            Ok(digest)  => f::Checksum::Some(digest),
+            Err(_)      => f::Checksum::Unknown,
+        }
+    }
+
+    /// One of this file's timestamps, as a number of seconds, along with
+    /// the nanoseconds past that second.
     pub fn timestamp(&self, time_type: TimeType) -> f::Time {
-        let time_in_seconds = match time_type {
-            TimeType::FileAccessed => self.metadata.atime(),
-            TimeType::FileModified => self.metadata.mtime(),
-            TimeType::FileCreated  => self.metadata.ctime(),
-        };
+        match time_type {
+            TimeType::FileAccessed => f::Time(self.metadata.atime(), self.metadata.atime_nsec()),
+            TimeType::FileModified => f::Time(self.metadata.mtime(), self.metadata.mtime_nsec()),
+            TimeType::FileCreated  => f::Time(self.metadata.ctime(), self.metadata.ctime_nsec()),
+        }
+    }
+
+    /// The most recent of this file's access, modification, and change
+    /// times, for when any sign of activity will do.
+    pub fn last_activity(&self) -> f::Time {
+        let times = [ (self.metadata.atime(), self.metadata.atime_nsec()),
+                      (self.metadata.mtime(), self.metadata.mtime_nsec()),
+                      (self.metadata.ctime(), self.metadata.ctime_nsec()) ];
 
-        f::Time(time_in_seconds)
+        let (seconds, nanoseconds) = times.iter().cloned().max().unwrap_or((0, 0));
+        f::Time(seconds, nanoseconds)
     }
 
     /// This file's 'type'.
@@ -371,14 +558,29 @@ impl<'dir> File<'dir> {
     pub fn git_status(&self) -> f::Git {
         match self.dir {
             None    => f::Git { staged: f::GitStatus::NotModified, unstaged: f::GitStatus::NotModified },
-            Some(d) => {
-                let cwd = match current_dir() {
-                    Err(_)  => Path::new(".").join(&self.path),
-                    Ok(dir) => dir.join(&self.path),
-                };
+            Some(d) => d.git_status(&self.absolute_path(), self.is_directory()),
+        }
+    }
 
-                d.git_status(&cwd, self.is_directory())
-            },
+    /// Whether this file is matched by the `.gitignore` rules of its parent
+    /// directory's Git repository.
+    ///
+    /// Like the Git status, this will always be `false` for files that have
+    /// been passed in on the command line.
+    pub fn git_ignored(&self) -> f::GitIgnored {
+        match self.dir {
+            None    => f::GitIgnored(false),
+            Some(d) => d.git_ignored(&self.absolute_path()),
+        }
+    }
+
+    /// The most recent commit to have touched this file or, for a
+    /// directory, any of its contents. This is `None` for files that aren't
+    /// tracked, and for files passed in on the command line.
+    pub fn git_last_commit(&self) -> Option<f::GitCommit> {
+        match self.dir {
+            None    => None,
+            Some(d) => d.git_last_commit(&self.absolute_path(), self.is_directory()),
         }
     }
 }
@@ -396,6 +598,136 @@ fn path_filename(path: &Path) -> String {
     }
 }
 
+/// Add the sizes of the files under the given directory to the total,
+/// descending at most the given number of levels further. Anything that
+/// can't be read makes the total approximate rather than failing.
+fn add_dir_size(path: &Path, depth: usize, total: &mut f::DirSize) {
+    let entries = match fs::read_dir(path) {
+        Ok(entries)  => entries,
+        Err(_)       => { total.approximate = true; return },
+    };
+
+    for entry in entries {
+        let path = match entry {
+            Ok(entry)  => entry.path(),
+            Err(_)     => { total.approximate = true; continue },
+        };
+
+        match fs::symlink_metadata(&path) {
+            Ok(ref metadata) if metadata.is_dir() => {
+                if depth == 0 { total.truncated = true }
+                         else { add_dir_size(&path, depth - 1, total) }
+            },
+            Ok(metadata)  => total.bytes += metadata.len(),
+            Err(_)        => total.approximate = true,
+        }
+    }
+}
+
+/// How many levels below a directory get walked to find its total size.
+const DIR_SIZE_DEPTH_LIMIT: usize = 64;
+
+/// The largest file, in bytes, that gets read to have its lines counted.
+const LINE_COUNT_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// The largest file, in bytes, that gets read to have its checksum taken.
+const CHECKSUM_LIMIT: u64 = 64 * 1024 * 1024;
+
+/// How many symlinks get followed before a chain is taken to be a loop,
+/// which is the same number Linux gives up at.
+const LINK_DEPTH_LIMIT: usize = 40;
+
+/// The most bytes to read from the start of a file when looking for its
+/// shebang line.
+const SHEBANG_LIMIT: u64 = 128;
+
+/// Count the newline bytes in everything the given reader has to read,
+/// a buffer at a time. If there turns out to be more than `limit` bytes,
+/// or there's a NUL byte, or the reading fails, there's no count.
+fn count_lines<R: Read>(mut reader: R, limit: u64) -> Option<usize> {
+    let mut buffer = [0u8; 8192];
+    let mut lines = 0;
+    let mut total = 0;
+
+    loop {
+        let count = match reader.read(&mut buffer) {
+            Ok(0)  => return Some(lines),
+            Ok(n)  => n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return None,
+        };
+
+        total += count as u64;
+        if total > limit || buffer[..count].contains(&0) {
+            return None;
+        }
+
+        lines += buffer[..count].iter().filter(|&&b| b == b'\n').count();
+    }
+}
+
+/// Follow the chain of symlinks starting at the given path, one link at a
+/// time, counting each one. Relative links are relative to the directory
+/// the link is in, not to the one the chain started in.
+fn link_depth(path: &Path) -> f::LinkDepth {
+    let mut current = path.to_path_buf();
+    let mut hops = 0;
+
+    loop {
+        // A link can also loop through one of the directories in its
+        // target's path, which the system reports rather than the link.
+        match fs::symlink_metadata(&current) {
+            Ok(ref metadata) if metadata.file_type().is_symlink() => {},
+            Ok(_)   => return f::LinkDepth::Some(hops),
+            Err(ref e) if e.raw_os_error() == Some(::libc::ELOOP) => return f::LinkDepth::Cyclic,
+            Err(_)  => return f::LinkDepth::Broken,
+        }
+
+        if hops == LINK_DEPTH_LIMIT {
+            return f::LinkDepth::Cyclic;
+        }
+
+        let target = match fs::read_link(&current) {
+            Ok(target)  => target,
+            Err(_)      => return f::LinkDepth::Broken,
+        };
+
+        current = match current.parent() {
+            Some(parent)  => parent.join(&target),
+            None          => target,
+        };
+
+        hops += 1;
+    }
+}
+
+/// Extract the interpreter's name from the start of a script, if it begins
+/// with a `#!` line. The directory is stripped from the interpreter's path,
+/// and scripts run through `env` have the program that `env` runs returned
+/// instead.
+fn shebang_interpreter(bytes: &[u8]) -> Option<String> {
+    if !bytes.starts_with(b"#!") {
+        return None;
+    }
+
+    let line = String::from_utf8_lossy(&bytes[2..]);
+    let line = match line.lines().next() {
+        Some(line) => line,
+        None       => return None,
+    };
+
+    let mut words = line.split_whitespace();
+    let program = match words.next() {
+        Some(path) => Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned()),
+        None       => None,
+    };
+
+    match program {
+        Some(ref env) if env == "env" => words.find(|w| !w.starts_with('-')).map(|w| w.to_string()),
+        program                       => program,
+    }
+}
+
 /// Extract an extension from a string, if one is present, in lowercase.
 ///
 /// The extension is the series of characters after the last dot. This
@@ -416,7 +748,7 @@ fn ext(name: &str) -> Option<String> {
 /// return an object in this `fields` module. These objects are later rendered
 /// into formatted strings in the `output/details` module.
 pub mod fields {
-    use std::os::unix::raw::{blkcnt_t, gid_t, ino_t, nlink_t, time_t, uid_t};
+    use std::os::unix::raw::{blkcnt_t, dev_t, gid_t, ino_t, nlink_t, time_t, uid_t};
 
     pub enum Type {
         File, Directory, Pipe, Link, Special,
@@ -442,11 +774,18 @@ pub mod fields {
 
     pub struct Inode(pub ino_t);
 
+    pub struct Device(pub dev_t);
+
     pub enum Blocks {
         Some(blkcnt_t),
         None,
     }
 
+    pub enum XattrCount {
+        Some(usize),
+        Unreadable,
+    }
+
     pub struct User(pub uid_t);
 
     pub struct Group(pub gid_t);
@@ -456,7 +795,37 @@ pub mod fields {
         None,
     }
 
-    pub struct Time(pub time_t);
+    pub struct DirSize {
+        pub bytes: u64,
+        pub truncated: bool,
+        pub approximate: bool,
+    }
+
+    pub enum EntryCount {
+        Some(usize),
+        Unreadable,
+    }
+
+    pub enum LineCount {
+        Some(usize),
+        Unknown,
+    }
+
+    pub enum LinkDepth {
+        Some(usize),
+        Cyclic,
+        Broken,
+        NotLink,
+    }
+
+    pub enum Checksum {
+        Some(String),
+        Unknown,
+    }
+
+    /// A point in time, as seconds since the Epoch and the nanoseconds
+    /// past that second.
+    pub struct Time(pub time_t, pub i64);
 
     pub enum GitStatus {
         NotModified,
@@ -477,12 +846,28 @@ pub mod fields {
             Git { staged: GitStatus::NotModified, unstaged: GitStatus::NotModified }
         }
     }
+
+    pub struct GitIgnored(pub bool);
+
+    pub struct Interpreter(pub Option<String>);
+
+    /// The abbreviated ID and commit time of the last commit to have
+    /// touched a file.
+    #[derive(Clone)]
+    pub struct GitCommit {
+        pub id: String,
+        pub time: time_t,
+    }
 }
 
 
 #[cfg(test)]
 mod test {
-    use super::ext;
+    use super::{ext, shebang_interpreter, count_lines, File};
+    use dir::Dir;
+    use test_support::TempDir;
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
 
     #[test]
     fn extension() {
@@ -498,4 +883,63 @@ mod test {
     fn no_extension() {
         assert_eq!(None, ext("jarlsberg"))
     }
+
+    #[test]
+    fn shebang() {
+        assert_eq!(Some("bash".to_string()), shebang_interpreter(b"#!/bin/bash\necho hi"))
+    }
+
+    #[test]
+    fn shebang_env() {
+        assert_eq!(Some("python3".to_string()), shebang_interpreter(b"#!/usr/bin/env python3\n"))
+    }
+
+    #[test]
+    fn no_shebang() {
+        assert_eq!(None, shebang_interpreter(b"\x7fELF"))
+    }
+
+    #[test]
+    fn parent_inode() {
+        let temp = TempDir::new("parent-inode");
+        let _ = fs::File::create(temp.path.join("file")).unwrap();
+
+        let dir = Dir::read_dir(&temp.path, false, false, false).unwrap();
+        let file = dir.files().next().unwrap().unwrap();
+        assert_eq!(file.parent_inode().map(|i| i.0), Some(fs::metadata(&temp.path).unwrap().ino()));
+
+        let alone = File::from_path(&temp.path.join("file"), None).unwrap();
+        assert!(alone.parent_inode().is_none());
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(Some(2), count_lines(&b"one\ntwo\n"[..], 100))
+    }
+
+    #[test]
+    fn lines_empty() {
+        assert_eq!(Some(0), count_lines(&b""[..], 100))
+    }
+
+    #[test]
+    fn lines_without_trailing_newline() {
+        assert_eq!(Some(1), count_lines(&b"one\ntwo"[..], 100))
+    }
+
+    #[test]
+    fn lines_over_limit() {
+        assert_eq!(None, count_lines(&b"one\ntwo\n"[..], 7))
+    }
+
+    #[test]
+    fn lines_across_buffers() {
+        let contents = vec![b'\n'; 20_000];
+        assert_eq!(Some(20_000), count_lines(&contents[..], 20_000))
+    }
+
+    #[test]
+    fn lines_binary() {
+        assert_eq!(None, count_lines(&b"one\n\0two\n"[..], 100))
+    }
 }
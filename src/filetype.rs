@@ -4,7 +4,68 @@ use file::File;
 use colours::Colours;
 
 
-pub fn file_colour(colours: &Colours, file: &File) -> Style {
+/// Ways of colouring files' names that go beyond picking a colour for each
+/// type of file, which get switched on by the user.
+#[derive(PartialEq, Debug, Copy, Clone, Default)]
+pub struct NameColouring {
+
+    /// Whether dot-files should have their file type's style dimmed.
+    pub dim_hidden: bool,
+
+    /// Whether files with changes in Git should have their names coloured
+    /// by their Git status instead of by their type.
+    pub git_status: bool,
+}
+
+pub fn file_colour(colours: &Colours, colouring: NameColouring, file: &File) -> Style {
+    let git = if colouring.git_status { git_colour(colours, file) } else { None };
+    let style = match git.or_else(|| extension_colour(colours, file)) {
+        Some(style)  => style,
+        None         => file_type_colour(colours, file),
+    };
+
+    if colouring.dim_hidden && file.is_dotfile() {
+        style.dimmed()
+    }
+    else {
+        style
+    }
+}
+
+/// The colour of a file's Git status, if it has any changes. Unstaged
+/// changes take precedence over staged ones, as they're the ones that
+/// still need dealing with.
+fn git_colour(colours: &Colours, file: &File) -> Option<Style> {
+    use file::fields::GitStatus;
+
+    let git = file.git_status();
+    let status = match git.unstaged {
+        GitStatus::NotModified  => git.staged,
+        unstaged                => unstaged,
+    };
+
+    match status {
+        GitStatus::NotModified  => None,
+        GitStatus::New          => Some(colours.git.new),
+        GitStatus::Modified     => Some(colours.git.modified),
+        GitStatus::Deleted      => Some(colours.git.deleted),
+        GitStatus::Renamed      => Some(colours.git.renamed),
+        GitStatus::TypeChange   => Some(colours.git.typechange),
+    }
+}
+
+/// The colour for a regular file's extension, if one has been given. Other
+/// types of file, and executables, keep their type's colour, as `ls` does.
+fn extension_colour(colours: &Colours, file: &File) -> Option<Style> {
+    if file.is_file() && !file.is_executable_file() {
+        colours.extension_style(&file.name)
+    }
+    else {
+        None
+    }
+}
+
+fn file_type_colour(colours: &Colours, file: &File) -> Style {
     match file {
         f if f.is_directory()        => colours.filetypes.directory,
         f if f.is_executable_file()  => colours.filetypes.executable,
@@ -0,0 +1,80 @@
+//! Matching file names against simple shell-style glob patterns.
+
+/// A **Glob** is a pattern that file names can be matched against. A `*`
+/// matches any run of characters, including none, and a `?` matches any
+/// single character; everything else has to match exactly.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Glob {
+    pattern: Vec<char>,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Glob {
+        Glob { pattern: pattern.chars().collect() }
+    }
+
+    /// Whether the given name matches this pattern in its entirety.
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+
+        let mut p = 0;
+        let mut n = 0;
+
+        // Where to resume from if the current attempt fails: the position
+        // just after the most recent star, and the position in the name
+        // that the star should next try to swallow up to.
+        let mut backtrack = None;
+
+        while n < name.len() {
+            if p < self.pattern.len() && self.pattern[p] == '*' {
+                backtrack = Some((p + 1, n));
+                p += 1;
+            }
+            else if p < self.pattern.len() && (self.pattern[p] == '?' || self.pattern[p] == name[n]) {
+                p += 1;
+                n += 1;
+            }
+            else if let Some((star_p, star_n)) = backtrack {
+                backtrack = Some((star_p, star_n + 1));
+                p = star_p;
+                n = star_n + 1;
+            }
+            else {
+                return false;
+            }
+        }
+
+        self.pattern[p..].iter().all(|c| *c == '*')
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::Glob;
+
+    #[test]
+    fn exact() {
+        assert!(Glob::new("Cargo.toml").matches("Cargo.toml"));
+        assert!(!Glob::new("Cargo.toml").matches("Cargo.lock"));
+    }
+
+    #[test]
+    fn star() {
+        assert!(Glob::new("*.rs").matches("main.rs"));
+        assert!(Glob::new("*.rs").matches(".rs"));
+        assert!(!Glob::new("*.rs").matches("main.rs.bak"));
+    }
+
+    #[test]
+    fn question_mark() {
+        assert!(Glob::new("?.txt").matches("a.txt"));
+        assert!(!Glob::new("?.txt").matches("ab.txt"));
+    }
+
+    #[test]
+    fn backtracking() {
+        assert!(Glob::new("*a*b").matches("xaxxab"));
+        assert!(!Glob::new("*a*b").matches("xaxxa"));
+    }
+}
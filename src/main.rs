@@ -37,10 +37,15 @@ mod dir;
 mod feature;
 mod file;
 mod filetype;
+mod glob;
+mod mime;
 mod options;
 mod output;
 mod term;
 
+#[cfg(test)]
+mod test_support;
+
 
 struct Exa {
     options: Options,
@@ -58,7 +63,7 @@ impl Exa {
                 },
                 Ok(f) => {
                     if f.is_directory() && !self.options.dir_action.treat_dirs_as_files() {
-                        match f.to_dir(self.options.should_scan_for_git()) {
+                        match f.to_dir(self.options.should_scan_for_git(), self.options.should_scan_for_ignored(), self.options.should_scan_for_commits()) {
                             Ok(d) => dirs.push(d),
                             Err(e) => println!("{}: {}", file_name, e),
                         }
@@ -70,6 +75,19 @@ impl Exa {
             }
         }
 
+        // Comparing two directories takes the place of listing them.
+        if let View::Details(ref details) = self.options.view {
+            if details.diff {
+                // The options have already checked that there were two
+                // paths, and any that couldn't be read have been reported.
+                if dirs.len() == 2 {
+                    details.view_diff(&dirs[0], &dirs[1]);
+                }
+
+                return;
+            }
+        }
+
         let no_files = files.is_empty();
         if !no_files {
             self.print_files(None, files);
@@ -112,7 +130,7 @@ impl Exa {
 
                     let mut child_dirs = Vec::new();
                     for child_dir in children.iter().filter(|f| f.is_directory()) {
-                        match child_dir.to_dir(false) {
+                        match child_dir.to_dir(false, false, false) {
                             Ok(d)  => child_dirs.push(d),
                             Err(e) => println!("{}: {}", child_dir.path.display(), e),
                         }
@@ -135,10 +153,10 @@ impl Exa {
 
     fn print_files(&self, dir: Option<&Dir>, files: Vec<File>) {
         match self.options.view {
-            View::Grid(g)         => g.view(&files),
-            View::Details(d)      => d.view(dir, files),
-            View::GridDetails(gd) => gd.view(dir, &files),
-            View::Lines(l)        => l.view(&files),
+            View::Grid(ref g)         => g.view(&files),
+            View::Details(ref d)      => d.view(dir, files),
+            View::GridDetails(ref gd) => gd.view(dir, &files),
+            View::Lines(ref l)        => l.view(&files),
         }
     }
 }
@@ -0,0 +1,127 @@
+//! Guessing files' content types (MIME types) from their extensions.
+//!
+//! This doesn't look inside files at all, so it can be fooled by a file with
+//! the wrong extension, but it's fast enough to do for every file in a
+//! listing. Extensions are matched case-insensitively.
+
+use file::File;
+
+
+/// The MIME type used for directories, as the freedesktop.org shared MIME
+/// database does.
+pub const DIRECTORY: &'static str = "inode/directory";
+
+
+/// Guess the content type of the given file, returning `None` if its
+/// extension isn't in the table.
+pub fn mime_type(file: &File) -> Option<&'static str> {
+    if file.is_directory() {
+        Some(DIRECTORY)
+    }
+    else {
+        match file.ext {
+            Some(ref ext)  => for_extension(&ext.to_lowercase()),
+            None           => None,
+        }
+    }
+}
+
+/// Look up the content type for a lowercase file extension.
+pub fn for_extension(ext: &str) -> Option<&'static str> {
+    let mime = match ext {
+
+        // Text
+        "txt" | "text" | "log"    => "text/plain",
+        "md"  | "markdown"        => "text/markdown",
+        "html" | "htm"            => "text/html",
+        "css"                     => "text/css",
+        "csv"                     => "text/csv",
+        "tsv"                     => "text/tab-separated-values",
+        "xml"                     => "text/xml",
+        "rs"                      => "text/x-rust",
+        "c"   | "h"               => "text/x-c",
+        "cpp" | "cc" | "hpp"      => "text/x-c++",
+        "py"                      => "text/x-python",
+        "rb"                      => "text/x-ruby",
+        "sh"                      => "application/x-sh",
+        "js"                      => "application/javascript",
+        "json"                    => "application/json",
+        "toml"                    => "application/toml",
+        "yaml" | "yml"            => "application/x-yaml",
+
+        // Images
+        "png"                     => "image/png",
+        "jpg" | "jpeg"            => "image/jpeg",
+        "gif"                     => "image/gif",
+        "bmp"                     => "image/bmp",
+        "tif" | "tiff"            => "image/tiff",
+        "webp"                    => "image/webp",
+        "svg"                     => "image/svg+xml",
+        "ico"                     => "image/x-icon",
+
+        // Audio and video
+        "mp3"                     => "audio/mpeg",
+        "ogg"                     => "audio/ogg",
+        "flac"                    => "audio/flac",
+        "wav"                     => "audio/wav",
+        "m4a"                     => "audio/mp4",
+        "mp4"                     => "video/mp4",
+        "mkv"                     => "video/x-matroska",
+        "webm"                    => "video/webm",
+        "avi"                     => "video/x-msvideo",
+        "mov"                     => "video/quicktime",
+
+        // Documents
+        "pdf"                     => "application/pdf",
+        "ps"                      => "application/postscript",
+        "rtf"                     => "application/rtf",
+        "doc"                     => "application/msword",
+        "odt"                     => "application/vnd.oasis.opendocument.text",
+
+        // Archives
+        "zip"                     => "application/zip",
+        "tar"                     => "application/x-tar",
+        "gz"                      => "application/gzip",
+        "bz2"                     => "application/x-bzip2",
+        "xz"                      => "application/x-xz",
+        "7z"                      => "application/x-7z-compressed",
+        "rar"                     => "application/vnd.rar",
+
+        // Other
+        "wasm"                    => "application/wasm",
+        "o"                       => "application/x-object",
+        "so"                      => "application/x-sharedlib",
+        "jar"                     => "application/java-archive",
+
+        _                         => return None,
+    };
+
+    Some(mime)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::for_extension;
+
+    #[test]
+    fn known() {
+        assert_eq!(for_extension("png"), Some("image/png"))
+    }
+
+    #[test]
+    fn rust() {
+        assert_eq!(for_extension("rs"), Some("text/x-rust"))
+    }
+
+    #[test]
+    fn object_and_shared_library() {
+        assert_eq!(for_extension("o"), Some("application/x-object"));
+        assert_eq!(for_extension("so"), Some("application/x-sharedlib"));
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(for_extension("exa"), None)
+    }
+}
@@ -1,24 +1,32 @@
 use std::cmp;
+use std::collections::HashMap;
+use std::env;
 use std::fmt;
+use std::fs;
+use std::io::Read;
 use std::num::ParseIntError;
 use std::os::unix::fs::MetadataExt;
 
+use datetime::local::LocalDateTime;
 use getopts;
 use natord;
 
-use colours::Colours;
+use colours::{Colours, Theme};
 use column::Column;
 use column::Column::*;
 use dir::Dir;
 use feature::xattr;
 use file::File;
+use glob::Glob;
+use filetype::NameColouring;
 use output::{Grid, Details, GridDetails, Lines};
+use output::details::{SEQUENTIAL_LIMIT, TREE_INDENT};
 use term::dimensions;
 
 
 /// The *Options* struct represents a parsed version of the user's
 /// command-line options.
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct Options {
     pub dir_action: DirAction,
     pub filter: FileFilter,
@@ -33,25 +41,103 @@ impl Options {
         let mut opts = getopts::Options::new();
         opts.optflag("1", "oneline",   "display one entry per line");
         opts.optflag("a", "all",       "show dot-files");
+        opts.optflag("",  "absolute",  "show each file's absolute path instead of its name");
+        opts.optflag("",  "dim-hidden", "dim the names of dot-files");
+        opts.optflag("",  "dim-metadata", "dim every column but the names");
+        opts.optopt ("",  "theme",     "read colours from a theme file", "FILE");
+        opts.optflag("",  "dereference", "show the metadata of the files symlinks point to, instead of their own");
+        opts.optflag("",  "canonical", "show the real path at the end of each symlink's chain of links");
+        opts.optflag("p", "slash",     "show a / after the names of directories");
+        opts.optflag("Q", "quote-names", "quote names with spaces or shell characters in them");
+        opts.optopt ("",  "column-widths", "limit the widths of columns, such as date=:12,user=8:", "SPEC");
+        opts.optflag("",  "html",      "print the details as an HTML table");
+        opts.optflag("",  "json-lines", "print each file's details as a line of JSON");
+        opts.optflag("",  "summary",   "show how many files of each type were listed after the details");
+        opts.optflag("",  "banner",    "show the absolute path being listed above the details");
         opts.optflag("b", "binary",    "use binary prefixes in file sizes");
         opts.optflag("B", "bytes",     "list file sizes in bytes, without prefixes");
+        opts.optflag("",  "both-prefixes", "list file sizes with both decimal and binary prefixes");
+        opts.optflag("",  "byte-suffix", "show a B after file sizes given in bytes");
+        opts.optflag("",  "allocated", "show the allocated size on disk next to each file size");
+        opts.optflag("",  "dir-entries", "show the number of entries in each directory as its size");
+        opts.optflag("",  "dir-totals", "show the total size of the files under each directory as its size");
+        opts.optflag("",  "unit-space", "put a space between file sizes and their units");
+        opts.optopt ("",  "unit-case", "show the kilo size unit as K or k (upper, lower)", "WORD");
         opts.optflag("d", "list-dirs", "list directories as regular files");
         opts.optflag("g", "group",     "show group as well as user");
         opts.optflag("G", "grid",      "display entries in a grid view (default)");
         opts.optflag("",  "group-directories-first", "list directories before other files");
+        opts.optflag("",  "english-dates", "use English month names regardless of locale");
         opts.optflag("h", "header",    "show a header row at the top");
+        opts.optopt ("",  "header-style", "how to style the header row (underline, plain, columns)", "WORD");
         opts.optflag("H", "links",     "show number of hard links");
+        opts.optflag("",  "link-siblings", "show how many of each file's hard links are in the listing");
+        opts.optflag("",  "link-depth", "show how many symlinks each symlink's chain goes through");
         opts.optflag("i", "inode",     "show each file's inode number");
+        opts.optflag("",  "parent-inode", "show the inode number of each file's directory");
+        opts.optflag("",  "hex-inodes", "show inode numbers in hexadecimal");
         opts.optflag("l", "long",      "display extended details and attributes");
         opts.optopt ("L", "level",     "maximum depth of recursion", "DEPTH");
+        opts.optopt ("",  "max-children", "maximum number of entries to show in each directory of the tree", "COUNT");
         opts.optflag("m", "modified",  "display timestamp of most recent modification");
+        opts.optopt ("",  "permissions", "how to show permissions (symbolic, octal, both, type, bits)", "WORD");
+        opts.optopt ("",  "pin",       "list files matching these comma-separated globs first", "GLOBS");
+        opts.optopt ("",  "modified-within", "only list files modified within a duration (such as 30m, 2h, 1d)", "DURATION");
         opts.optflag("r", "reverse",   "reverse order of files");
         opts.optflag("R", "recurse",   "recurse into directories");
         opts.optopt ("s", "sort",      "field to sort by", "WORD");
+        opts.optopt ("",  "size-unit", "list all file sizes in one unit (k, M, G, T, Ki, Mi, Gi, Ti)", "UNIT");
+        opts.optopt ("",  "size-decimals", "number of decimal places to show in sizes (0 to 2, or any with --size-unit)", "COUNT");
+        opts.optflag("",  "align-decimals", "line file sizes up on their decimal points");
         opts.optflag("S", "blocks",    "show number of file system blocks");
+        opts.optflag("",  "total-blocks", "show the total number of blocks used by the files above the details");
         opts.optopt ("t", "time",      "which timestamp to show for a file", "WORD");
         opts.optflag("T", "tree",      "recurse into subdirectories in a tree view");
+        opts.optflag("",  "precise-times", "display dates in full, down to the nanosecond");
+        opts.optopt ("",  "time-style", "how to format dates (default, full-iso, long-iso, iso, +FORMAT)", "STYLE");
+        opts.optflag("",  "utc",       "display dates in UTC instead of the local time zone");
+        opts.optflag("",  "two-lines", "show each file's details on the line below its name");
+        opts.optflag("",  "tree-paths", "show each file's path from the root of the tree");
+        opts.optflag("",  "flat-tree", "list the tree as paths from its root, without the tree or columns");
+        opts.optflag("",  "rank",      "show each file's position in the sorted listing");
+        opts.optflag("",  "subdirs",   "show how many directories are immediately inside each directory");
+        opts.optflag("",  "tree-total", "show how many files are listed beneath each directory in the tree");
+        opts.optopt ("",  "tree-indent", "number of columns to indent each level of the tree by", "WIDTH");
+        opts.optopt ("",  "tree-style", "characters to draw the tree with (light, heavy, double, rounded, ascii)", "STYLE");
+        opts.optflag("",  "mirror-tree", "draw the tree on the right of the names, for right-to-left languages");
+        opts.optopt ("",  "error-style", "how to show errors reading files (rows, inline)", "STYLE");
+        opts.optopt ("",  "highlight", "highlight the names of files matching a glob pattern", "GLOB");
+        opts.optflag("",  "full-width", "extend highlighted rows to the edge of the terminal");
+        opts.optflag("",  "collapse",  "merge directories that only contain one directory in the tree");
+        opts.optflag("",  "borders",   "draw lines around the table and between its columns");
+        opts.optflag("",  "line-numbers", "number each line of the listing");
+        opts.optflag("",  "diff",      "show only the files that differ between two directories");
+        opts.optflag("",  "compact",   "leave out the least important columns until rows fit the terminal");
+        opts.optflag("",  "truncate",  "truncate rows that are too wide for the terminal");
         opts.optflag("u", "accessed",  "display timestamp of last access for a file");
+        opts.optflag("",  "mount",     "display the mount point of the device each file resides on");
+        opts.optflag("",  "interpreter", "display the interpreter named in each script's shebang line");
+        opts.optflag("",  "lines",     "display the number of lines in each text file");
+        opts.optflag("",  "size-bar",  "display a bar showing each file's size relative to the largest");
+        opts.optflag("",  "size-percent", "display each file's size as a percentage of the total");
+        opts.optflag("",  "fs-percent", "display each file's size as a percentage of its filesystem's capacity");
+        opts.optflag("",  "sparseness", "display how much of each file's size isn't allocated on disk");
+        opts.optopt ("",  "checksum",  "display a checksum of each file's contents (crc32, md5)", "ALGORITHM");
+        opts.optopt ("",  "large",     "mark files larger than a size, such as 100M", "SIZE");
+        opts.optopt ("",  "group-prefix", "text to show before each group name", "PREFIX");
+        opts.optopt ("",  "placeholder", "text to show for values that don't apply, instead of -", "TEXT");
+        opts.optopt ("",  "group-by",  "split the listing into sections by type, extension, or owner", "FIELD");
+        opts.optflag("",  "group-members", "show how many members each group has");
+        opts.optflag("",  "full-names", "show users' full names instead of their login names");
+        opts.optflag("",  "ditto-owners", "show a ditto mark for users and groups that repeat the row above's");
+        opts.optflag("",  "access",    "display whether you can read, write, and execute each file");
+        opts.optflag("",  "mime",      "display each file's content type, guessed from its extension");
+        opts.optflag("",  "last-activity", "display the most recent of each file's timestamps");
+        opts.optflag("",  "meta-changed", "display how long after its last modification each file's metadata changed");
+        opts.optflag("",  "lifetime",  "display how long each file was modified for after it was created");
+        opts.optflag("",  "age-days",  "display the number of days since each file was modified");
+        opts.optflag("",  "age-gradient", "display a dot coloured by how long ago each file was modified");
+        opts.optopt ("",  "age-stops", "ages and colours for the age gradient, such as 1h=46,1d=82,1w=196", "SPEC");
         opts.optflag("U", "created",   "display timestamp of creation for a file");
         opts.optflag("x", "across",    "sort multi-column view entries across");
 
@@ -60,10 +146,20 @@ impl Options {
 
         if cfg!(feature="git") {
             opts.optflag("", "git", "show git status");
+            opts.optflag("", "git-summary", "show each file's git status as a single character");
+            opts.optflag("", "git-ignored", "show whether each file is ignored by git");
+            opts.optflag("", "git-commit", "show the last commit to touch each file (slow)");
+            opts.optflag("", "git-names", "colour the names of changed files by their git status");
         }
 
         if xattr::ENABLED {
             opts.optflag("@", "extended", "display extended attribute keys and sizes in long (-l) output");
+            opts.optflag("", "xattr-count", "show the number of extended attributes of each file");
+            opts.optflag("", "no-xattr-marker", "don't show an @ after the permissions of files with extended attributes");
+        }
+
+        if cfg!(target_os = "linux") {
+            opts.optflag("", "capabilities", "show the capabilities each file grants, as setcap sets them");
         }
 
         let matches = match opts.parse(args) {
@@ -83,11 +179,23 @@ impl Options {
             None        => SortField::default(),
         };
 
+        let modified_within = match matches.opt_str("modified-within") {
+            Some(word)  => Some(try!(parse_duration("modified-within", word))),
+            None        => None,
+        };
+
+        let pinned = match matches.opt_str("pin") {
+            Some(list)  => list.split(',').map(Glob::new).collect(),
+            None        => Vec::new(),
+        };
+
         let filter = FileFilter {
-            list_dirs_first: matches.opt_present("group-directories-first"),
+            dirs_first:      matches.opt_present("group-directories-first"),
             reverse:         matches.opt_present("reverse"),
             show_invisibles: matches.opt_present("all"),
             sort_field:      sort_field,
+            modified_within: modified_within,
+            pinned:          pinned,
         };
 
         let path_strs = if matches.free.is_empty() {
@@ -100,6 +208,10 @@ impl Options {
         let dir_action = try!(DirAction::deduce(&matches));
         let view = try!(View::deduce(&matches, filter, dir_action));
 
+        if matches.opt_present("diff") && path_strs.len() != 2 {
+            return Err(Misfire::DiffPaths(path_strs.len()));
+        }
+
         Ok((Options {
             dir_action: dir_action,
             view:       view,
@@ -116,24 +228,65 @@ impl Options {
     }
 
     /// Whether the View specified in this set of options includes a Git
-    /// status column. It's only worth trying to discover a repository if the
-    /// results will end up being displayed.
+    /// status column, or colours names by their Git status. It's only worth
+    /// trying to discover a repository if the results will end up being
+    /// displayed.
     pub fn should_scan_for_git(&self) -> bool {
-        match self.view {
+        let git_names = match self.view {
+            View::Details(ref d)       => d.name_colouring.git_status,
+            View::GridDetails(ref gd)  => gd.details.name_colouring.git_status,
+            View::Grid(ref g)          => g.name_colouring.git_status,
+            View::Lines(ref l)         => l.name_colouring.git_status,
+        };
+
+        git_names || match self.view {
             View::Details(Details { columns: Some(cols), .. }) => cols.should_scan_for_git(),
             View::GridDetails(GridDetails { details: Details { columns: Some(cols), .. }, .. }) => cols.should_scan_for_git(),
             _ => false,
         }
     }
+
+    /// Whether the View specified in this set of options shows which files
+    /// are ignored. Finding them means looking through every ignored
+    /// directory, so this is only done when it's needed.
+    pub fn should_scan_for_ignored(&self) -> bool {
+        match self.view {
+            View::Details(Details { columns: Some(cols), .. }) => cols.git_summary || cols.git_ignored,
+            View::GridDetails(GridDetails { details: Details { columns: Some(cols), .. }, .. }) => cols.git_summary || cols.git_ignored,
+            _ => false,
+        }
+    }
+
+    /// Whether the View specified in this set of options includes the last
+    /// commit column. Searching a repository's history is slow, so this is
+    /// only done when it's asked for.
+    pub fn should_scan_for_commits(&self) -> bool {
+        match self.view {
+            View::Details(Details { columns: Some(cols), .. }) => cols.git_commit,
+            View::GridDetails(GridDetails { details: Details { columns: Some(cols), .. }, .. }) => cols.git_commit,
+            _ => false,
+        }
+    }
 }
 
 
-#[derive(Default, PartialEq, Debug, Copy, Clone)]
+#[derive(Default, PartialEq, Debug, Clone)]
 pub struct FileFilter {
-    list_dirs_first: bool,
+
+    /// Whether directories should be listed before any other files,
+    /// whichever field is being sorted by.
+    dirs_first: bool,
+
     reverse: bool,
     show_invisibles: bool,
     sort_field: SortField,
+
+    /// Only keep files that were modified less than this many seconds ago.
+    modified_within: Option<i64>,
+
+    /// Patterns for files to list before all the others, whatever they get
+    /// sorted by.
+    pinned: Vec<Glob>,
 }
 
 impl FileFilter {
@@ -141,22 +294,76 @@ impl FileFilter {
         if !self.show_invisibles {
             files.retain(|f| !f.is_dotfile());
         }
+
+        if self.modified_within.is_some() {
+            let now = LocalDateTime::now().to_instant().seconds();
+            files.retain(|f| self.is_recent(f.timestamp(TimeType::FileModified).0, now));
+        }
+    }
+
+    /// Whether a file with the given modification time passes the
+    /// time-window filter, relative to the given current time.
+    fn is_recent(&self, mtime: i64, now: i64) -> bool {
+        match self.modified_within {
+            Some(seconds) => now - mtime <= seconds,
+            None          => true,
+        }
     }
 
     pub fn sort_files(&self, files: &mut Vec<File>) {
-        files.sort_by(|a, b| self.compare_files(a, b));
+        self.sort_by_file(files, |file| file)
+    }
+
+    /// Sort a list of things that each have a file in them, such as the
+    /// rows of the tree view, in the same order that `sort_files` would
+    /// put their files in, reversing it if asked to.
+    pub fn sort_by_file<T, F>(&self, items: &mut Vec<T>, file: F)
+    where F: Fn(&T) -> &File {
+        items.sort_by(|a, b| self.compare_files(file(a), file(b)));
 
         if self.reverse {
-            files.reverse();
+            items.reverse();
+        }
+
+        if self.dirs_first {
+            // Reversing the list will have sent the directories to the
+            // bottom, so bring them back up. This relies on the fact that
+            // sort_by is stable.
+            items.sort_by(|a, b| file(b).is_directory().cmp(&file(a).is_directory()));
+        }
+
+        if !self.pinned.is_empty() {
+            // The same goes for the pinned files, which go above even the
+            // directories.
+            items.sort_by(|a, b| self.is_pinned(file(b)).cmp(&self.is_pinned(file(a))));
+        }
+    }
+
+    /// Whether the given file matches one of the patterns to pin to the top.
+    fn is_pinned(&self, file: &File) -> bool {
+        self.pinned.iter().any(|glob| glob.matches(&file.name))
+    }
+
+    /// Compare two files, first by whether they're pinned, then by whether
+    /// they're directories (if directories are being listed first), then
+    /// by the sort field.
+    fn compare_files(&self, a: &File, b: &File) -> cmp::Ordering {
+        match self.is_pinned(b).cmp(&self.is_pinned(a)) {
+            cmp::Ordering::Equal  => {},
+            order                 => return order,
         }
 
-        if self.list_dirs_first {
-            // This relies on the fact that sort_by is stable.
-            files.sort_by(|a, b| b.is_directory().cmp(&a.is_directory()));
+        if self.dirs_first {
+            match b.is_directory().cmp(&a.is_directory()) {
+                cmp::Ordering::Equal  => {},
+                order                 => return order,
+            }
         }
+
+        self.compare_by_field(a, b)
     }
 
-    pub fn compare_files(&self, a: &File, b: &File) -> cmp::Ordering {
+    fn compare_by_field(&self, a: &File, b: &File) -> cmp::Ordering {
         match self.sort_field {
             SortField::Unsorted      => cmp::Ordering::Equal,
             SortField::Name          => natord::compare(&*a.name, &*b.name),
@@ -173,6 +380,69 @@ impl FileFilter {
     }
 }
 
+/// Turn a user-supplied size, such as `100M` or `2Gi`, into a number of
+/// bytes. A number without a unit is taken to be in bytes. The name of the
+/// option it was given to is used in the error message.
+fn parse_size(option: &str, word: String) -> Result<u64, Misfire> {
+    let bad_size = || Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--{} {}", option, word)));
+
+    let split = word.find(|c: char| !c.is_digit(10) && c != '.').unwrap_or(word.len());
+    let number: f64 = try!(word[.. split].parse().map_err(|_| bad_size()));
+
+    let multiplier = if split == word.len() { 1f64 }
+                                       else { try!(SizeUnit::from_word(word[split ..].to_string()).map_err(|_| bad_size())).bytes() };
+
+    Ok((number * multiplier) as u64)
+}
+
+/// Read and parse the theme file at the given path. Any problems with it,
+/// from it not existing to it naming styles that don't, are errors.
+fn read_theme(path: &str) -> Result<Theme, Misfire> {
+    let mut contents = String::new();
+    if let Err(e) = fs::File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+        return Err(Misfire::BadTheme(format!("{}: {}", path, e)));
+    }
+
+    Theme::parse(&contents).map_err(|e| Misfire::BadTheme(format!("{}: {}", path, e)))
+}
+
+/// Determine how many columns each level of the tree should be indented by.
+/// Zero is rejected, as the levels would be impossible to tell apart.
+fn tree_indent(matches: &getopts::Matches) -> Result<usize, Misfire> {
+    match matches.opt_str("tree-indent") {
+        None        => Ok(TREE_INDENT),
+        Some(word)  => match word.parse() {
+            Ok(0)   => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--tree-indent {}", word)))),
+            Ok(n)   => Ok(n),
+            Err(e)  => Err(Misfire::FailedParse(e)),
+        },
+    }
+}
+
+/// Turn a user-supplied duration, such as `30m` or `2d`, into a number of
+/// seconds. A number without a suffix is taken to be in seconds. Negative
+/// durations, and ones too long to count the seconds of, are rejected. The
+/// name of the option it was given to is used in the error message.
+fn parse_duration(option: &str, word: String) -> Result<i64, Misfire> {
+    let (number, multiplier) = match word.chars().last() {
+        Some('s')  => (&word[.. word.len() - 1], 1),
+        Some('m')  => (&word[.. word.len() - 1], 60),
+        Some('h')  => (&word[.. word.len() - 1], 60 * 60),
+        Some('d')  => (&word[.. word.len() - 1], 60 * 60 * 24),
+        Some('w')  => (&word[.. word.len() - 1], 60 * 60 * 24 * 7),
+        _          => (&word[..], 1),
+    };
+
+    let seconds = if number.starts_with('-') { None }
+                                         else { number.parse::<i64>().ok().and_then(|n| n.checked_mul(multiplier)) };
+
+    match seconds {
+        Some(seconds)  => Ok(seconds),
+        None           => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--{} {}", option, word)))),
+    }
+}
+
+
 /// User-supplied field to sort by.
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum SortField {
@@ -237,6 +507,12 @@ pub enum Misfire {
 
     /// A numeric option was given that failed to be parsed as a number.
     FailedParse(ParseIntError),
+
+    /// The theme file couldn't be read, or had problems in it.
+    BadTheme(String),
+
+    /// Directories were being compared, but there weren't two of them.
+    DiffPaths(usize),
 }
 
 impl Misfire {
@@ -260,12 +536,14 @@ impl fmt::Display for Misfire {
             Useless(a, true, b)    => write!(f, "Option --{} is useless given option --{}.", a, b),
             Useless2(a, b1, b2)    => write!(f, "Option --{} is useless without options --{} or --{}.", a, b1, b2),
             FailedParse(ref e)     => write!(f, "Failed to parse number: {}", e),
+            BadTheme(ref e)        => write!(f, "Failed to load theme: {}", e),
+            DiffPaths(count)       => write!(f, "Option --diff needs two directories to compare, not {}.", count),
         }
     }
 }
 
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum View {
     Details(Details),
     Grid(Grid),
@@ -277,21 +555,228 @@ impl View {
     pub fn deduce(matches: &getopts::Matches, filter: FileFilter, dir_action: DirAction) -> Result<View, Misfire> {
         use self::Misfire::*;
 
+        let theme = match matches.opt_str("theme") {
+            Some(path)  => Some(try!(read_theme(&path))),
+            None        => None,
+        };
+
+        let colourful = || {
+            let mut colours = Colours::colourful();
+
+            // The exa-specific variable takes precedence over the standard
+            // one, so it gets applied last.
+            if let Ok(spec) = env::var("LS_COLORS") {
+                colours.apply_ls_colors(&spec);
+            }
+
+            if let Ok(spec) = env::var("EXA_COLORS") {
+                colours.apply_exa_colors(&spec);
+            }
+
+            // A theme file has to be asked for on the command line, so it
+            // takes precedence over the environment.
+            if let Some(ref theme) = theme {
+                colours.apply_theme(theme);
+            }
+
+            colours
+        };
+
+        // Names only get dimmed or coloured when everything else is
+        // coloured too.
+        let name_colouring = |colourful: bool| {
+            if colourful {
+                NameColouring {
+                    dim_hidden: matches.opt_present("dim-hidden"),
+                    git_status: cfg!(feature="git") && matches.opt_present("git-names"),
+                }
+            }
+            else {
+                NameColouring::default()
+            }
+        };
+
+        let console_width = || {
+            if matches.opt_present("truncate") { dimensions().map(|(width, _)| width) }
+                                          else { None }
+        };
+
+        let compact_width = || {
+            if matches.opt_present("compact") { dimensions().map(|(width, _)| width) }
+                                         else { None }
+        };
+
+        let pad_width = || {
+            if matches.opt_present("full-width") { dimensions().map(|(width, _)| width) }
+                                            else { None }
+        };
+
         let long = || {
             if matches.opt_present("across") && !matches.opt_present("grid") {
                 Err(Useless("across", true, "long"))
             }
+            else if matches.opt_present("dir-entries") && matches.opt_present("tree") {
+                Err(Conflict("dir-entries", "tree"))
+            }
+            else if matches.opt_present("dir-totals") && matches.opt_present("tree") {
+                Err(Conflict("dir-totals", "tree"))
+            }
+            else if matches.opt_present("precise-times") && matches.opt_present("time-style") {
+                Err(Conflict("precise-times", "time-style"))
+            }
+            else if matches.opt_present("dir-totals") && matches.opt_present("dir-entries") {
+                Err(Conflict("dir-totals", "dir-entries"))
+            }
+            else if matches.opt_present("borders") && matches.opt_present("tree") {
+                Err(Conflict("borders", "tree"))
+            }
+            else if matches.opt_present("group-by") && matches.opt_present("tree") {
+                Err(Conflict("group-by", "tree"))
+            }
+            else if matches.opt_present("group-by") && matches.opt_present("grid") {
+                Err(Useless("group-by", true, "grid"))
+            }
+            else if matches.opt_present("line-numbers") && matches.opt_present("borders") {
+                Err(Conflict("line-numbers", "borders"))
+            }
+            else if matches.opt_present("diff") && matches.opt_present("tree") {
+                Err(Conflict("diff", "tree"))
+            }
+            else if matches.opt_present("diff") && matches.opt_present("recurse") {
+                Err(Conflict("diff", "recurse"))
+            }
+            else if matches.opt_present("diff") && matches.opt_present("grid") {
+                Err(Conflict("diff", "grid"))
+            }
+            else if matches.opt_present("diff") && matches.opt_present("two-lines") {
+                Err(Conflict("diff", "two-lines"))
+            }
+            else if matches.opt_present("diff") && matches.opt_present("borders") {
+                Err(Conflict("diff", "borders"))
+            }
+            else if matches.opt_present("diff") && matches.opt_present("html") {
+                Err(Conflict("diff", "html"))
+            }
+            else if matches.opt_present("diff") && matches.opt_present("json-lines") {
+                Err(Conflict("diff", "json-lines"))
+            }
+            else if matches.opt_present("absolute") && matches.opt_present("tree") {
+                Err(Conflict("absolute", "tree"))
+            }
+            else if matches.opt_present("two-lines") && matches.opt_present("grid") {
+                Err(Conflict("two-lines", "grid"))
+            }
+            else if matches.opt_present("html") && matches.opt_present("grid") {
+                Err(Conflict("html", "grid"))
+            }
+            else if matches.opt_present("json-lines") && matches.opt_present("grid") {
+                Err(Conflict("json-lines", "grid"))
+            }
+            else if matches.opt_present("json-lines") && matches.opt_present("html") {
+                Err(Conflict("json-lines", "html"))
+            }
+            else if matches.opt_present("tree-paths") && !matches.opt_present("tree") {
+                Err(Useless("tree-paths", false, "tree"))
+            }
+            else if matches.opt_present("flat-tree") && !matches.opt_present("tree") {
+                Err(Useless("flat-tree", false, "tree"))
+            }
+            else if matches.opt_present("tree-total") && !matches.opt_present("tree") {
+                Err(Useless("tree-total", false, "tree"))
+            }
+            else if matches.opt_present("collapse") && !matches.opt_present("tree") {
+                Err(Useless("collapse", false, "tree"))
+            }
+            else if matches.opt_present("tree-indent") && !matches.opt_present("tree") {
+                Err(Useless("tree-indent", false, "tree"))
+            }
+            else if matches.opt_present("mirror-tree") && !matches.opt_present("tree") {
+                Err(Useless("mirror-tree", false, "tree"))
+            }
+            else if matches.opt_present("group-prefix") && !matches.opt_present("group") {
+                Err(Useless("group-prefix", false, "group"))
+            }
+            else if matches.opt_present("group-members") && !matches.opt_present("group") {
+                Err(Useless("group-members", false, "group"))
+            }
+            else if matches.opt_present("full-width") && !matches.opt_present("highlight") {
+                Err(Useless("full-width", false, "highlight"))
+            }
+            else if matches.opt_present("age-stops") && !matches.opt_present("age-gradient") {
+                Err(Useless("age-stops", false, "age-gradient"))
+            }
+            else if matches.opt_present("hex-inodes") && !matches.opt_present("inode") && !matches.opt_present("parent-inode") {
+                Err(Useless("hex-inodes", false, "inode"))
+            }
+            else if matches.opt_present("link-siblings") && !matches.opt_present("links") {
+                Err(Useless("link-siblings", false, "links"))
+            }
+            else if xattr::ENABLED && matches.opt_present("no-xattr-marker") && !matches.opt_present("extended") {
+                Err(Useless("no-xattr-marker", false, "extended"))
+            }
             else if matches.opt_present("oneline") {
                 Err(Useless("oneline", true, "long"))
             }
             else {
+                let colourful_output = !matches.opt_present("json-lines") && (dimensions().is_some() || matches.opt_present("html"));
+
+                // The stops only get used when there's colour, but a bad
+                // list is still a mistake when there isn't.
+                let age_stops = try!(AgeStop::deduce(matches));
+
                 let details = Details {
                     columns: Some(try!(Columns::deduce(matches))),
                     header: matches.opt_present("header"),
+                    header_style: try!(HeaderStyle::deduce(matches)),
+                    width_limits: try!(WidthLimit::deduce(matches)),
+                    english_dates: matches.opt_present("english-dates"),
+                    utc: matches.opt_present("utc"),
+                    precise_times: matches.opt_present("precise-times"),
+                    time_style: try!(TimeStyle::deduce(matches)),
+                    now: None,
+                    group_prefix: matches.opt_str("group-prefix"),
+                    placeholder: matches.opt_str("placeholder"),
+                    group_by: try!(GroupBy::deduce(matches)),
+                    group_members: matches.opt_present("group-members"),
+                    full_names: matches.opt_present("full-names"),
+                    hex_inodes: matches.opt_present("hex-inodes"),
+                    link_siblings: matches.opt_present("link-siblings"),
+                    align_decimals: matches.opt_present("align-decimals"),
+                    dereference: matches.opt_present("dereference"),
+                    ditto_owners: matches.opt_present("ditto-owners"),
+                    absolute_paths: matches.opt_present("absolute"),
+                    canonical_links: matches.opt_present("canonical"),
+                    dir_slash: matches.opt_present("slash"),
+                    quote_names: matches.opt_present("quote-names"),
+                    name_colouring: name_colouring(colourful_output),
+                    two_lines: matches.opt_present("two-lines"),
+                    tree_paths: matches.opt_present("tree-paths"),
+                    flat_tree: matches.opt_present("flat-tree"),
+                    banner: matches.opt_present("banner"),
+                    total_blocks: matches.opt_present("total-blocks"),
+                    summary: matches.opt_present("summary"),
+                    html: matches.opt_present("html"),
+                    json_lines: matches.opt_present("json-lines"),
+                    collapse_dirs: matches.opt_present("collapse"),
+                    tree_indent: try!(tree_indent(matches)),
+                    tree_style: try!(TreeStyle::deduce(matches)),
+                    mirror_tree: matches.opt_present("mirror-tree"),
+                    error_style: try!(ErrorStyle::deduce(matches)),
+                    highlight: matches.opt_str("highlight").map(|pattern| Glob::new(&pattern)),
+                    pad_width: pad_width(),
+                    compact_width: compact_width(),
+                    borders: matches.opt_present("borders"),
+                    dim_metadata: colourful_output && matches.opt_present("dim-metadata"),
+                    line_numbers: matches.opt_present("line-numbers"),
+                    diff: matches.opt_present("diff"),
                     recurse: dir_action.recurse_options(),
-                    filter: filter,
+                    filter: filter.clone(),
                     xattr: xattr::ENABLED && matches.opt_present("extended"),
-                    colours: if dimensions().is_some() { Colours::colourful() } else { Colours::plain() },
+                    hide_xattr_marker: xattr::ENABLED && matches.opt_present("no-xattr-marker"),
+                    colours: if colourful_output { colourful() } else { Colours::plain() },
+                    age_stops: if colourful_output { age_stops } else { Vec::new() },
+                    console_width: console_width(),
+                    sequential_limit: SEQUENTIAL_LIMIT,
                 };
 
                 Ok(details)
@@ -299,7 +784,7 @@ impl View {
         };
 
         let long_options_scan = || {
-            for option in &[ "binary", "bytes", "inode", "links", "header", "blocks", "time", "group" ] {
+            for option in &[ "binary", "bytes", "both-prefixes", "byte-suffix", "allocated", "dir-entries", "dir-totals", "subdirs", "rank", "unit-space", "unit-case", "size-unit", "size-decimals", "align-decimals", "inode", "parent-inode", "hex-inodes", "dereference", "links", "link-siblings", "link-depth", "header", "header-style", "column-widths", "english-dates", "utc", "precise-times", "time-style", "absolute", "two-lines", "compact", "borders", "diff", "dim-metadata", "permissions", "access", "size-bar", "size-percent", "fs-percent", "sparseness", "large", "lines", "checksum", "blocks", "total-blocks", "time", "last-activity", "meta-changed", "lifetime", "age-days", "age-gradient", "age-stops", "mime", "interpreter", "mount", "group", "group-prefix", "placeholder", "group-by", "group-members", "full-names", "ditto-owners" ] {
                 if matches.opt_present(option) {
                     return Err(Useless(option, false, "long"));
                 }
@@ -308,48 +793,144 @@ impl View {
             if cfg!(feature="git") && matches.opt_present("git") {
                 Err(Useless("git", false, "long"))
             }
+            else if cfg!(feature="git") && matches.opt_present("git-summary") {
+                Err(Useless("git-summary", false, "long"))
+            }
+            else if cfg!(feature="git") && matches.opt_present("git-ignored") {
+                Err(Useless("git-ignored", false, "long"))
+            }
+            else if cfg!(feature="git") && matches.opt_present("git-commit") {
+                Err(Useless("git-commit", false, "long"))
+            }
             else if matches.opt_present("level") && !matches.opt_present("recurse") && !matches.opt_present("tree") {
                 Err(Useless2("level", "recurse", "tree"))
             }
             else if xattr::ENABLED && matches.opt_present("extended") {
                 Err(Useless("extended", false, "long"))
             }
+            else if xattr::ENABLED && matches.opt_present("xattr-count") {
+                Err(Useless("xattr-count", false, "long"))
+            }
+            else if xattr::ENABLED && matches.opt_present("no-xattr-marker") {
+                Err(Useless("no-xattr-marker", false, "long"))
+            }
+            else if cfg!(target_os = "linux") && matches.opt_present("capabilities") {
+                Err(Useless("capabilities", false, "long"))
+            }
+            else if matches.opt_present("truncate") && !matches.opt_present("tree") {
+                Err(Useless2("truncate", "long", "tree"))
+            }
+            else if matches.opt_present("line-numbers") && !matches.opt_present("tree") {
+                Err(Useless2("line-numbers", "long", "tree"))
+            }
+            else if matches.opt_present("tree-paths") && !matches.opt_present("tree") {
+                Err(Useless("tree-paths", false, "tree"))
+            }
+            else if matches.opt_present("flat-tree") && !matches.opt_present("tree") {
+                Err(Useless("flat-tree", false, "tree"))
+            }
+            else if matches.opt_present("collapse") && !matches.opt_present("tree") {
+                Err(Useless("collapse", false, "tree"))
+            }
+            else if matches.opt_present("tree-indent") && !matches.opt_present("tree") {
+                Err(Useless("tree-indent", false, "tree"))
+            }
+            else if matches.opt_present("mirror-tree") && !matches.opt_present("tree") {
+                Err(Useless("mirror-tree", false, "tree"))
+            }
+            else if matches.opt_present("tree-total") {
+                Err(Useless("tree-total", false, "long"))
+            }
+            else if matches.opt_present("banner") && !matches.opt_present("tree") {
+                Err(Useless2("banner", "long", "tree"))
+            }
+            else if matches.opt_present("highlight") && !matches.opt_present("tree") {
+                Err(Useless2("highlight", "long", "tree"))
+            }
+            else if matches.opt_present("full-width") && !matches.opt_present("highlight") {
+                Err(Useless("full-width", false, "highlight"))
+            }
+            else if matches.opt_present("summary") && !matches.opt_present("tree") {
+                Err(Useless2("summary", "long", "tree"))
+            }
+            else if matches.opt_present("html") && !matches.opt_present("tree") {
+                Err(Useless2("html", "long", "tree"))
+            }
+            else if matches.opt_present("json-lines") && !matches.opt_present("tree") {
+                Err(Useless2("json-lines", "long", "tree"))
+            }
+            else if matches.opt_present("canonical") && !matches.opt_present("tree") {
+                Err(Useless2("canonical", "long", "tree"))
+            }
+            else if matches.opt_present("slash") && !matches.opt_present("tree") {
+                Err(Useless2("slash", "long", "tree"))
+            }
+            else if matches.opt_present("error-style") && !matches.opt_present("tree") {
+                Err(Useless2("error-style", "long", "tree"))
+            }
             else {
                 Ok(())
             }
         };
 
         let other_options_scan = || {
-            if let Some((width, _)) = dimensions() {
+            // The tree gets listed in full whether or not the output is
+            // going to a terminal, so it can be piped into other programs
+            // as paths, JSON, or HTML.
+            if matches.opt_present("tree") && !matches.opt_present("oneline") {
+                let colourful_output = !matches.opt_present("json-lines") && (dimensions().is_some() || matches.opt_present("html"));
+
+                let details = Details {
+                    canonical_links: matches.opt_present("canonical"),
+                    dir_slash: matches.opt_present("slash"),
+                    quote_names: matches.opt_present("quote-names"),
+                    name_colouring: name_colouring(colourful_output),
+                    tree_paths: matches.opt_present("tree-paths"),
+                    flat_tree: matches.opt_present("flat-tree"),
+                    banner: matches.opt_present("banner"),
+                    summary: matches.opt_present("summary"),
+                    html: matches.opt_present("html"),
+                    json_lines: matches.opt_present("json-lines"),
+                    collapse_dirs: matches.opt_present("collapse"),
+                    tree_indent: try!(tree_indent(matches)),
+                    tree_style: try!(TreeStyle::deduce(matches)),
+                    mirror_tree: matches.opt_present("mirror-tree"),
+                    error_style: try!(ErrorStyle::deduce(matches)),
+                    highlight: matches.opt_str("highlight").map(|pattern| Glob::new(&pattern)),
+                    pad_width: pad_width(),
+                    line_numbers: matches.opt_present("line-numbers"),
+                    recurse: dir_action.recurse_options(),
+                    filter: filter.clone(),
+                    colours: if colourful_output { colourful() } else { Colours::plain() },
+                    console_width: console_width(),
+                    sequential_limit: SEQUENTIAL_LIMIT,
+                    ..Details::default()
+                };
+
+                Ok(View::Details(details))
+            }
+            else if let Some((width, _)) = dimensions() {
                 if matches.opt_present("oneline") {
                     if matches.opt_present("across") {
                         Err(Useless("across", true, "oneline"))
                     }
                     else {
                         let lines = Lines {
-                             colours: Colours::colourful(),
+                             colours: colourful(),
+                             name_colouring: name_colouring(true),
+                             quote_names: matches.opt_present("quote-names"),
                         };
 
                         Ok(View::Lines(lines))
                     }
                 }
-                else if matches.opt_present("tree") {
-                    let details = Details {
-                        columns: None,
-                        header: false,
-                        recurse: dir_action.recurse_options(),
-                        filter: filter,
-                        xattr: false,
-                        colours: if dimensions().is_some() { Colours::colourful() } else { Colours::plain() },
-                    };
-
-                    Ok(View::Details(details))
-                }
                 else {
                     let grid = Grid {
                         across: matches.opt_present("across"),
                         console_width: width,
-                        colours: Colours::colourful(),
+                        colours: colourful(),
+                        name_colouring: name_colouring(true),
+                        quote_names: matches.opt_present("quote-names"),
                     };
 
                     Ok(View::Grid(grid))
@@ -361,6 +942,8 @@ impl View {
                 // fallback to the lines view.
                 let lines = Lines {
                      colours: Colours::plain(),
+                     name_colouring: name_colouring(false),
+                     quote_names: matches.opt_present("quote-names"),
                 };
 
                 Ok(View::Lines(lines))
@@ -394,6 +977,14 @@ pub enum SizeFormat {
     DecimalBytes,
     BinaryBytes,
     JustBytes,
+
+    /// Sizes are displayed with decimal prefixes, then again with binary
+    /// ones, for when the difference between the two matters.
+    BothPrefixes,
+
+    /// Every size is displayed in the same unit, with the given number of
+    /// decimal places.
+    FixedUnit(SizeUnit, usize),
 }
 
 impl Default for SizeFormat {
@@ -406,6 +997,38 @@ impl SizeFormat {
     pub fn deduce(matches: &getopts::Matches) -> Result<SizeFormat, Misfire> {
         let binary = matches.opt_present("binary");
         let bytes  = matches.opt_present("bytes");
+        let both   = matches.opt_present("both-prefixes");
+
+        if let Some(word) = matches.opt_str("size-unit") {
+            if binary {
+                return Err(Misfire::Conflict("size-unit", "binary"));
+            }
+            else if bytes {
+                return Err(Misfire::Conflict("size-unit", "bytes"));
+            }
+            else if both {
+                return Err(Misfire::Conflict("size-unit", "both-prefixes"));
+            }
+
+            let unit = try!(SizeUnit::from_word(word));
+            let decimals = match matches.opt_str("size-decimals") {
+                Some(count) => try!(count.parse().map_err(Misfire::FailedParse)),
+                None        => 1,
+            };
+
+            return Ok(SizeFormat::FixedUnit(unit, decimals));
+        }
+
+        if both {
+            if binary {
+                return Err(Misfire::Conflict("both-prefixes", "binary"));
+            }
+            else if bytes {
+                return Err(Misfire::Conflict("both-prefixes", "bytes"));
+            }
+
+            return Ok(SizeFormat::BothPrefixes);
+        }
 
         match (binary, bytes) {
             (true,  true )  => Err(Misfire::Conflict("binary", "bytes")),
@@ -417,87 +1040,605 @@ impl SizeFormat {
 }
 
 
-#[derive(PartialEq, Debug, Copy, Clone)]
-pub enum TimeType {
-    FileAccessed,
-    FileModified,
-    FileCreated,
+/// How to display the file size column.
+#[derive(PartialEq, Debug, Copy, Clone, Default)]
+pub struct SizeOptions {
+    pub format: SizeFormat,
+
+    /// Whether to follow sizes that are a plain number of bytes with a `B`,
+    /// so they look like the sizes that have a prefix.
+    pub byte_suffix: bool,
+
+    /// Whether to show the size allocated on disk after the apparent size,
+    /// which makes sparse files stand out.
+    pub allocated: bool,
+
+    /// Whether directories should show how many entries they contain in
+    /// place of a size.
+    pub dir_entries: bool,
+
+    /// Whether directories should show the total size of the files under
+    /// them in place of a size.
+    pub dir_totals: bool,
+
+    /// Whether to put a space between a size and its unit.
+    pub unit_space: bool,
+
+    /// Which case to display units in.
+    pub unit_case: UnitCase,
+
+    /// How many decimal places to show in sizes with a prefix. By default,
+    /// sizes under 10 get one decimal place and larger ones get none.
+    pub decimals: Option<usize>,
 }
 
-impl TimeType {
-    pub fn header(&self) -> &'static str {
-        match *self {
-            TimeType::FileAccessed  => "Date Accessed",
-            TimeType::FileModified  => "Date Modified",
-            TimeType::FileCreated   => "Date Created",
+impl SizeOptions {
+
+    /// Read how many decimal places prefixed sizes should have, which can
+    /// be from zero to two. With `--size-unit`, the same option sets the
+    /// number of places for the fixed unit instead, so it's not read here.
+    fn deduce_decimals(matches: &getopts::Matches) -> Result<Option<usize>, Misfire> {
+        let word = match matches.opt_str("size-decimals") {
+            Some(word)  => word,
+            None        => return Ok(None),
+        };
+
+        if matches.opt_present("size-unit") {
+            return Ok(None);
         }
+        else if matches.opt_present("bytes") {
+            return Err(Misfire::Conflict("size-decimals", "bytes"));
+        }
+
+        match word.parse() {
+            Ok(count) if count <= 2  => Ok(Some(count)),
+            Ok(_)                    => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--size-decimals {}", word)))),
+            Err(e)                   => Err(Misfire::FailedParse(e)),
+        }
+    }
+
+    /// Turn the symbol for a unit into the text that should be displayed
+    /// after the number, with the requested spacing and case.
+    ///
+    /// Only the kilo prefix ever changes case, as it's the only one that
+    /// gets written both ways: `m` would mean milli rather than mega, and
+    /// binary prefixes always end with a lower-case `i`.
+    pub fn unit_symbol(&self, symbol: &str) -> String {
+        let symbol = match (self.unit_case, symbol) {
+            (UnitCase::Upper, "k")  => "K",
+            (UnitCase::Lower, "K")  => "k",
+            _                       => symbol,
+        };
+
+        if self.unit_space { format!(" {}", symbol) }
+                      else { symbol }
     }
 }
 
 
+/// Which case the units of file sizes should be displayed in.
 #[derive(PartialEq, Debug, Copy, Clone)]
-pub struct TimeTypes {
-    accessed: bool,
-    modified: bool,
-    created:  bool,
+pub enum UnitCase {
+
+    /// Leave units the way they are, such as `k` and `M`.
+    AsIs,
+
+    /// Show the kilo prefix as `K`.
+    Upper,
+
+    /// Show the kilo prefix as `k`.
+    Lower,
 }
 
-impl Default for TimeTypes {
-    fn default() -> TimeTypes {
-        TimeTypes { accessed: false, modified: true, created: false }
+impl Default for UnitCase {
+    fn default() -> UnitCase {
+        UnitCase::AsIs
     }
 }
 
-impl TimeTypes {
+impl UnitCase {
+    pub fn deduce(matches: &getopts::Matches) -> Result<UnitCase, Misfire> {
+        match matches.opt_str("unit-case") {
+            None => Ok(UnitCase::default()),
+            Some(word) => match &word[..] {
+                "upper"  => Ok(UnitCase::Upper),
+                "lower"  => Ok(UnitCase::Lower),
+                case     => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--unit-case {}", case)))),
+            },
+        }
+    }
+}
 
-    /// Find which field to use based on a user-supplied word.
-    fn deduce(matches: &getopts::Matches) -> Result<TimeTypes, Misfire> {
-        let possible_word = matches.opt_str("time");
-        let modified = matches.opt_present("modified");
-        let created  = matches.opt_present("created");
-        let accessed = matches.opt_present("accessed");
 
-        if let Some(word) = possible_word {
-            if modified {
-                return Err(Misfire::Useless("modified", true, "time"));
-            }
-            else if created {
-                return Err(Misfire::Useless("created", true, "time"));
-            }
-            else if accessed {
-                return Err(Misfire::Useless("accessed", true, "time"));
-            }
+/// A unit that every file size can be forced into, rather than having the
+/// most appropriate prefix picked for each file.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum SizeUnit {
+    Kilo, Mega, Giga, Tera,
+    Kibi, Mebi, Gibi, Tebi,
+}
 
-            match &word[..] {
-                "mod" | "modified"  => Ok(TimeTypes { accessed: false, modified: true, created: false }),
-                "acc" | "accessed"  => Ok(TimeTypes { accessed: true, modified: false, created: false }),
-                "cr"  | "created"   => Ok(TimeTypes { accessed: false, modified: false, created: true }),
-                field   => Err(TimeTypes::none(field)),
-            }
+impl SizeUnit {
+
+    /// Find which unit to use based on a user-supplied word.
+    fn from_word(word: String) -> Result<SizeUnit, Misfire> {
+        match &word[..] {
+            "k"  | "kB"   => Ok(SizeUnit::Kilo),
+            "M"  | "MB"   => Ok(SizeUnit::Mega),
+            "G"  | "GB"   => Ok(SizeUnit::Giga),
+            "T"  | "TB"   => Ok(SizeUnit::Tera),
+            "Ki" | "KiB"  => Ok(SizeUnit::Kibi),
+            "Mi" | "MiB"  => Ok(SizeUnit::Mebi),
+            "Gi" | "GiB"  => Ok(SizeUnit::Gibi),
+            "Ti" | "TiB"  => Ok(SizeUnit::Tebi),
+            unit          => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--size-unit {}", unit)))),
         }
-        else {
-            if modified || created || accessed {
-                Ok(TimeTypes { accessed: accessed, modified: modified, created: created })
-            }
-            else {
-                Ok(TimeTypes::default())
-            }
+    }
+
+    /// The symbol to display after each number.
+    pub fn symbol(&self) -> &'static str {
+        match *self {
+            SizeUnit::Kilo => "k",   SizeUnit::Kibi => "Ki",
+            SizeUnit::Mega => "M",   SizeUnit::Mebi => "Mi",
+            SizeUnit::Giga => "G",   SizeUnit::Gibi => "Gi",
+            SizeUnit::Tera => "T",   SizeUnit::Tebi => "Ti",
         }
     }
 
-    /// How to display an error when the word didn't match with anything.
-    fn none(field: &str) -> Misfire {
-        Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--time {}", field)))
+    /// The number of bytes in one of this unit.
+    pub fn bytes(&self) -> f64 {
+        match *self {
+            SizeUnit::Kilo => 1000f64,
+            SizeUnit::Mega => 1000f64 * 1000f64,
+            SizeUnit::Giga => 1000f64 * 1000f64 * 1000f64,
+            SizeUnit::Tera => 1000f64 * 1000f64 * 1000f64 * 1000f64,
+            SizeUnit::Kibi => 1024f64,
+            SizeUnit::Mebi => 1024f64 * 1024f64,
+            SizeUnit::Gibi => 1024f64 * 1024f64 * 1024f64,
+            SizeUnit::Tebi => 1024f64 * 1024f64 * 1024f64 * 1024f64,
+        }
     }
 }
 
 
-/// What to do when encountering a directory?
+/// How the cells of the header row should be styled.
 #[derive(PartialEq, Debug, Copy, Clone)]
-pub enum DirAction {
-    AsFile,
-    List,
-    Recurse(RecurseOptions),
+pub enum HeaderStyle {
+
+    /// Every cell uses the same header style, which is underlined.
+    Uniform,
+
+    /// Every cell is left unstyled.
+    Plain,
+
+    /// Every cell takes on the colour of the column beneath it, so the
+    /// header doubles as a legend.
+    PerColumn,
+}
+
+impl Default for HeaderStyle {
+    fn default() -> HeaderStyle {
+        HeaderStyle::Uniform
+    }
+}
+
+impl HeaderStyle {
+    pub fn deduce(matches: &getopts::Matches) -> Result<HeaderStyle, Misfire> {
+        let word = match matches.opt_str("header-style") {
+            Some(word) => word,
+            None       => return Ok(HeaderStyle::default()),
+        };
+
+        if !matches.opt_present("header") {
+            return Err(Misfire::Useless("header-style", false, "header"));
+        }
+
+        match &word[..] {
+            "underline"  => Ok(HeaderStyle::Uniform),
+            "plain"      => Ok(HeaderStyle::Plain),
+            "columns"    => Ok(HeaderStyle::PerColumn),
+            style        => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--header-style {}", style)))),
+        }
+    }
+}
+
+
+/// The set of characters the tree gets drawn with.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TreeStyle {
+    Light,
+    Heavy,
+    Double,
+    Rounded,
+    Ascii,
+}
+
+impl Default for TreeStyle {
+    fn default() -> TreeStyle {
+        TreeStyle::Light
+    }
+}
+
+impl TreeStyle {
+    pub fn deduce(matches: &getopts::Matches) -> Result<TreeStyle, Misfire> {
+        let word = match matches.opt_str("tree-style") {
+            Some(word) => word,
+            None       => return Ok(TreeStyle::default()),
+        };
+
+        if !matches.opt_present("tree") {
+            return Err(Misfire::Useless("tree-style", false, "tree"));
+        }
+
+        match &word[..] {
+            "light"    => Ok(TreeStyle::Light),
+            "heavy"    => Ok(TreeStyle::Heavy),
+            "double"   => Ok(TreeStyle::Double),
+            "rounded"  => Ok(TreeStyle::Rounded),
+            "ascii"    => Ok(TreeStyle::Ascii),
+            style      => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--tree-style {}", style)))),
+        }
+    }
+}
+
+
+/// Where to show errors that occur while reading files and directories.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ErrorStyle {
+
+    /// Each error gets a row of its own, underneath the file it's about.
+    Rows,
+
+    /// Errors get added to the end of the row of the file they're about,
+    /// without adding any rows.
+    Inline,
+}
+
+impl Default for ErrorStyle {
+    fn default() -> ErrorStyle {
+        ErrorStyle::Rows
+    }
+}
+
+impl ErrorStyle {
+    pub fn deduce(matches: &getopts::Matches) -> Result<ErrorStyle, Misfire> {
+        match matches.opt_str("error-style").as_ref().map(|s| &s[..]) {
+            None            => Ok(ErrorStyle::default()),
+            Some("rows")    => Ok(ErrorStyle::Rows),
+            Some("inline")  => Ok(ErrorStyle::Inline),
+            Some(style)     => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--error-style {}", style)))),
+        }
+    }
+}
+
+
+/// What to split the details view into sections by.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum GroupBy {
+
+    /// Directories, files, symlinks, and everything else.
+    Type,
+
+    /// Each file's extension.
+    Extension,
+
+    /// The user that owns each file.
+    Owner,
+}
+
+impl GroupBy {
+    pub fn deduce(matches: &getopts::Matches) -> Result<Option<GroupBy>, Misfire> {
+        match matches.opt_str("group-by").as_ref().map(|s| &s[..]) {
+            None               => Ok(None),
+            Some("type")       => Ok(Some(GroupBy::Type)),
+            Some("extension")  => Ok(Some(GroupBy::Extension)),
+            Some("owner")      => Ok(Some(GroupBy::Owner)),
+            Some(field)        => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--group-by {}", field)))),
+        }
+    }
+}
+
+
+/// The narrowest and widest a type of column is allowed to be.
+#[derive(PartialEq, Debug, Copy, Clone, Default)]
+pub struct WidthLimit {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+impl WidthLimit {
+
+    /// Read the limits from a comma-separated list of `name=min:max`
+    /// entries, where either number can be left out, and a lone number sets
+    /// both. Names that aren't the name of any column are an error.
+    pub fn deduce(matches: &getopts::Matches) -> Result<HashMap<String, WidthLimit>, Misfire> {
+        let mut limits = HashMap::new();
+
+        let spec = match matches.opt_str("column-widths") {
+            Some(spec)  => spec,
+            None        => return Ok(limits),
+        };
+
+        for entry in spec.split(',') {
+            let bad_entry = || Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--column-widths {}", entry)));
+
+            let mut parts = entry.splitn(2, '=');
+            let (name, range) = match (parts.next(), parts.next()) {
+                (Some(name), Some(range))  => (name, range),
+                _                          => return Err(bad_entry()),
+            };
+
+            if !Column::is_name(name) {
+                return Err(bad_entry());
+            }
+
+            let number = |word: &str| -> Result<Option<usize>, Misfire> {
+                if word.is_empty() { Ok(None) }
+                              else { word.parse().map(Some).map_err(Misfire::FailedParse) }
+            };
+
+            let limit = match range.find(':') {
+                Some(index) => WidthLimit { min: try!(number(&range[.. index])), max: try!(number(&range[index + 1 ..])) },
+                None        => { let width = try!(number(range)); WidthLimit { min: width, max: width } },
+            };
+
+            let _ = limits.insert(name.to_string(), limit);
+        }
+
+        Ok(limits)
+    }
+
+    /// Constrain the given width to these limits.
+    pub fn apply(&self, width: usize) -> usize {
+        let width = match self.min {
+            Some(min)  => cmp::max(width, min),
+            None       => width,
+        };
+
+        match self.max {
+            Some(max)  => cmp::min(width, max),
+            None       => width,
+        }
+    }
+}
+
+
+/// One stop of the age gradient: files modified less than this many
+/// seconds ago get painted in this 256-colour palette colour, unless they
+/// fall under an earlier stop.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct AgeStop {
+    pub age: i64,
+    pub colour: u8,
+}
+
+impl AgeStop {
+
+    /// Read the stops from a comma-separated list of `duration=colour`
+    /// entries, such as `1h=46,1d=82`, sorted from youngest to oldest. Files
+    /// older than every stop get the last one's colour. Without a list, the
+    /// gradient goes from bright green to red over a year.
+    pub fn deduce(matches: &getopts::Matches) -> Result<Vec<AgeStop>, Misfire> {
+        let spec = matches.opt_str("age-stops").unwrap_or_else(|| "1h=46,1d=82,1w=226,30d=214,365d=196".to_string());
+        let mut stops = Vec::new();
+
+        for entry in spec.split(',') {
+            let mut parts = entry.splitn(2, '=');
+            let (age, colour) = match (parts.next(), parts.next()) {
+                (Some(age), Some(colour))  => (age, colour),
+                _  => return Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--age-stops {}", entry)))),
+            };
+
+            stops.push(AgeStop {
+                age:    try!(parse_duration("age-stops", age.to_string())),
+                colour: try!(colour.parse().map_err(Misfire::FailedParse)),
+            });
+        }
+
+        stops.sort_by(|a, b| a.age.cmp(&b.age));
+        Ok(stops)
+    }
+}
+
+
+/// How to display the permissions column.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum PermissionsFormat {
+
+    /// The `ls`-style string of flags, such as `.rwxr-xr-x`.
+    Symbolic,
+
+    /// The `chmod`-style octal number, such as `0755`.
+    Octal,
+
+    /// The octal number, followed by the string of flags.
+    Both,
+
+    /// Just the character for the file's type, such as `d`, without any
+    /// of the permission bits.
+    TypeOnly,
+
+    /// The string of flags without the file type character at the start,
+    /// such as `rwxr-xr-x`, for when the colours already show the type.
+    Bits,
+}
+
+impl Default for PermissionsFormat {
+    fn default() -> PermissionsFormat {
+        PermissionsFormat::Symbolic
+    }
+}
+
+impl PermissionsFormat {
+    pub fn deduce(matches: &getopts::Matches) -> Result<PermissionsFormat, Misfire> {
+        match matches.opt_str("permissions") {
+            None => Ok(PermissionsFormat::default()),
+            Some(word) => match &word[..] {
+                "symbolic"  => Ok(PermissionsFormat::Symbolic),
+                "octal"     => Ok(PermissionsFormat::Octal),
+                "both"      => Ok(PermissionsFormat::Both),
+                "type"      => Ok(PermissionsFormat::TypeOnly),
+                "bits"      => Ok(PermissionsFormat::Bits),
+                format      => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--permissions {}", format)))),
+            },
+        }
+    }
+}
+
+
+/// The algorithm to use for the checksum column.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ChecksumAlgorithm {
+    Crc32,
+    Md5,
+}
+
+impl ChecksumAlgorithm {
+    fn deduce(matches: &getopts::Matches) -> Result<Option<ChecksumAlgorithm>, Misfire> {
+        match matches.opt_str("checksum") {
+            None => Ok(None),
+            Some(word) => match &word[..] {
+                "crc32"  => Ok(Some(ChecksumAlgorithm::Crc32)),
+                "md5"    => Ok(Some(ChecksumAlgorithm::Md5)),
+                other    => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--checksum {}", other)))),
+            },
+        }
+    }
+}
+
+
+/// The style to format dates in, named after the ones GNU `ls` has.
+#[derive(PartialEq, Debug, Clone)]
+pub enum TimeStyle {
+
+    /// exa's own style: the day, month, and time for this year's dates,
+    /// and the day, month, and year for older ones.
+    Default,
+
+    /// The full date and time, down to the nanosecond, with the offset
+    /// of the time zone.
+    FullISO,
+
+    /// The date and time, down to the minute.
+    LongISO,
+
+    /// The month, day, and time for this year's dates, and the date
+    /// without a time for older ones.
+    ISO,
+
+    /// A `strftime`-style format string of the user's own.
+    Custom(String),
+}
+
+impl Default for TimeStyle {
+    fn default() -> TimeStyle {
+        TimeStyle::Default
+    }
+}
+
+impl TimeStyle {
+    pub fn deduce(matches: &getopts::Matches) -> Result<TimeStyle, Misfire> {
+        let word = match matches.opt_str("time-style") {
+            Some(word) => word,
+            None       => return Ok(TimeStyle::default()),
+        };
+
+        if word.starts_with('+') {
+            return Ok(TimeStyle::Custom(word[1..].to_string()));
+        }
+
+        match &word[..] {
+            "default" | "locale"  => Ok(TimeStyle::Default),
+            "full-iso"            => Ok(TimeStyle::FullISO),
+            "long-iso"            => Ok(TimeStyle::LongISO),
+            "iso"                 => Ok(TimeStyle::ISO),
+            style                 => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--time-style {}", style)))),
+        }
+    }
+}
+
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TimeType {
+    FileAccessed,
+    FileModified,
+    FileCreated,
+}
+
+impl TimeType {
+    pub fn header(&self) -> &'static str {
+        match *self {
+            TimeType::FileAccessed  => "Date Accessed",
+            TimeType::FileModified  => "Date Modified",
+            TimeType::FileCreated   => "Date Created",
+        }
+    }
+}
+
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct TimeTypes {
+    accessed: bool,
+    modified: bool,
+    created:  bool,
+}
+
+impl Default for TimeTypes {
+    fn default() -> TimeTypes {
+        TimeTypes { accessed: false, modified: true, created: false }
+    }
+}
+
+impl TimeTypes {
+
+    /// Find which field to use based on a user-supplied word.
+    fn deduce(matches: &getopts::Matches) -> Result<TimeTypes, Misfire> {
+        let possible_word = matches.opt_str("time");
+        let modified = matches.opt_present("modified");
+        let created  = matches.opt_present("created");
+        let accessed = matches.opt_present("accessed");
+
+        if let Some(word) = possible_word {
+            if modified {
+                return Err(Misfire::Useless("modified", true, "time"));
+            }
+            else if created {
+                return Err(Misfire::Useless("created", true, "time"));
+            }
+            else if accessed {
+                return Err(Misfire::Useless("accessed", true, "time"));
+            }
+
+            match &word[..] {
+                "mod" | "modified"  => Ok(TimeTypes { accessed: false, modified: true, created: false }),
+                "acc" | "accessed"  => Ok(TimeTypes { accessed: true, modified: false, created: false }),
+                "cr"  | "created"   => Ok(TimeTypes { accessed: false, modified: false, created: true }),
+                field   => Err(TimeTypes::none(field)),
+            }
+        }
+        else {
+            if modified || created || accessed {
+                Ok(TimeTypes { accessed: accessed, modified: modified, created: created })
+            }
+            else {
+                Ok(TimeTypes::default())
+            }
+        }
+    }
+
+    /// How to display an error when the word didn't match with anything.
+    fn none(field: &str) -> Misfire {
+        Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--time {}", field)))
+    }
+}
+
+
+/// What to do when encountering a directory?
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum DirAction {
+    AsFile,
+    List,
+    Recurse(RecurseOptions),
 }
 
 impl DirAction {
@@ -537,6 +1678,10 @@ impl DirAction {
 pub struct RecurseOptions {
     pub tree:      bool,
     pub max_depth: Option<usize>,
+
+    /// The most entries to show for each directory in the tree, with the
+    /// rest summed up in a single row.
+    pub max_children: Option<usize>,
 }
 
 impl RecurseOptions {
@@ -551,9 +1696,19 @@ impl RecurseOptions {
             None
         };
 
+        let max_children = match matches.opt_str("max-children") {
+            Some(_) if !tree => return Err(Misfire::Useless("max-children", false, "tree")),
+            Some(count) => match count.parse() {
+                Ok(c)  => Some(c),
+                Err(e) => return Err(Misfire::FailedParse(e)),
+            },
+            None => None,
+        };
+
         Ok(RecurseOptions {
             tree: tree,
             max_depth: max_depth,
+            max_children: max_children,
         })
     }
 
@@ -570,51 +1725,195 @@ impl RecurseOptions {
 
 #[derive(PartialEq, Copy, Clone, Debug, Default)]
 pub struct Columns {
+    permissions_format: PermissionsFormat,
+    access: bool,
     size_format: SizeFormat,
+    byte_suffix: bool,
+    allocated: bool,
+    dir_entries: bool,
+    dir_totals: bool,
+    unit_space: bool,
+    unit_case: UnitCase,
+    decimals: Option<usize>,
+    size_bar: bool,
+    size_percent: bool,
+    fs_percent: bool,
+    sparseness: bool,
+    large_threshold: Option<u64>,
+    checksum: Option<ChecksumAlgorithm>,
+    line_count: bool,
     time_types: TimeTypes,
     inode: bool,
+    parent_inode: bool,
+    mount: bool,
     links: bool,
+    link_depth: bool,
     blocks: bool,
     group: bool,
-    git: bool
+    xattr_count: bool,
+    capabilities: bool,
+    tree_total: bool,
+    subdir_count: bool,
+    rank: bool,
+    last_activity: bool,
+    meta_changed: bool,
+    lifetime: bool,
+    age_days: bool,
+    age_gradient: bool,
+    mime: bool,
+    interpreter: bool,
+    git: bool,
+    git_summary: bool,
+    git_ignored: bool,
+    git_commit: bool,
 }
 
 impl Columns {
     pub fn deduce(matches: &getopts::Matches) -> Result<Columns, Misfire> {
         Ok(Columns {
+            permissions_format: try!(PermissionsFormat::deduce(matches)),
+            access: matches.opt_present("access"),
             size_format: try!(SizeFormat::deduce(matches)),
+            byte_suffix: matches.opt_present("byte-suffix"),
+            allocated:   matches.opt_present("allocated"),
+            dir_entries: matches.opt_present("dir-entries"),
+            dir_totals:  matches.opt_present("dir-totals"),
+            unit_space:  matches.opt_present("unit-space"),
+            unit_case:   try!(UnitCase::deduce(matches)),
+            decimals:    try!(SizeOptions::deduce_decimals(matches)),
+            size_bar:    matches.opt_present("size-bar"),
+            size_percent: matches.opt_present("size-percent"),
+            fs_percent: matches.opt_present("fs-percent"),
+            sparseness:  matches.opt_present("sparseness"),
+            large_threshold: match matches.opt_str("large") {
+                Some(word)  => Some(try!(parse_size("large", word))),
+                None        => None,
+            },
+            checksum:    try!(ChecksumAlgorithm::deduce(matches)),
+            line_count:  matches.opt_present("lines"),
             time_types:  try!(TimeTypes::deduce(matches)),
             inode:  matches.opt_present("inode"),
+            parent_inode: matches.opt_present("parent-inode"),
+            mount:  matches.opt_present("mount"),
             links:  matches.opt_present("links"),
+            link_depth: matches.opt_present("link-depth"),
             blocks: matches.opt_present("blocks"),
             group:  matches.opt_present("group"),
+            xattr_count: xattr::ENABLED && matches.opt_present("xattr-count"),
+            capabilities: cfg!(target_os = "linux") && matches.opt_present("capabilities"),
+            tree_total: matches.opt_present("tree-total"),
+            subdir_count: matches.opt_present("subdirs"),
+            rank:   matches.opt_present("rank"),
+            last_activity: matches.opt_present("last-activity"),
+            meta_changed: matches.opt_present("meta-changed"),
+            lifetime: matches.opt_present("lifetime"),
+            age_days: matches.opt_present("age-days"),
+            age_gradient: matches.opt_present("age-gradient"),
+            mime:     matches.opt_present("mime"),
+            interpreter: matches.opt_present("interpreter"),
             git:    cfg!(feature="git") && matches.opt_present("git"),
+            git_summary: cfg!(feature="git") && matches.opt_present("git-summary"),
+            git_ignored: cfg!(feature="git") && matches.opt_present("git-ignored"),
+            git_commit:  cfg!(feature="git") && matches.opt_present("git-commit"),
         })
     }
 
     pub fn should_scan_for_git(&self) -> bool {
-        self.git
+        self.git || self.git_summary || self.git_ignored || self.git_commit
     }
 
     pub fn for_dir(&self, dir: Option<&Dir>) -> Vec<Column> {
         let mut columns = vec![];
 
+        if self.rank {
+            columns.push(Rank);
+        }
+
         if self.inode {
             columns.push(Inode);
         }
 
-        columns.push(Permissions);
+        if self.parent_inode {
+            columns.push(ParentInode);
+        }
+
+        columns.push(Permissions(self.permissions_format));
+
+        if self.access {
+            columns.push(Access);
+        }
+
+        if self.xattr_count {
+            columns.push(XattrCount);
+        }
+
+        if self.capabilities {
+            columns.push(Capabilities);
+        }
 
         if self.links {
             columns.push(HardLinks);
         }
 
-        columns.push(FileSize(self.size_format));
+        if self.link_depth {
+            columns.push(LinkDepth);
+        }
+
+        columns.push(FileSize(SizeOptions {
+            format:      self.size_format,
+            byte_suffix: self.byte_suffix,
+            allocated:   self.allocated,
+            dir_entries: self.dir_entries,
+            dir_totals:  self.dir_totals,
+            unit_space:  self.unit_space,
+            unit_case:   self.unit_case,
+            decimals:    self.decimals,
+        }));
+
+        if self.size_bar {
+            columns.push(SizeBar);
+        }
+
+        if self.size_percent {
+            columns.push(SizePercent);
+        }
+
+        if self.fs_percent {
+            columns.push(FsPercent);
+        }
+
+        if self.sparseness {
+            columns.push(Sparseness);
+        }
+
+        if let Some(threshold) = self.large_threshold {
+            columns.push(LargeFlag(threshold));
+        }
+
+        if self.line_count {
+            columns.push(LineCount);
+        }
+
+        if let Some(algorithm) = self.checksum {
+            columns.push(Checksum(algorithm));
+        }
 
         if self.blocks {
             columns.push(Blocks);
         }
 
+        if self.mount {
+            columns.push(Mount);
+        }
+
+        if self.tree_total {
+            columns.push(Descendants);
+        }
+
+        if self.subdir_count {
+            columns.push(SubdirCount);
+        }
+
         columns.push(User);
 
         if self.group {
@@ -633,11 +1932,51 @@ impl Columns {
             columns.push(Timestamp(TimeType::FileAccessed));
         }
 
+        if self.last_activity {
+            columns.push(LastActivity);
+        }
+
+        if self.meta_changed {
+            columns.push(MetaChanged);
+        }
+
+        if self.lifetime {
+            columns.push(Lifetime);
+        }
+
+        if self.age_days {
+            columns.push(AgeDays);
+        }
+
+        if self.age_gradient {
+            columns.push(AgeGradient);
+        }
+
+        if self.mime {
+            columns.push(MimeType);
+        }
+
+        if self.interpreter {
+            columns.push(Interpreter);
+        }
+
         if cfg!(feature="git") {
             if let Some(d) = dir {
-                if self.should_scan_for_git() && d.has_git_repo() {
+                if self.git && d.has_git_repo() {
                     columns.push(GitStatus);
                 }
+
+                if self.git_summary && d.has_git_repo() {
+                    columns.push(GitSummary);
+                }
+
+                if self.git_ignored && d.has_git_repo() {
+                    columns.push(GitIgnored);
+                }
+
+                if self.git_commit && d.has_git_repo() {
+                    columns.push(GitLastCommit);
+                }
             }
         }
 
@@ -647,9 +1986,13 @@ impl Columns {
 
 #[cfg(test)]
 mod test {
-    use super::Options;
+    use super::{Options, FileFilter, SortField, View, WidthLimit};
+    use super::{SizeOptions, SizeUnit, UnitCase};
     use super::Misfire;
     use feature::xattr;
+    use getopts;
+    use glob::Glob;
+    use output::Details;
 
     fn is_helpful<T>(misfire: Result<T, Misfire>) -> bool {
         match misfire {
@@ -688,6 +2031,57 @@ mod test {
         assert_eq!(opts.unwrap_err(), Misfire::Conflict("binary", "bytes"))
     }
 
+    #[test]
+    fn both_prefixes_and_binary() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--both-prefixes".to_string(), "--binary".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("both-prefixes", "binary"))
+    }
+
+    #[test]
+    fn unit_case_prefixes() {
+        let units = [ SizeUnit::Kilo, SizeUnit::Mega, SizeUnit::Giga, SizeUnit::Tera,
+                      SizeUnit::Kibi, SizeUnit::Mebi, SizeUnit::Gibi, SizeUnit::Tebi ];
+
+        let upper = SizeOptions { unit_case: UnitCase::Upper, ..SizeOptions::default() };
+        let lower = SizeOptions { unit_case: UnitCase::Lower, ..SizeOptions::default() };
+
+        let symbols: Vec<_> = units.iter().map(|u| upper.unit_symbol(u.symbol())).collect();
+        assert_eq!(symbols, vec![ "K", "M", "G", "T", "Ki", "Mi", "Gi", "Ti" ]);
+
+        let symbols: Vec<_> = units.iter().map(|u| lower.unit_symbol(u.symbol())).collect();
+        assert_eq!(symbols, vec![ "k", "M", "G", "T", "Ki", "Mi", "Gi", "Ti" ]);
+    }
+
+    #[test]
+    fn size_unit_and_binary() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--size-unit".to_string(), "Ki".to_string(), "--binary".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("size-unit", "binary"))
+    }
+
+    #[test]
+    fn size_decimals_without_unit() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--size-decimals".to_string(), "2".to_string() ]);
+        assert!(opts.is_ok())
+    }
+
+    #[test]
+    fn size_decimals_without_unit_too_many() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--size-decimals".to_string(), "3".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption("--size-decimals 3".to_string())))
+    }
+
+    #[test]
+    fn size_decimals_with_unit() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--size-unit".to_string(), "M".to_string(), "--size-decimals".to_string(), "4".to_string() ]);
+        assert!(opts.is_ok())
+    }
+
+    #[test]
+    fn size_decimals_and_bytes() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--size-decimals".to_string(), "2".to_string(), "--bytes".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("size-decimals", "bytes"))
+    }
+
     #[test]
     fn just_binary() {
         let opts = Options::getopts(&[ "--binary".to_string() ]);
@@ -718,6 +2112,63 @@ mod test {
         assert_eq!(opts.unwrap_err(), Misfire::Useless("header", false, "long"))
     }
 
+    #[test]
+    fn header_style_without_header() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--header-style".to_string(), "plain".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("header-style", false, "header"))
+    }
+
+    #[test]
+    fn json_lines_with_html() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--json-lines".to_string(), "--html".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("json-lines", "html"))
+    }
+
+    /// Parse the given arguments, which have to give the tree view, and
+    /// return its details. The tree shouldn't depend on the output being a
+    /// terminal, so this should work however the tests get run.
+    fn tree_details(args: &[&str]) -> Details {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let (options, _) = Options::getopts(&args).unwrap();
+
+        match options.view {
+            View::Details(details) => details,
+            _                      => panic!("not the details view"),
+        }
+    }
+
+    #[test]
+    fn tree_json_lines() {
+        let details = tree_details(&[ "--tree", "--json-lines" ]);
+        assert!(details.json_lines);
+        assert!(details.recurse.map(|r| r.tree).unwrap_or(false));
+    }
+
+    #[test]
+    fn tree_flat_tree() {
+        let details = tree_details(&[ "--tree", "--flat-tree" ]);
+        assert!(details.flat_tree);
+        assert!(details.recurse.map(|r| r.tree).unwrap_or(false));
+    }
+
+    #[test]
+    fn tree_html() {
+        let details = tree_details(&[ "--tree", "--html" ]);
+        assert!(details.html);
+    }
+
+    #[test]
+    fn dir_totals_with_tree() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--tree".to_string(), "--dir-totals".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("dir-totals", "tree"))
+    }
+
+    #[test]
+    fn dir_entries_with_tree() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--tree".to_string(), "--dir-entries".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("dir-entries", "tree"))
+    }
+
     #[test]
     fn just_group() {
         let opts = Options::getopts(&[ "--group".to_string() ]);
@@ -749,6 +2200,20 @@ mod test {
         assert_eq!(opts.unwrap_err(), Misfire::Useless("git", false, "long"))
     }
 
+    #[test]
+    #[cfg(feature="git")]
+    fn just_git_ignored() {
+        let opts = Options::getopts(&[ "--git-ignored".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("git-ignored", false, "long"))
+    }
+
+    #[test]
+    #[cfg(feature="git")]
+    fn just_git_commit() {
+        let opts = Options::getopts(&[ "--git-commit".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("git-commit", false, "long"))
+    }
+
     #[test]
     fn extended_without_long() {
         if xattr::ENABLED {
@@ -757,9 +2222,245 @@ mod test {
         }
     }
 
+    #[test]
+    fn truncate_without_long_or_tree() {
+        let opts = Options::getopts(&[ "--truncate".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless2("truncate", "long", "tree"))
+    }
+
+    fn sorted_names(filter: FileFilter) -> Vec<String> {
+        use std::fs;
+        use std::io::Write;
+        use file::File;
+        use test_support::TempDir;
+
+        let temp = TempDir::new("sorted-names");
+        fs::create_dir_all(temp.path.join("src/output")).unwrap();
+        fs::File::create(temp.path.join("Cargo.toml")).unwrap().write_all(b"[package]\n").unwrap();
+        fs::File::create(temp.path.join("LICENCE")).unwrap().write_all(b"The MIT License (MIT)\n\nCopyright (c) 2014\n").unwrap();
+
+        let mut files: Vec<File> = [ "LICENCE", "src", "Cargo.toml", "src/output" ].iter()
+            .map(|p| File::from_path(&temp.path.join(p), None).unwrap())
+            .collect();
+
+        filter.sort_files(&mut files);
+        files.into_iter().map(|f| f.name).collect()
+    }
+
+    #[test]
+    fn pinned_first() {
+        use std::fs;
+        use file::File;
+        use test_support::TempDir;
+
+        let temp = TempDir::new("pinned");
+        let path = temp.path.clone();
+        for name in &[ "Cargo.toml", "README.md", "build.rs" ] {
+            let _ = fs::File::create(path.join(name)).unwrap();
+        }
+
+        let names = |filter: FileFilter| {
+            let mut files: Vec<File> = [ "build.rs", "README.md", "Cargo.toml" ].iter()
+                .map(|n| File::from_path(&path.join(n), None).unwrap())
+                .collect();
+
+            filter.sort_files(&mut files);
+            files.into_iter().map(|f| f.name).collect::<Vec<_>>()
+        };
+
+        let filter = FileFilter { sort_field: SortField::Name, pinned: vec![ Glob::new("README*") ], ..FileFilter::default() };
+        assert_eq!(names(filter), vec![ "README.md", "Cargo.toml", "build.rs" ]);
+
+        let filter = FileFilter { sort_field: SortField::Name, reverse: true, pinned: vec![ Glob::new("README*") ], ..FileFilter::default() };
+        assert_eq!(names(filter), vec![ "README.md", "build.rs", "Cargo.toml" ]);
+    }
+
+    #[test]
+    fn precise_times_and_time_style() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--precise-times".to_string(), "--time-style=iso".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("precise-times", "time-style"))
+    }
+
+    #[test]
+    fn bad_time_style() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--time-style=posix".to_string() ]);
+        assert!(opts.is_err())
+    }
+
+    #[test]
+    fn missing_theme() {
+        let opts = Options::getopts(&[ "--theme=/exa-test-no-such-theme".to_string() ]);
+        match opts {
+            Err(Misfire::BadTheme(message))  => assert!(message.starts_with("/exa-test-no-such-theme: ")),
+            _                                => panic!("expected a bad theme"),
+        }
+    }
+
+    #[test]
+    fn error_style_alone() {
+        let opts = Options::getopts(&[ "--error-style=inline".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless2("error-style", "long", "tree"))
+    }
+
+    #[test]
+    fn bad_error_style() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--error-style=nowhere".to_string() ]);
+        assert!(opts.is_err())
+    }
+
+    #[test]
+    fn tree_style_without_tree() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--tree-style=ascii".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("tree-style", false, "tree"))
+    }
+
+    #[test]
+    fn dirs_first_by_name() {
+        let filter = FileFilter { dirs_first: true, sort_field: SortField::Name, ..FileFilter::default() };
+        assert_eq!(sorted_names(filter), vec![ "output", "src", "Cargo.toml", "LICENCE" ])
+    }
+
+    #[test]
+    fn dirs_first_by_size() {
+        let filter = FileFilter { dirs_first: true, sort_field: SortField::Size, ..FileFilter::default() };
+        let names = sorted_names(filter);
+        assert!(names[0] == "output" || names[0] == "src");
+        assert!(names[1] == "output" || names[1] == "src");
+        assert_eq!(&names[2..], &[ "Cargo.toml", "LICENCE" ])
+    }
+
+    #[test]
+    fn dirs_first_reversed() {
+        let filter = FileFilter { dirs_first: true, reverse: true, sort_field: SortField::Name, ..FileFilter::default() };
+        assert_eq!(sorted_names(filter), vec![ "src", "output", "LICENCE", "Cargo.toml" ])
+    }
+
+    #[test]
+    fn age_stops_without_gradient() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--age-stops".to_string(), "1d=46".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("age-stops", false, "age-gradient"))
+    }
+
+    #[test]
+    fn age_stops_invalid_without_colour() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--age-gradient".to_string(), "--age-stops".to_string(), "1d".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption("--age-stops 1d".to_string())))
+    }
+
+    #[test]
+    fn human_sizes() {
+        assert_eq!(super::parse_size("large", "100M".to_string()), Ok(100_000_000));
+        assert_eq!(super::parse_size("large", "1.5Ki".to_string()), Ok(1536));
+        assert_eq!(super::parse_size("large", "512".to_string()), Ok(512));
+        assert!(super::parse_size("large", "lots".to_string()).is_err());
+    }
+
+    #[test]
+    fn modified_within() {
+        let filter = FileFilter { modified_within: Some(60 * 60), ..FileFilter::default() };
+        let now = 1_000_000;
+
+        let mtimes = vec![ now, now - 30 * 60, now - 60 * 60, now - 60 * 60 - 1, now - 60 * 60 * 24 ];
+        let recent: Vec<i64> = mtimes.into_iter().filter(|&t| filter.is_recent(t, now)).collect();
+        assert_eq!(recent, vec![ now, now - 30 * 60, now - 60 * 60 ])
+    }
+
+    #[test]
+    fn modified_within_days() {
+        let opts = Options::getopts(&[ "--modified-within".to_string(), "2d".to_string() ]).unwrap().0;
+        assert_eq!(opts.filter.modified_within, Some(2 * 24 * 60 * 60))
+    }
+
+    #[test]
+    fn modified_within_negative() {
+        let opts = Options::getopts(&[ "--modified-within=-2d".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption("--modified-within -2d".to_string())))
+    }
+
+    #[test]
+    fn modified_within_overflow() {
+        let opts = Options::getopts(&[ "--modified-within".to_string(), "9223372036854775807w".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption("--modified-within 9223372036854775807w".to_string())))
+    }
+
+    #[test]
+    fn modified_within_nonsense() {
+        let opts = Options::getopts(&[ "--modified-within".to_string(), "soon".to_string() ]);
+        assert!(opts.is_err())
+    }
+
+    #[test]
+    fn tree_paths_without_tree() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--tree-paths".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("tree-paths", false, "tree"))
+    }
+
+    #[test]
+    fn flat_tree_without_tree() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--flat-tree".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("flat-tree", false, "tree"))
+    }
+
     #[test]
     fn level_without_recurse_or_tree() {
         let opts = Options::getopts(&[ "--level".to_string(), "69105".to_string() ]);
         assert_eq!(opts.unwrap_err(), Misfire::Useless2("level", "recurse", "tree"))
     }
+
+    #[test]
+    fn group_by_nothing() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--group-by".to_string(), "colour".to_string() ]);
+        assert!(opts.is_err())
+    }
+
+    #[test]
+    fn group_by_tree() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--tree".to_string(), "--group-by".to_string(), "type".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("group-by", "tree"))
+    }
+
+    #[test]
+    fn group_by_grid() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--grid".to_string(), "--group-by".to_string(), "type".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("group-by", true, "grid"))
+    }
+
+    #[test]
+    fn diff_one_directory() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--diff".to_string(), "old".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::DiffPaths(1))
+    }
+
+    #[test]
+    fn diff_tree() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--diff".to_string(), "--tree".to_string(), "old".to_string(), "new".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("diff", "tree"))
+    }
+
+    #[test]
+    fn width_limits() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--column-widths".to_string(), "date=:12,user=8:,size=5".to_string() ]);
+        let (options, _) = opts.unwrap();
+
+        let limits = match options.view {
+            View::Details(details) => details.width_limits,
+            _                      => panic!("not the details view"),
+        };
+
+        assert_eq!(limits["date"], WidthLimit { min: None,    max: Some(12) });
+        assert_eq!(limits["user"], WidthLimit { min: Some(8), max: None });
+        assert_eq!(limits["size"], WidthLimit { min: Some(5), max: Some(5) });
+    }
+
+    #[test]
+    fn width_limit_unknown_column() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--column-widths".to_string(), "user=3,colour=5".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption("--column-widths colour=5".to_string())))
+    }
+
+    #[test]
+    fn width_limit_without_range() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--column-widths".to_string(), "date".to_string() ]);
+        assert!(opts.is_err())
+    }
 }
@@ -111,22 +111,39 @@
 //! are used in place of the filename.
 
 
+use std::cmp;
+use std::collections::HashMap;
+use std::env::current_dir;
 use std::error::Error;
+use std::fmt;
+use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::io::Write;
+use std::iter::repeat;
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::string::ToString;
+use std::sync::{Arc, Once, ONCE_INIT};
 
 use colours::Colours;
 use column::{Alignment, Column, Cell};
 use dir::Dir;
-use feature::xattr::{Attribute, FileAttributes};
+use feature::capabilities::CapabilitySet;
+use feature::gecos::full_name;
+use feature::groups::primary_members;
+use feature::mounts::{filesystem_size, mount_points};
+use feature::xattr::{self, Attribute, FileAttributes};
 use file::fields as f;
 use file::File;
-use options::{Columns, FileFilter, RecurseOptions, SizeFormat};
+use filetype::{file_colour, NameColouring};
+use glob::Glob;
+use mime;
+use options::{AgeStop, Columns, ErrorStyle, FileFilter, GroupBy, HeaderStyle, PermissionsFormat, RecurseOptions, SizeFormat, SizeOptions, TimeStyle, TimeType, TreeStyle, WidthLimit};
 
 use ansi_term::{ANSIString, ANSIStrings, Style};
+use ansi_term::Colour::Fixed;
 
-use datetime::local::{LocalDateTime, DatePiece};
+use datetime::local::{LocalDateTime, DatePiece, TimePiece};
 use datetime::format::{DateFormat};
 use datetime::zoned::{TimeZone};
 
@@ -134,10 +151,13 @@ use locale;
 
 use number_prefix::{binary_prefix, decimal_prefix, Prefixed, Standalone, PrefixNames};
 
-use users::{OSUsers, Users};
+use unicode_width::UnicodeWidthStr;
+
+use users::{OSUsers, Users, uid_t, gid_t};
 use users::mock::MockUsers;
 
-use super::filename;
+use super::{display_name, link_arrow};
+use super::{html, json};
 
 
 /// With the **Details** view, the output gets formatted into columns, with
@@ -151,7 +171,7 @@ use super::filename;
 ///
 /// Almost all the heavy lifting is done in a Table object, which handles the
 /// columns for each row.
-#[derive(PartialEq, Debug, Copy, Clone, Default)]
+#[derive(PartialEq, Debug, Clone, Default)]
 pub struct Details {
 
     /// A Columns object that says which columns should be included in the
@@ -170,12 +190,173 @@ pub struct Details {
     /// Whether to show a header line or not.
     pub header: bool,
 
+    /// Limits on how wide each type of column can be, keyed by name.
+    pub width_limits: HashMap<String, WidthLimit>,
+
+    /// How to style the header line, if one is shown.
+    pub header_style: HeaderStyle,
+
+    /// Whether to display each file's metadata on the line after its name,
+    /// rather than on the same line.
+    pub two_lines: bool,
+
+    /// How many columns to indent each level of the tree by.
+    pub tree_indent: usize,
+
+    /// The characters to draw the tree with.
+    pub tree_style: TreeStyle,
+
+    /// Whether to draw the tree mirrored, on the right of the names.
+    pub mirror_tree: bool,
+
+    /// Whether errors get rows of their own, or go on their files' rows.
+    pub error_style: ErrorStyle,
+
+    /// A pattern to match file names against. Files that match have their
+    /// names highlighted, to make them easier to pick out.
+    pub highlight: Option<Glob>,
+
+    /// The width to pad highlighted rows out to, so their background
+    /// reaches the edge of the terminal, if any.
+    pub pad_width: Option<usize>,
+
+    /// The width to drop columns until the table fits into, if any.
+    pub compact_width: Option<usize>,
+
+    /// Whether to draw lines around and between the table's cells.
+    pub borders: bool,
+
+    /// Whether to paint every column but the names faintly, so the names
+    /// are the brightest part of each row.
+    pub dim_metadata: bool,
+
+    /// Whether to put each line's number at the start of it.
+    pub line_numbers: bool,
+
+    /// Whether two directories are being compared, rather than listed.
+    pub diff: bool,
+
+    /// Whether to merge chains of directories that each only contain one
+    /// other directory into a single row in the tree view.
+    pub collapse_dirs: bool,
+
+    /// Whether to print the table as HTML instead of for the terminal.
+    pub html: bool,
+
+    /// Whether to print each file as a line of JSON as soon as it's been
+    /// examined, instead of collecting everything into a table first.
+    pub json_lines: bool,
+
+    /// Whether to print a line counting the files of each type after the
+    /// table.
+    pub summary: bool,
+
+    /// Whether to print the absolute path of the directory being listed
+    /// above the table.
+    pub banner: bool,
+
+    /// Whether to print the total number of blocks used by the files being
+    /// listed above the table, like `ls -l` does.
+    pub total_blocks: bool,
+
+    /// Whether to display each file's path relative to the root of the tree
+    /// after its name.
+    pub tree_paths: bool,
+
+    /// Whether to list the tree as each file's path from its root, in place
+    /// of the tree characters and the other columns.
+    pub flat_tree: bool,
+
+    /// Whether to display each file's absolute path instead of its name.
+    pub absolute_paths: bool,
+
+    /// Whether symlinks should show the canonical path of the file at the
+    /// end of their chain of links, instead of what they point to directly.
+    pub canonical_links: bool,
+
+    /// Whether to put a slash after the names of directories, so they can
+    /// be told apart without colours.
+    pub dir_slash: bool,
+
+    /// Whether to quote names that a shell would need quoted.
+    pub quote_names: bool,
+
+    /// How to colour files' names, beyond their file types' colours.
+    pub name_colouring: NameColouring,
+
+    /// Whether to always use English month names, instead of those from the
+    /// user's locale.
+    pub english_dates: bool,
+
+    /// Whether to display dates in UTC instead of the local time zone.
+    pub utc: bool,
+
+    /// Text to put before each group name in the group column, if any.
+    pub group_prefix: Option<String>,
+
+    /// Text to show for values that don't apply, instead of a dash.
+    pub placeholder: Option<String>,
+
+    /// What to split the listing into sections by, if anything. Each
+    /// section gets a heading and a table of its own.
+    pub group_by: Option<GroupBy>,
+
+    /// Whether to show how many members each group has after its name.
+    pub group_members: bool,
+
+    /// Whether to show users' full names instead of their login names.
+    pub full_names: bool,
+
+    /// Whether to show inode numbers in hexadecimal instead of decimal.
+    pub hex_inodes: bool,
+
+    /// Whether to show how many of each hard-linked file's links are in
+    /// the listing.
+    pub link_siblings: bool,
+
+    /// Whether to pad file sizes so their decimal points line up.
+    pub align_decimals: bool,
+
+    /// Whether symlinks should show the metadata of the files they point
+    /// to, rather than their own.
+    pub dereference: bool,
+
+    /// Whether to show dates in full, down to the nanosecond.
+    pub precise_times: bool,
+
+    /// The style to format dates in.
+    pub time_style: TimeStyle,
+
+    /// Whether to replace a user or group that's the same as the one in the
+    /// row above with a ditto mark.
+    pub ditto_owners: bool,
+
+    /// A fixed time to treat as the current time, instead of the time the
+    /// listing is made, if any.
+    pub now: Option<LocalDateTime>,
+
     /// Whether to show each file's extended attributes.
     pub xattr: bool,
 
+    /// Whether to leave out the `@` after the permissions of files that
+    /// have extended attributes, while still listing the attributes.
+    pub hide_xattr_marker: bool,
+
     /// The colours to use to display information in the table, including the
     /// colour of the tree view symbols.
     pub colours: Colours,
+
+    /// The ages at which the age gradient changes colour, which are left
+    /// empty when the output isn't coloured.
+    pub age_stops: Vec<AgeStop>,
+
+    /// The width of the terminal, if rows that don't fit into it should be
+    /// truncated instead of being left to wrap onto the next line.
+    pub console_width: Option<usize>,
+
+    /// Directories with fewer files than this get examined on the current
+    /// thread, rather than by a pool of threads.
+    pub sequential_limit: usize,
 }
 
 impl Details {
@@ -183,6 +364,166 @@ impl Details {
     /// Print the details of the given vector of files -- all of which will
     /// have been read from the given directory, if present -- to stdout.
     pub fn view(&self, dir: Option<&Dir>, files: Vec<File>) {
+        if self.html {
+            return self.view_html(dir, files);
+        }
+
+        // Each file's line gets printed while it's being added, and no rows
+        // get kept in the table, so there's nothing left to print after.
+        if self.json_lines {
+            let mut table = self.make_table(dir);
+            let _ = self.add_files_to_table(&mut table, files, 0, Path::new(""));
+            return;
+        }
+
+        // Print the banner on its own, outside of the table, so it has no
+        // effect on the widths of the columns.
+        if self.banner {
+            if let Some(path) = banner_path(dir, &files) {
+                println!("{}", self.colours.filetypes.directory.paint(&path.to_string_lossy()));
+            }
+        }
+
+        if self.total_blocks {
+            println!("total {}", self.colours.blocks.paint(&total_blocks(&files).to_string()));
+        }
+
+        // When the files are being grouped, each group gets its own table,
+        // so the columns only have to be as wide as that group needs.
+        match self.group_by {
+            Some(group_by) => {
+                for (index, (heading, group)) in group_files(files, group_by).into_iter().enumerate() {
+                    if index > 0 {
+                        println!("");
+                    }
+
+                    println!("{}", self.colours.header.paint(&heading));
+                    self.print_files_table(dir, group);
+                }
+            },
+            None => self.print_files_table(dir, files),
+        }
+    }
+
+    /// Add the given files to a table, and print it out.
+    fn print_files_table(&self, dir: Option<&Dir>, files: Vec<File>) {
+        let mut table = self.make_table(dir);
+        if let Some(width) = self.console_width { table.set_max_width(width) }
+        if self.two_lines { table.use_two_lines() }
+        table.set_tree_indent(self.tree_indent);
+        table.set_tree_style(self.tree_style);
+        if self.mirror_tree { table.use_mirrored_tree() }
+        if self.flat_tree { table.use_flat_tree() }
+
+        let _ = self.add_files_to_table(&mut table, files, 0, Path::new(""));
+        if self.link_siblings { table.add_link_siblings() }
+        if self.align_decimals { table.align_size_decimals() }
+        if self.collapse_dirs { table.collapse_directory_chains() }
+
+        for cell in table.print_table() {
+            println!("{}", cell.text);
+        }
+
+        if self.summary {
+            println!("{}", table.summary());
+        }
+    }
+
+    /// Print the details of the given files as an HTML table, or, in the
+    /// tree view, as nested lists. The same cells get computed as for the
+    /// terminal, but their colours are turned into inline styles.
+    pub fn view_html(&self, dir: Option<&Dir>, files: Vec<File>) {
+        let mut table = self.make_table(dir);
+
+        let _ = self.add_files_to_table(&mut table, files, 0, Path::new(""));
+        if self.link_siblings { table.add_link_siblings() }
+        if self.align_decimals { table.align_size_decimals() }
+        if self.collapse_dirs { table.collapse_directory_chains() }
+
+        let tree = self.recurse.map(|r| r.tree).unwrap_or(false);
+        for line in table.print_html(tree) {
+            println!("{}", line);
+        }
+    }
+
+    /// Print the files that differ between two directories, each marked
+    /// with a `+` if it's only in the new one, a `-` if it's only in the
+    /// old one, or a `~` if it's in both but its size or modified time has
+    /// changed. Files that are the same in both get left out.
+    pub fn view_diff(&self, old: &Dir, new: &Dir) {
+        let mut errors = Vec::new();
+        let old_files = self.read_files(old, &mut errors);
+        let new_files = self.read_files(new, &mut errors);
+
+        let mut diffs = diff_files(old_files, new_files);
+        self.filter.sort_by_file(&mut diffs, |diff| &diff.1);
+
+        let mut table = self.make_table(Some(new));
+        if let Some(width) = self.console_width { table.set_max_width(width) }
+
+        // Files that couldn't be read from either directory go at the top,
+        // as they can't be said to be different or not.
+        for (error, path) in errors {
+            table.add_error(&error, 0, false, path);
+        }
+
+        for (difference, file) in diffs {
+            let egg = self.make_egg(Arc::new(file), 0, Path::new(""), |file, xattrs| table.cells_for_file(file, xattrs));
+            let style = match difference {
+                Difference::Added    => self.colours.git.new,
+                Difference::Removed  => self.colours.git.deleted,
+                Difference::Changed  => self.colours.git.modified,
+            };
+
+            table.rows.push(Row {
+                depth:    0,
+                cells:    Some(egg.cells),
+                name:     egg.name,
+                last:     false,
+                is_dir:   egg.file.is_directory(),
+                highlighted: egg.highlighted,
+                size:     bar_size(egg.file.size()),
+                marker:   Some(Cell::paint(style, difference.marker())),
+            });
+            table.count_file(&egg.file);
+            table.note_hard_links(&egg.file);
+
+            let count = egg.errors.len();
+            for (index, (error, path)) in egg.errors.into_iter().enumerate() {
+                table.add_error(&error, 1, index == count - 1, path);
+            }
+        }
+
+        if self.link_siblings { table.add_link_siblings() }
+        if self.align_decimals { table.align_size_decimals() }
+
+        for cell in table.print_table() {
+            println!("{}", cell.text);
+        }
+
+        if self.summary {
+            println!("{}", table.summary());
+        }
+    }
+
+    /// The files in the given directory that pass the filter. Any that
+    /// couldn't be read get added to the errors, along with their paths.
+    fn read_files<'dir>(&self, dir: &'dir Dir, errors: &mut Vec<(io::Error, Option<PathBuf>)>) -> Vec<File<'dir>> {
+        let mut files = Vec::new();
+        for file in dir.files() {
+            match file {
+                Ok(file)       => files.push(file),
+                Err((path, e)) => errors.push((e, Some(path))),
+            }
+        }
+
+        self.filter.filter_files(&mut files);
+        files
+    }
+
+    /// Create a table with the columns for the given directory, and the
+    /// options that affect the contents of its cells.
+    fn make_table(&self, dir: Option<&Dir>) -> Table<OSUsers> {
 
         // First, transform the Columns object into a vector of columns for
         // the current directory.
@@ -191,110 +532,176 @@ impl Details {
             None => Vec::new(),
         };
 
-        // Next, add a header if the user requests it.
-        let mut table = Table::with_options(self.colours, columns_for_dir);
-        if self.header { table.add_header() }
+        let mut table = self.cells_table(columns_for_dir);
+        if self.header { table.add_header(self.header_style) }
+        if self.ditto_owners { table.use_ditto_owners() }
+        if self.quote_names { table.use_quoted_names() }
+        table.set_name_colouring(self.name_colouring);
+        if let Some(width) = self.pad_width { table.set_pad_width(width) }
+        if let Some(width) = self.compact_width { table.set_compact_width(width) }
+        if self.borders { table.use_borders() }
+        if self.line_numbers { table.use_line_numbers() }
+        table.set_width_limits(self.width_limits.clone());
+        table
+    }
 
-        // Then add files to the table and print it out.
-        self.add_files_to_table(&mut table, files, 0);
-        for cell in table.print_table() {
-            println!("{}", cell.text);
-        }
+    /// Create a table with the given columns, set up with every option
+    /// that changes how a file's cells get rendered. The grid-details view
+    /// renders its cells with one of these too, so the two can't drift.
+    pub fn cells_table(&self, columns: Vec<Column>) -> Table<OSUsers> {
+        let mut table = Table::with_options(self.colours.clone(), columns);
+        if self.english_dates { table.use_english_dates() }
+        if self.utc { table.use_utc() }
+        if self.precise_times { table.use_precise_times() }
+        table.set_time_style(self.time_style.clone());
+        if let Some(now) = self.now { table.set_now(now) }
+        if let Some(ref prefix) = self.group_prefix { table.set_group_prefix(prefix.clone()) }
+        if let Some(ref text) = self.placeholder { table.set_placeholder(text.clone()) }
+        if self.group_members { table.show_group_members() }
+        if self.full_names { table.use_full_names() }
+        if self.hex_inodes { table.use_hex_inodes() }
+        if self.link_siblings { table.use_link_siblings() }
+        if self.dereference { table.use_dereference() }
+        if self.dim_metadata { table.use_dimmed_metadata() }
+        table.set_age_stops(self.age_stops.clone());
+        table
     }
 
     /// Adds files to the table, possibly recursively. This is easily
     /// parallelisable, and uses a pool of threads.
-    fn add_files_to_table<'dir, U: Users+Send>(&self, mut table: &mut Table<U>, src: Vec<File<'dir>>, depth: usize) {
+    ///
+    /// The prefix is the path from the root of the tree to the files being
+    /// added, which is only used when displaying tree paths.
+    ///
+    /// Returns the number of file rows that were added, including those of
+    /// any descendants, and whether any directories went unlisted because
+    /// they were too deep.
+    fn add_files_to_table<'dir, U: Users+Send>(&self, table: &mut Table<U>, mut src: Vec<File<'dir>>, depth: usize, prefix: &Path) -> (usize, bool) {
         use num_cpus;
         use scoped_threadpool::Pool;
-        use std::sync::{Arc, Mutex};
+        use std::sync::Mutex;
+
+        // If there are too many files to show them all, sort them first, so
+        // the ones that get shown are the first ones in the order they're
+        // displayed in, reversed or not, rather than just any of them.
+        let hidden = match self.recurse.and_then(|r| r.max_children) {
+            Some(max) if src.len() > max => {
+                self.filter.sort_files(&mut src);
+                let hidden = src.len() - max;
+                src.truncate(max);
+                hidden
+            },
+            _ => 0,
+        };
 
-        let mut pool = Pool::new(num_cpus::get() as u32);
         let mut file_eggs = Vec::new();
 
-        struct Egg<'_> {
-            cells:   Vec<Cell>,
-            name:    Cell,
-            xattrs:  Vec<Attribute>,
-            errors:  Vec<(io::Error, Option<PathBuf>)>,
-            dir:     Option<Dir>,
-            file:    Arc<File<'_>>,
+        // Printing a line takes the lock on stdout, so lines from
+        // different threads never get mixed up with one another.
+        let render = |table: &mut Table<U>, file: &File<'dir>, xattrs: bool| {
+            let cells = table.cells_for_file(file, xattrs);
+            if self.json_lines {
+                println!("{}", table.json_line(file, depth, &cells));
+            }
+            cells
+        };
+
+        // Starting up a pool of threads, and locking the table for every
+        // file, takes longer than examining a handful of files, so small
+        // directories get done on this thread instead. The eggs get sorted
+        // afterwards either way, so the output comes out the same.
+        if src.len() < self.sequential_limit {
+            for file in src.into_iter() {
+                let egg = self.make_egg(Arc::new(file), depth, prefix, |file, xattrs| render(&mut *table, file, xattrs));
+                file_eggs.push(egg);
+            }
         }
+        else {
+            let mut pool = Pool::new(num_cpus::get() as u32);
 
-        pool.scoped(|scoped| {
-            let file_eggs = Arc::new(Mutex::new(&mut file_eggs));
-            let table = Arc::new(Mutex::new(&mut table));
+            pool.scoped(|scoped| {
+                let file_eggs = Arc::new(Mutex::new(&mut file_eggs));
+                let table = Arc::new(Mutex::new(&mut *table));
+                let render = &render;
 
-            for file in src.into_iter() {
-                let file: Arc<File> = Arc::new(file);
-                let file_eggs = file_eggs.clone();
-                let table = table.clone();
-
-                scoped.execute(move || {
-                    let mut errors = Vec::new();
-
-                    let mut xattrs = Vec::new();
-                    match file.path.attributes() {
-                        Ok(xs) => {
-                            if self.xattr {
-                                for xattr in xs {
-                                    xattrs.push(xattr);
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            if self.xattr {
-                                errors.push((e, None));
-                            }
-                        },
-                    };
+                for file in src.into_iter() {
+                    let file_eggs = file_eggs.clone();
+                    let table = table.clone();
+
+                    scoped.execute(move || {
+                        let egg = self.make_egg(Arc::new(file), depth, prefix, |file, xattrs| render(&mut **table.lock().unwrap(), file, xattrs));
+                        file_eggs.lock().unwrap().push(egg);
+                    });
+                }
+            });
+        }
 
-                    let cells = table.lock().unwrap().cells_for_file(&file, !xattrs.is_empty());
+        self.filter.sort_by_file(&mut file_eggs, |egg| &*egg.file);
 
-                    let name = Cell {
-                        text: filename(&file, &self.colours, true),
-                        length: file.file_name_width()
-                    };
+        let num_eggs = file_eggs.len();
+        let mut total_count = 0;
+        let mut total_too_deep = false;
+
+        for (index, egg) in file_eggs.into_iter().enumerate() {
+            let mut files = Vec::new();
+            let mut errors = egg.errors;
 
-                    let mut dir = None;
+            if self.json_lines {
+                total_count += 1;
 
-                    if let Some(r) = self.recurse {
-                        if file.is_directory() && r.tree && !r.is_too_deep(depth) {
-                            if let Ok(d) = file.to_dir(false) {
-                                dir = Some(d);
-                            }
+                if let Some(ref dir) = egg.dir {
+                    for file_to_add in dir.files() {
+                        match file_to_add {
+                            Ok(f)          => files.push(f),
+                            Err((path, e)) => errors.push((e, Some(path)))
                         }
-                    };
+                    }
+
+                    self.filter.filter_files(&mut files);
+                    let (count, _) = self.add_files_to_table(table, files, depth + 1, prefix);
+                    total_count += count;
+                }
 
-                    let egg = Egg {
-                        cells: cells,
-                        name: name,
-                        xattrs: xattrs,
-                        errors: errors,
-                        dir: dir,
-                        file: file,
+                // There's no table to put errors in, so they go to stderr
+                // instead, keeping stdout as nothing but JSON.
+                for (error, path) in errors {
+                    let _ = match path {
+                        Some(path) => writeln!(io::stderr(), "{}: {}", path.display(), error),
+                        None       => writeln!(io::stderr(), "{}: {}", egg.file.path.display(), error),
                     };
+                }
 
-                    file_eggs.lock().unwrap().push(egg);
-                });
+                continue;
             }
-        });
 
-        file_eggs.sort_by(|a, b| self.filter.compare_files(&*a.file, &*b.file));
-
-        let num_eggs = file_eggs.len();
-        for (index, egg) in file_eggs.into_iter().enumerate() {
-            let mut files = Vec::new();
-            let mut errors = egg.errors;
+            let mut cells = egg.cells;
+            table.set_rank(&mut cells, index + 1);
 
             let row = Row {
                 depth:    depth,
-                cells:    Some(egg.cells),
+                cells:    Some(cells),
                 name:     egg.name,
-                last:     index == num_eggs - 1,
+                last:     index == num_eggs - 1 && hidden == 0,
+                is_dir:   egg.file.is_directory(),
+                highlighted: egg.highlighted,
+                size:     bar_size(egg.file.size()),
+                marker:   None,
             };
 
+            let row_index = table.rows.len();
             table.rows.push(row);
+            table.count_file(&egg.file);
+            table.note_hard_links(&egg.file);
+            total_count += 1;
+
+            if egg.too_deep {
+                total_too_deep = true;
+                table.set_descendants(row_index, 0, true);
+            }
+
+            if self.error_style == ErrorStyle::Inline {
+                table.add_inline_errors(row_index, &mut errors);
+            }
 
             if let Some(ref dir) = egg.dir {
                 for file_to_add in dir.files() {
@@ -304,25 +711,40 @@ impl Details {
                     }
                 }
 
+                if self.error_style == ErrorStyle::Inline {
+                    table.add_inline_errors(row_index, &mut errors);
+                }
+
                 self.filter.filter_files(&mut files);
 
                 if !files.is_empty() {
+                    let name_width = xattr_name_width(&egg.xattrs);
                     for xattr in egg.xattrs {
-                        table.add_xattr(xattr, depth + 1, false);
+                        table.add_xattr(xattr, depth + 1, false, name_width);
                     }
 
                     for (error, path) in errors {
                         table.add_error(&error, depth + 1, false, path);
                     }
 
-                    self.add_files_to_table(table, files, depth + 1);
+                    let child_prefix = if depth != 0          { prefix.join(&egg.file.name) }
+                                  else if self.flat_tree  { egg.file.path.clone() }
+                                  else                    { PathBuf::new() };
+
+                    let (count, too_deep) = self.add_files_to_table(table, files, depth + 1, &child_prefix);
+                    table.set_descendants(row_index, count, too_deep);
+                    total_count += count;
+                    total_too_deep = total_too_deep || too_deep;
                     continue;
                 }
+
+                table.set_descendants(row_index, 0, false);
             }
 
             let count = egg.xattrs.len();
+            let name_width = xattr_name_width(&egg.xattrs);
             for (index, xattr) in egg.xattrs.into_iter().enumerate() {
-                table.add_xattr(xattr, depth + 1, errors.is_empty() && index == count - 1);
+                table.add_xattr(xattr, depth + 1, errors.is_empty() && index == count - 1, name_width);
             }
 
             let count = errors.len();
@@ -330,7 +752,298 @@ impl Details {
                 table.add_error(&error, depth + 1, index == count - 1, path);
             }
         }
+
+        if hidden > 0 && !self.json_lines {
+            table.add_hidden_count(hidden, depth);
+        }
+
+        (total_count, total_too_deep)
+    }
+
+    /// The file that a symlink's arrow should point to, which is either
+    /// the one it links to directly or the one at the end of its chain,
+    /// or `None` if it isn't a symlink.
+    fn link_arrow_target<'a>(&self, file: &'a File) -> Option<Result<File<'a>, String>> {
+        if !file.is_link() { None }
+        else if self.canonical_links { Some(file.canonical_target()) }
+        else { Some(file.link_target()) }
+    }
+
+    /// Whether a file with the given extended attributes should have an
+    /// `@` marker after its permissions.
+    fn xattr_marker(&self, xattrs: &[Attribute]) -> bool {
+        !xattrs.is_empty() && !self.hide_xattr_marker
+    }
+
+    /// Examine a file for the table: render its cells using the given
+    /// function, which is the only part that needs the table itself, then
+    /// build its name, and open it if it's a directory that the tree is
+    /// going to descend into.
+    fn make_egg<'dir, F>(&self, file: Arc<File<'dir>>, depth: usize, prefix: &Path, render: F) -> Egg<'dir>
+    where F: FnOnce(&File<'dir>, bool) -> Vec<Cell> {
+        let mut errors = Vec::new();
+
+        // Querying a file's extended attributes can be slow on some
+        // filesystems, so it's only done when they're going to be shown.
+        // Without them, there's no `@` marker.
+        let mut xattrs = Vec::new();
+        if self.xattr {
+            match file.path.attributes() {
+                Ok(xs) => {
+                    for xattr in xs {
+                        xattrs.push(xattr);
+                    }
+                },
+                Err(e) => errors.push((e, None)),
+            };
+        }
+
+        let cells = render(&*file, self.xattr_marker(&xattrs));
+
+        // In the flat tree, the files at the top show their paths as they
+        // were given, and the ones below them have their names joined on.
+        let mut name = if self.flat_tree && !self.absolute_paths {
+            let path = if depth == 0 { file.path.clone() }
+                                else { prefix.join(&file.name) };
+            let path = display_name(&path.to_string_lossy(), self.quote_names);
+            Cell::paint(file_colour(&self.colours, self.name_colouring, &file), &path)
+        }
+        else if self.absolute_paths {
+            let path = file.absolute_path();
+            Cell::paint(file_colour(&self.colours, self.name_colouring, &file), &path.to_string_lossy())
+        }
+        else {
+            name_cell(&file, &self.colours, self.name_colouring, self.link_arrow_target(&file), self.dir_slash, self.quote_names)
+        };
+
+        // Highlighted names get repainted in the highlight style as a
+        // whole, as the background would otherwise be reset partway
+        // through by the name's own colours.
+        let highlighted = self.highlight.as_ref().map(|glob| glob.matches(&file.name)).unwrap_or(false);
+        if highlighted {
+            let plain = if self.absolute_paths { Cell::paint(Style::default(), &file.absolute_path().to_string_lossy()) }
+                                          else { name_cell(&file, &Colours::plain(), NameColouring::default(), self.link_arrow_target(&file), self.dir_slash, self.quote_names) };
+            name.text = self.colours.highlight.paint(&plain.text).to_string();
+        }
+
+        // Paths don't have an arrow after them, so their slash can go on
+        // the end.
+        if self.dir_slash && (self.absolute_paths || self.flat_tree) {
+            if let Some(slash) = dir_slash(&file, &self.colours) {
+                name.append(&slash);
+            }
+        }
+
+        if self.tree_paths && depth != 0 {
+            let path = prefix.join(&file.name);
+            name.add_spaces(2);
+            name.append(&Cell::paint(self.colours.symlink_path, &path.to_string_lossy()));
+        }
+
+        let mut dir = None;
+        let mut too_deep = false;
+
+        // A directory that can't be opened gets its error listed
+        // underneath it, like errors reading any of its files would be, so
+        // the last row of the tree still ends up with the corner.
+        if let Some(r) = self.recurse {
+            if file.is_directory() && r.tree {
+                if r.is_too_deep(depth) {
+                    too_deep = true;
+                }
+                else {
+                    match file.to_dir(false, false, false) {
+                        Ok(d)   => dir = Some(d),
+                        Err(e)  => errors.push((e, None)),
+                    }
+                }
+            }
+        };
+
+        Egg {
+            cells: cells,
+            name: name,
+            xattrs: xattrs,
+            errors: errors,
+            dir: dir,
+            too_deep: too_deep,
+            highlighted: highlighted,
+            file: file,
+        }
+    }
+}
+
+
+/// The device and inode of a file with more than one hard link, which all
+/// of its links share, or `None` for files with just the one.
+fn hard_link_key(file: &File) -> Option<(u64, u64)> {
+    if file.links().multiple {
+        Some((file.device().0 as u64, file.inode().0 as u64))
+    }
+    else {
+        None
+    }
+}
+
+/// Count how many times each device and inode pair appears.
+fn count_hard_links<I: Iterator<Item=(u64, u64)>>(keys: I) -> HashMap<(u64, u64), usize> {
+    let mut counts = HashMap::new();
+    for key in keys {
+        *counts.entry(key).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Whether the given column is the file size column, whatever its options.
+fn is_size_column(column: &Column) -> bool {
+    match *column {
+        Column::FileSize(_)  => true,
+        _                    => false,
+    }
+}
+
+/// How wide the part of a size after its whole number is: the decimal
+/// point and the digits after it, then any unit. Cells that don't start
+/// with a digit, such as the header or a dash, have no number to line up.
+fn fraction_width(text: &str, thousands: &str, point: &str) -> Option<usize> {
+    if !text.chars().next().map(|c| c.is_digit(10)).unwrap_or(false) {
+        return None;
+    }
+
+    if !point.is_empty() {
+        if let Some(index) = text.find(point) {
+            return Some(UnicodeWidthStr::width(&text[index ..]));
+        }
+    }
+
+    let whole = text.char_indices()
+                    .find(|&(_, c)| !c.is_digit(10) && !thousands.contains(c))
+                    .map(|(index, _)| index)
+                    .unwrap_or(text.len());
+    Some(UnicodeWidthStr::width(&text[whole ..]))
+}
+
+/// Pad each of the given size cells with spaces on the right, so that
+/// their whole numbers all end in the same place once they've been
+/// right-aligned: sizes without decimals get spaces where they'd be.
+fn align_decimals(cells: Vec<&mut Cell>, thousands: &str, point: &str) {
+    let widths: Vec<_> = cells.iter().map(|cell| fraction_width(&cell.plain_text(), thousands, point)).collect();
+    let widest = widths.iter().filter_map(|w| *w).max().unwrap_or(0);
+
+    for (cell, width) in cells.into_iter().zip(widths.into_iter()) {
+        if let Some(width) = width {
+            cell.add_spaces(widest - width);
+        }
+    }
+}
+
+/// Split the given files into groups by the given attribute, each with the
+/// heading to print above it. Files stay in the same order within their
+/// groups. Type groups always come in the same order, and the others go in
+/// order of their headings.
+fn group_files<'dir>(files: Vec<File<'dir>>, group_by: GroupBy) -> Vec<(String, Vec<File<'dir>>)> {
+    let mut users = OSUsers::empty_cache();
+    let mut groups: Vec<((usize, String), Vec<File<'dir>>)> = Vec::new();
+
+    for file in files {
+        let key = match group_by {
+            GroupBy::Type if file.is_directory()  => (0, "Directories".to_string()),
+            GroupBy::Type if file.is_file()       => (1, "Files".to_string()),
+            GroupBy::Type if file.is_link()       => (2, "Symlinks".to_string()),
+            GroupBy::Type                         => (3, "Others".to_string()),
+            GroupBy::Extension => match file.ext {
+                Some(ref ext)  => (0, format!(".{}", ext)),
+                None           => (1, "No extension".to_string()),
+            },
+            GroupBy::Owner => match users.get_user_by_uid(file.user().0) {
+                Some(user)  => (0, user.name),
+                None        => (0, file.user().0.to_string()),
+            },
+        };
+
+        match groups.iter().position(|&(ref k, _)| *k == key) {
+            Some(index)  => groups[index].1.push(file),
+            None         => groups.push((key, vec![ file ])),
+        }
+    }
+
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+    groups.into_iter().map(|((_, heading), files)| (heading, files)).collect()
+}
+
+/// Format a non-negative number of seconds in its largest whole unit, such
+/// as `40s`, `3h`, or `12d`.
+fn format_duration(seconds: i64) -> String {
+    match seconds {
+        0    ... 59     => format!("{}s", seconds),
+        60   ... 3599   => format!("{}m", seconds / 60),
+        3600 ... 86399  => format!("{}h", seconds / 3600),
+        _               => format!("{}d", seconds / 86400),
+    }
+}
+
+/// How a file differs between the two directories being compared.
+#[derive(PartialEq, Debug, Copy, Clone)]
+enum Difference {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl Difference {
+    fn marker(&self) -> &'static str {
+        match *self {
+            Difference::Added    => "+",
+            Difference::Removed  => "-",
+            Difference::Changed  => "~",
+        }
+    }
+}
+
+/// Match up the files from an old and a new version of a directory by
+/// their names, keeping the ones that were added, removed, or changed.
+fn diff_files<'dir>(old: Vec<File<'dir>>, new: Vec<File<'dir>>) -> Vec<(Difference, File<'dir>)> {
+    let mut diffs = Vec::new();
+    let mut old: HashMap<String, File<'dir>> = old.into_iter().map(|file| (file.name.clone(), file)).collect();
+
+    for file in new {
+        match old.remove(&file.name) {
+            Some(previous) => {
+                if file.differs_from(&previous) {
+                    diffs.push((Difference::Changed, file));
+                }
+            },
+            None => diffs.push((Difference::Added, file)),
+        }
     }
+
+    for (_, file) in old {
+        diffs.push((Difference::Removed, file));
+    }
+
+    diffs
+}
+
+/// The text to show for an error, along with the path of the file it's
+/// about, if it isn't the one whose row it goes under.
+fn error_message(error: &io::Error, path: Option<PathBuf>) -> String {
+    match path {
+        Some(path) => format!("<{}: {}>", path.display(), error),
+        None       => format!("<{}>", error),
+    }
+}
+
+/// A file that's been examined, but not yet added to the table. Files get
+/// examined in any order, so they're only added once they've been sorted.
+struct Egg<'dir> {
+    cells:   Vec<Cell>,
+    name:    Cell,
+    xattrs:  Vec<Attribute>,
+    errors:  Vec<(io::Error, Option<PathBuf>)>,
+    dir:     Option<Dir>,
+    too_deep: bool,
+    highlighted: bool,
+    file:    Arc<File<'dir>>,
 }
 
 
@@ -358,6 +1071,23 @@ struct Row {
     /// Whether this is the last entry in the directory. This flag is used
     /// when calculating the tree view.
     last: bool,
+
+    /// Whether this row is for a directory. This is used when collapsing
+    /// chains of directories that only contain one other directory.
+    is_dir: bool,
+
+    /// The size of this row's file in bytes, if it has one. The size bar
+    /// column can only be drawn once every row's size is known, so it gets
+    /// rendered from this when the table is printed.
+    size: Option<u64>,
+
+    /// Whether this row's file matched the highlight pattern, in which case
+    /// it can get padded out with the highlight's background.
+    highlighted: bool,
+
+    /// A one-character marker to put in front of this row's line, such as
+    /// how its file differs between two directories.
+    marker: Option<Cell>,
 }
 
 impl Row {
@@ -370,6 +1100,25 @@ impl Row {
             None => 0,
         }
     }
+
+    /// Gets the Unicode display width of this row's name, including the
+    /// tree characters in front of it, which take up the given number of
+    /// columns per level.
+    fn name_width(&self, indent: usize) -> usize {
+        if self.depth == 0 { self.name.length }
+                      else { self.depth * indent + 1 + self.name.length }
+    }
+}
+
+
+/// How many files of each type have been added to a table.
+#[derive(Default)]
+struct TypeCounts {
+    files:        usize,
+    directories:  usize,
+    links:        usize,
+    pipes:        usize,
+    specials:     usize,
 }
 
 
@@ -379,25 +1128,193 @@ pub struct Table<U> {
     columns:  Vec<Column>,
     rows:     Vec<Row>,
 
+    /// The width that rows get truncated to, if any.
+    max_width: Option<usize>,
+
+    /// Whether to display each file's name and metadata on separate lines.
+    two_lines: bool,
+
+    /// The narrowest each column is allowed to be, regardless of its
+    /// contents. This is used to line up several tables with one another.
+    min_widths: Vec<usize>,
+
+    /// The user's limits on the widths of types of column, keyed by the
+    /// columns' names.
+    width_limits: HashMap<String, WidthLimit>,
+
+    /// The order in which columns get truncated when a row is wider than
+    /// the maximum width. The filename is only cut once these have all been
+    /// reduced to nothing.
+    truncation_order: Vec<Column>,
+
     time:         locale::Time,
     numeric:      locale::Numeric,
-    tz:           TimeZone,
+
+    /// The time zone to display dates in. If the local time zone couldn't
+    /// be determined, this is `None`, and dates are displayed in UTC.
+    tz:           Option<TimeZone>,
+
+    /// Whether dates are being deliberately displayed in UTC, in which case
+    /// they get marked with the time zone.
+    utc:          bool,
+
+    /// Whether dates are shown in full, with fractional seconds.
+    precise_times: bool,
+
+    /// The style to format dates in.
+    time_style:   TimeStyle,
     users:        U,
     colours:      Colours,
     current_year: i64,
-}
 
-impl Default for Table<MockUsers> {
-    fn default() -> Table<MockUsers> {
-        Table {
-            columns: Columns::default().for_dir(None),
-            rows:    Vec::new(),
-            time:    locale::Time::english(),
+    /// The current time, in seconds, which relative ages are measured from.
+    /// The default table is pinned to the epoch, so tests don't depend on
+    /// when they're run.
+    now:          i64,
+
+    /// How many columns each level of the tree takes up.
+    tree_indent:  usize,
+
+    /// The characters the tree gets drawn with.
+    tree_style:   TreeStyle,
+
+    /// Whether the tree gets drawn on the right of the names, for
+    /// right-to-left languages.
+    mirror_tree:  bool,
+
+    /// Text to put before each group name, to tell them apart from users.
+    group_prefix: Option<String>,
+
+    /// The text to show for values that don't apply, such as the size of
+    /// a directory.
+    placeholder:  String,
+
+    /// Whether to put a slash after the names of directories.
+    dir_slash:    bool,
+
+    /// Whether names that need quoting in a shell get quoted.
+    quote_names:  bool,
+
+    /// How to colour files' names, beyond their file types' colours.
+    name_colouring: NameColouring,
+
+    /// Whether to show how many members each group has.
+    group_members: bool,
+
+    /// The full names of users, looked up from their GECOS fields, if
+    /// they're being shown instead of login names. Users without one are
+    /// cached as `None`.
+    full_names:   Option<HashMap<uid_t, Option<String>>>,
+
+    /// Whether to show inode numbers in hexadecimal.
+    hex_inodes:   bool,
+
+    /// Whether to count how many of each hard-linked file's links are in
+    /// the table.
+    link_siblings: bool,
+
+    /// The index of each row for a file with more than one hard link, with
+    /// the device and inode that its links share.
+    hard_links:   Vec<(usize, (u64, u64))>,
+
+    /// Whether symlinks get the cells of the files they point to.
+    dereference:  bool,
+
+    /// Whether to ditto users and groups that repeat the row above's.
+    ditto_owners: bool,
+
+    /// The users whose primary group is each group, keyed by group ID. The
+    /// password file only gets read once a member count is first displayed.
+    primary_members: Option<HashMap<gid_t, Vec<String>>>,
+
+    /// How many files of each type are in the table, for the summary.
+    counts:       TypeCounts,
+
+    /// The mount points of each device, keyed by device number. The mount
+    /// table only gets read once a file's mount point is first displayed.
+    mounts:       Option<HashMap<u64, String>>,
+
+    /// The capacity of each device's filesystem, keyed by device number,
+    /// so each filesystem only gets examined once.
+    fs_sizes:     HashMap<u64, Option<u64>>,
+
+    /// The width to pad highlighted rows out to, if any.
+    pad_width:    Option<usize>,
+
+    /// The width that whole columns get dropped to fit into, if any.
+    compact_width: Option<usize>,
+
+    /// Whether to draw lines around and between the cells.
+    borders:      bool,
+
+    /// The colours to paint the metadata columns with, if they're dimmed.
+    /// The header and the names keep the table's own colours.
+    dimmed:       Option<Colours>,
+
+    /// Whether each line gets its number put in front of it.
+    line_numbers: bool,
+
+    /// Whether to print only the names, which are paths from the root of
+    /// the tree, without the tree characters or any other columns.
+    flat_tree:    bool,
+
+    /// Whether the first row is a header, which gets a line under it when
+    /// there are borders.
+    has_header:   bool,
+
+    /// The ages at which the age gradient changes colour, youngest first.
+    age_stops:    Vec<AgeStop>,
+}
+
+impl Default for Table<MockUsers> {
+    fn default() -> Table<MockUsers> {
+        let columns = Columns::default().for_dir(None);
+
+        Table {
+            truncation_order: Column::truncation_order(&columns),
+            max_width: None,
+            min_widths: Vec::new(),
+            width_limits: HashMap::new(),
+            two_lines: false,
+            columns: columns,
+            rows:    Vec::new(),
+            time:    locale::Time::english(),
             numeric: locale::Numeric::english(),
-            tz:      TimeZone::localtime().unwrap(),
+            tz:      zone_or_utc(TimeZone::localtime()),
+            utc:     false,
+            precise_times: false,
+            time_style:   TimeStyle::default(),
             users:   MockUsers::with_current_uid(0),
             colours: Colours::default(),
-            current_year: 1234,
+            current_year: 1970,
+            now:          0,
+            tree_indent:  TREE_INDENT,
+            tree_style:   TreeStyle::default(),
+            mirror_tree:  false,
+            group_prefix: None,
+            placeholder:  "-".to_string(),
+            dir_slash:    false,
+            quote_names:  false,
+            name_colouring: NameColouring::default(),
+            group_members: false,
+            full_names:   None,
+            hex_inodes:   false,
+            link_siblings: false,
+            hard_links:   Vec::new(),
+            dereference:  false,
+            ditto_owners: false,
+            primary_members: None,
+            counts:       TypeCounts::default(),
+            mounts:       None,
+            fs_sizes:     HashMap::new(),
+            pad_width:    None,
+            compact_width: None,
+            borders:      false,
+            dimmed:       None,
+            line_numbers: false,
+            flat_tree: false,
+            has_header:   false,
+            age_stops:    Vec::new(),
         }
     }
 }
@@ -407,77 +1324,693 @@ impl Table<OSUsers> {
     /// Create a new, empty Table object, setting the caching fields to their
     /// empty states.
     pub fn with_options(colours: Colours, columns: Vec<Column>) -> Table<OSUsers> {
+        let now = LocalDateTime::now();
+
         Table {
+            truncation_order: Column::truncation_order(&columns),
+            max_width: None,
+            min_widths: Vec::new(),
+            width_limits: HashMap::new(),
+            two_lines: false,
             columns: columns,
             rows:    Vec::new(),
 
             time:         locale::Time::load_user_locale().unwrap_or_else(|_| locale::Time::english()),
             numeric:      locale::Numeric::load_user_locale().unwrap_or_else(|_| locale::Numeric::english()),
-            tz:           TimeZone::localtime().unwrap(),
+            tz:           zone_or_utc(TimeZone::localtime()),
+            utc:          false,
+            precise_times: false,
+            time_style:   TimeStyle::default(),
             users:        OSUsers::empty_cache(),
             colours:      colours,
-            current_year: LocalDateTime::now().year(),
+            current_year: now.year(),
+            now:          now.to_instant().seconds(),
+            tree_indent:  TREE_INDENT,
+            tree_style:   TreeStyle::default(),
+            mirror_tree:  false,
+            group_prefix: None,
+            placeholder:  "-".to_string(),
+            dir_slash:    false,
+            quote_names:  false,
+            name_colouring: NameColouring::default(),
+            group_members: false,
+            full_names:   None,
+            hex_inodes:   false,
+            link_siblings: false,
+            hard_links:   Vec::new(),
+            dereference:  false,
+            ditto_owners: false,
+            primary_members: None,
+            counts:       TypeCounts::default(),
+            mounts:       None,
+            fs_sizes:     HashMap::new(),
+            pad_width:    None,
+            compact_width: None,
+            borders:      false,
+            dimmed:       None,
+            line_numbers: false,
+            flat_tree: false,
+            has_header:   false,
+            age_stops:    Vec::new(),
         }
     }
 }
 
 impl<U> Table<U> where U: Users {
 
+    /// The time zone that dates are displayed in, or `None` if the local
+    /// time zone couldn't be determined and UTC is being used instead.
+    pub fn time_zone(&self) -> Option<&TimeZone> {
+        self.tz.as_ref()
+    }
+
+    /// Truncate rows so that none of them are wider than the given width.
+    pub fn set_max_width(&mut self, width: usize) {
+        self.max_width = Some(width);
+    }
+
+    /// Put each file's name on one line, and its metadata on the line after
+    /// it, which fits much better in narrow terminals.
+    pub fn use_two_lines(&mut self) {
+        self.two_lines = true;
+    }
+
+    /// Put the given text before each group name.
+    pub fn set_group_prefix(&mut self, prefix: String) {
+        self.group_prefix = Some(prefix);
+    }
+
+    /// Show the given text for values that don't apply, instead of `-`.
+    pub fn set_placeholder(&mut self, placeholder: String) {
+        self.placeholder = placeholder;
+    }
+
+    /// A cell for a value that doesn't apply to a file.
+    fn placeholder(&self) -> Cell {
+        Cell::paint(self.colours.punctuation, &self.placeholder)
+    }
+
+    /// Pad highlighted rows with the highlight style until they're the
+    /// given width, which is usually the width of the terminal.
+    pub fn set_pad_width(&mut self, width: usize) {
+        self.pad_width = Some(width);
+    }
+
+    /// Leave out the least important columns, as many as it takes for the
+    /// widest row to fit into the given width. The permissions are always
+    /// kept, as is the file name.
+    pub fn set_compact_width(&mut self, width: usize) {
+        self.compact_width = Some(width);
+    }
+
+    /// Draw lines around the table, between its columns, and under its
+    /// header. This only works with flat listings, as the lines would get
+    /// in the way of the tree.
+    pub fn use_borders(&mut self) {
+        self.borders = true;
+    }
+
+    /// Paint the metadata columns with faint versions of the table's
+    /// colours, leaving the header and the names as they are.
+    pub fn use_dimmed_metadata(&mut self) {
+        self.dimmed = Some(self.colours.dimmed());
+    }
+
+    /// The colours to paint cells in the metadata columns with.
+    fn metadata_colours(&self) -> &Colours {
+        self.dimmed.as_ref().unwrap_or(&self.colours)
+    }
+
+    /// Number each line of the table, other than the header's, counting
+    /// down the whole tree rather than within each directory.
+    pub fn use_line_numbers(&mut self) {
+        self.line_numbers = true;
+    }
+
+    /// Print the tree as a plain list of its files' paths, in the same
+    /// order, leaving out the header, the tree characters, and the rows for
+    /// attributes and errors, so it can be passed on to other programs.
+    pub fn use_flat_tree(&mut self) {
+        self.flat_tree = true;
+    }
+
+    /// Use the given stops, youngest first, to colour the age gradient.
+    pub fn set_age_stops(&mut self, stops: Vec<AgeStop>) {
+        self.age_stops = stops;
+    }
+
+    /// Show the metadata of the files that symlinks point to, instead of
+    /// the links' own, as `ls -L` does.
+    pub fn use_dereference(&mut self) {
+        self.dereference = true;
+    }
+
+    /// Work out the characters the user's locale puts between the
+    /// thousands and before the decimals of a number, by formatting one.
+    fn number_separators(&self) -> (String, String) {
+        let thousands = self.numeric.format_int(1000).trim_matches(|c: char| c.is_digit(10)).to_string();
+        let point = self.numeric.format_float(0.5, 1).trim_matches(|c: char| c.is_digit(10)).to_string();
+        (thousands, point)
+    }
+
+    /// Pad the cells in the size column with spaces on the right, so their
+    /// decimal points line up, now that every row has been added.
+    pub fn align_size_decimals(&mut self) {
+        let column_index = match self.columns.iter().position(is_size_column) {
+            Some(index)  => index,
+            None         => return,
+        };
+
+        let (thousands, point) = self.number_separators();
+        let cells = self.rows.iter_mut().filter_map(|row| row.cells.as_mut()).map(|cells| &mut cells[column_index]).collect();
+        align_decimals(cells, &thousands, &point);
+    }
+
+    /// Pad the size cells of the given files so their decimal points line
+    /// up, before they've been added to a table, as the grid view needs.
+    pub fn align_size_decimals_in_cells(&self, cells: &mut [Vec<Cell>]) {
+        let column_index = match self.columns.iter().position(is_size_column) {
+            Some(index)  => index,
+            None         => return,
+        };
+
+        let (thousands, point) = self.number_separators();
+        align_decimals(cells.iter_mut().map(|row| &mut row[column_index]).collect(), &thousands, &point);
+    }
+
+    /// Count how many of each hard-linked file's links are in the table,
+    /// once all its rows have been added.
+    pub fn use_link_siblings(&mut self) {
+        self.link_siblings = true;
+    }
+
+    /// Show inode numbers in hexadecimal, with a `0x` prefix.
+    pub fn use_hex_inodes(&mut self) {
+        self.hex_inodes = true;
+    }
+
+    /// Replace users and groups that are the same as the ones in the row
+    /// above, at the same depth of the tree, with a ditto mark.
+    pub fn use_ditto_owners(&mut self) {
+        self.ditto_owners = true;
+    }
+
+    /// Show how many members each group has after its name.
+    pub fn show_group_members(&mut self) {
+        self.group_members = true;
+    }
+
+    /// Show users' full names instead of their login names, for those
+    /// that have them.
+    pub fn use_full_names(&mut self) {
+        self.full_names = Some(HashMap::new());
+    }
+
+    /// Put a slash after the name of each directory added to the table.
+    pub fn use_dir_slashes(&mut self) {
+        self.dir_slash = true;
+    }
+
+    /// Quote the names of files added to the table that have spaces or
+    /// other characters a shell would treat specially.
+    pub fn use_quoted_names(&mut self) {
+        self.quote_names = true;
+    }
+
+    /// Colour the names of files added to the table in the given ways, as
+    /// well as by their types.
+    pub fn set_name_colouring(&mut self, colouring: NameColouring) {
+        self.name_colouring = colouring;
+    }
+
+    /// Set how many columns each level of the tree takes up.
+    pub fn set_tree_indent(&mut self, indent: usize) {
+        self.tree_indent = indent;
+    }
+
+    /// Set the characters the tree gets drawn with.
+    pub fn set_tree_style(&mut self, style: TreeStyle) {
+        self.tree_style = style;
+    }
+
+    /// Draw the tree on the right of the names rather than the left,
+    /// mirrored, with the names lined up on the right.
+    pub fn use_mirrored_tree(&mut self) {
+        self.mirror_tree = true;
+    }
+
+    /// Merge every directory whose only child is another directory into one
+    /// row, so a chain such as `a`, `b`, `c` gets displayed as `a/b/c`. The
+    /// merged row shows the details of the deepest directory, and everything
+    /// beneath it gets moved up a level for each directory merged.
+    ///
+    /// A child counts as the only one if it's both the first and the last
+    /// row beneath its parent; any extended attributes or errors would come
+    /// before it, so directories with those never get merged.
+    pub fn collapse_directory_chains(&mut self) {
+        let mut index = 0;
+
+        while index + 1 < self.rows.len() {
+            let can_merge = {
+                let parent = &self.rows[index];
+                let child = &self.rows[index + 1];
+
+                parent.is_dir && parent.cells.is_some()
+                    && child.is_dir && child.cells.is_some()
+                    && child.depth == parent.depth + 1 && child.last
+            };
+
+            if !can_merge {
+                index += 1;
+                continue;
+            }
+
+            let child = self.rows.remove(index + 1);
+            let depth = child.depth;
+
+            for row in self.rows[index + 1 ..].iter_mut().take_while(|r| r.depth > depth) {
+                row.depth -= 1;
+            }
+
+            let parent = &mut self.rows[index];
+            parent.name.append(&Cell::paint(self.colours.punctuation, "/"));
+            parent.name.append(&child.name);
+            parent.cells = child.cells;
+
+            // Don't move on, as the merged directory may itself have only
+            // one child directory.
+        }
+    }
+
+    /// Fill in the descendants cell of the row at the given index, if the
+    /// table has such a column, with the number of rows listed beneath it.
+    /// If part of the tree below was cut off, the count gets a `+` suffix.
+    fn set_descendants(&mut self, row_index: usize, count: usize, too_deep: bool) {
+        let column_index = match self.columns.iter().position(|c| *c == Column::Descendants) {
+            Some(index)  => index,
+            None         => return,
+        };
+
+        let mut cell = Cell::paint(self.metadata_colours().size.numbers, &self.numeric.format_int(count));
+        if too_deep {
+            cell.append(&Cell::paint(self.metadata_colours().punctuation, "+"));
+        }
+
+        if let Some(ref mut cells) = self.rows[row_index].cells {
+            cells[column_index] = cell;
+        }
+    }
+
+    /// Fill in a file's position in the listing, counting from one, if
+    /// there's a column for it. Files only get their positions once they've
+    /// been sorted, so their cells start off with a placeholder. In the
+    /// tree view, each directory's files get counted separately.
+    pub fn set_rank(&self, cells: &mut [Cell], rank: usize) {
+        if let Some(index) = self.columns.iter().position(|c| *c == Column::Rank) {
+            cells[index] = Cell::paint(self.metadata_colours().punctuation, &self.numeric.format_int(rank));
+        }
+    }
+
+    /// Format dates using English month names, regardless of the user's
+    /// locale, so the output is the same across machines.
+    pub fn use_english_dates(&mut self) {
+        self.time = locale::Time::english();
+    }
+
+    /// Show dates in full, including the year and the fractions of a
+    /// second, whenever they are.
+    pub fn use_precise_times(&mut self) {
+        self.precise_times = true;
+    }
+
+    /// Format dates in the given style, rather than exa's own.
+    pub fn set_time_style(&mut self, style: TimeStyle) {
+        self.time_style = style;
+    }
+
+    /// Display dates in UTC, marked as such, instead of in the local time
+    /// zone. The current year gets measured in UTC too, so the choice
+    /// between showing the time or the year stays consistent.
+    pub fn use_utc(&mut self) {
+        self.tz = None;
+        self.utc = true;
+        self.current_year = LocalDateTime::at(self.now).year();
+    }
+
+    /// Use the given time as the current time, instead of the time the
+    /// table was created. Both relative ages and the choice of whether to
+    /// show a date's time or its year are measured from it, so pinning it
+    /// makes the table's output the same no matter when it's rendered.
+    pub fn set_now(&mut self, now: LocalDateTime) {
+        self.now = now.to_instant().seconds();
+        self.current_year = now.year();
+    }
+
+    /// Force each column to be at least as wide as the corresponding width
+    /// in the given vector, even if none of its cells are that wide.
+    pub fn set_min_widths(&mut self, widths: Vec<usize>) {
+        self.min_widths = widths;
+    }
+
+    /// Work out the list of column widths by finding the longest cell for
+    /// each column, taking any minimum widths into account. Any limits set
+    /// for a type of column get applied last, so a column's maximum width
+    /// always wins; cells wider than that get truncated when printed.
+    pub fn column_widths(&self) -> Vec<usize> {
+        (0 .. self.columns.len())
+            .map(|n| {
+                let widest = self.rows.iter().map(|row| row.column_width(n)).max().unwrap_or(0);
+                let width = cmp::max(widest, self.min_widths.get(n).cloned().unwrap_or(0));
+
+                match self.width_limits.get(self.columns[n].name()) {
+                    Some(limit) => limit.apply(width),
+                    None        => width,
+                }
+            })
+            .collect()
+    }
+
+    /// Constrain the widths of the columns with the given names.
+    pub fn set_width_limits(&mut self, limits: HashMap<String, WidthLimit>) {
+        self.width_limits = limits;
+    }
+
+    /// Override the order in which columns get truncated to make rows fit.
+    /// Columns that aren't in the list never get truncated.
+    pub fn set_truncation_order(&mut self, order: Vec<Column>) {
+        self.truncation_order = order;
+    }
+
     /// Add a dummy "header" row to the table, which contains the names of all
-    /// the columns, underlined. This has dummy data for the cases that aren't
-    /// actually used, such as the depth or list of attributes.
-    pub fn add_header(&mut self) {
+    /// the columns, styled in the given way. This has dummy data for the
+    /// cases that aren't actually used, such as the depth or list of
+    /// attributes.
+    pub fn add_header(&mut self, style: HeaderStyle) {
+        let name_style = match style {
+            HeaderStyle::Plain  => Style::default(),
+            _                   => self.colours.header,
+        };
+
         let row = Row {
             depth:    0,
-            cells:    Some(self.columns.iter().map(|c| Cell::paint(self.colours.header, c.header())).collect()),
-            name:     Cell::paint(self.colours.header, "Name"),
+            cells:    Some(self.columns.iter().map(|c| Cell::paint(self.header_style(c, style), c.header())).collect()),
+            name:     Cell::paint(name_style, "Name"),
             last:     false,
+            is_dir:   false,
+            highlighted: false,
+            size:     None,
+            marker:   None,
         };
 
         self.rows.push(row);
+        self.has_header = true;
+    }
+
+    /// The style to paint the given column's header in.
+    fn header_style(&self, column: &Column, style: HeaderStyle) -> Style {
+        let c = &self.colours;
+
+        match style {
+            HeaderStyle::Uniform    => c.header,
+            HeaderStyle::Plain      => Style::default(),
+            HeaderStyle::PerColumn  => match *column {
+                Column::Permissions(_) => c.perms.user_read,
+                Column::FileSize(_)    => c.size.numbers,
+                Column::Timestamp(_)   => c.date,
+                Column::LastActivity   => c.date,
+                Column::MetaChanged    => c.date,
+                Column::Lifetime       => c.date,
+                Column::AgeDays        => c.date,
+                Column::AgeGradient    => c.date,
+                Column::Blocks         => c.blocks,
+                Column::User           => c.users.user_you,
+                Column::Access         => c.perms.user_read,
+                Column::Group          => c.users.group_yours,
+                Column::HardLinks      => c.links.normal,
+                Column::LinkDepth      => c.filetypes.symlink,
+                Column::Inode          => c.inode,
+                Column::ParentInode    => c.inode,
+                Column::Mount          => c.symlink_path,
+                Column::XattrCount     => c.perms.attribute,
+                Column::Capabilities   => c.perms.attribute,
+                Column::Descendants    => c.size.numbers,
+                Column::SubdirCount    => c.size.numbers,
+                Column::Rank           => c.punctuation,
+                Column::SizeBar        => c.size.numbers,
+                Column::SizePercent    => c.size.numbers,
+                Column::FsPercent      => c.size.numbers,
+                Column::Sparseness     => c.size.numbers,
+                Column::LargeFlag(_)   => c.size.large,
+                Column::LineCount      => c.size.numbers,
+                Column::Checksum(_)    => c.inode,
+                Column::MimeType       => c.filetypes.normal,
+                Column::Interpreter    => c.filetypes.executable,
+                Column::GitStatus      => c.git.modified,
+                Column::GitSummary     => c.git.modified,
+                Column::GitIgnored     => c.git.ignored,
+                Column::GitLastCommit  => c.git.commit,
+            }.underline(),
+        }
     }
 
     fn add_error(&mut self, error: &io::Error, depth: usize, last: bool, path: Option<PathBuf>) {
-        let error_message = match path {
-            Some(path) => format!("<{}: {}>", path.display(), error),
-            None       => format!("<{}>", error),
+        let row = Row {
+            depth:    depth,
+            cells:    None,
+            name:     Cell::paint(self.colours.broken_arrow, &error_message(error, path)),
+            last:     last,
+            is_dir:   false,
+            highlighted: false,
+            size:     None,
+            marker:   None,
         };
 
+        self.rows.push(row);
+    }
+
+    /// Add errors to the end of the name of the row at the given index,
+    /// rather than giving them rows of their own. The errors get taken out
+    /// of the vector, leaving nothing for any rows.
+    fn add_inline_errors(&mut self, row_index: usize, errors: &mut Vec<(io::Error, Option<PathBuf>)>) {
+        for (error, path) in errors.drain(..) {
+            let message = Cell::paint(self.colours.broken_arrow, &error_message(&error, path));
+            let name = &mut self.rows[row_index].name;
+            name.add_spaces(1);
+            name.append(&message);
+        }
+    }
+
+    /// Add a row saying how many of a directory's files were left out, which
+    /// comes last in the directory.
+    fn add_hidden_count(&mut self, hidden: usize, depth: usize) {
         let row = Row {
             depth:    depth,
             cells:    None,
-            name:     Cell::paint(self.colours.broken_arrow, &error_message),
-            last:     last,
+            name:     Cell::paint(self.colours.punctuation, &format!("… ({} more)", self.numeric.format_int(hidden))),
+            last:     true,
+            is_dir:   false,
+            highlighted: false,
+            size:     None,
+            marker:   None,
         };
 
         self.rows.push(row);
     }
 
-    fn add_xattr(&mut self, xattr: Attribute, depth: usize, last: bool) {
+    /// Add a row for an extended attribute, padding its name to the given
+    /// width so the lengths of all of a file's attributes line up.
+    fn add_xattr(&mut self, xattr: Attribute, depth: usize, last: bool, name_width: usize) {
+        let mut text = xattr.name.clone();
+        for _ in UnicodeWidthStr::width(&xattr.name[..]) .. name_width {
+            text.push(' ');
+        }
+
+        text.push_str(&format!(" (len {})", xattr.size));
+
         let row = Row {
             depth:    depth,
             cells:    None,
-            name:     Cell::paint(self.colours.perms.attribute, &format!("{} (len {})", xattr.name, xattr.size)),
+            name:     Cell::paint(self.colours.perms.attribute, &text),
             last:     last,
+            is_dir:   false,
+            highlighted: false,
+            size:     None,
+            marker:   None,
         };
 
         self.rows.push(row);
     }
 
     pub fn add_file_with_cells(&mut self, cells: Vec<Cell>, file: &File, depth: usize, last: bool, links: bool) {
+        let target = if links && file.is_link() { Some(file.link_target()) } else { None };
+        let name = name_cell(file, &self.colours, self.name_colouring, target, self.dir_slash, self.quote_names);
+
         let row = Row {
             depth:    depth,
             cells:    Some(cells),
-            name:     Cell { text: filename(file, &self.colours, links), length: file.file_name_width() },
+            name:     name,
             last:     last,
+            is_dir:   file.is_directory(),
+            highlighted: false,
+            size:     bar_size(file.size()),
+            marker:   None,
         };
 
         self.rows.push(row);
+        self.count_file(file);
+        self.note_hard_links(file);
+    }
+
+    /// Remember which row a file with more than one hard link went into,
+    /// so its other links can be looked for among the rest of the rows
+    /// once they've all been added. It has to be the last row.
+    fn note_hard_links(&mut self, file: &File) {
+        if let (true, Some(key)) = (self.link_siblings, hard_link_key(file)) {
+            let row_index = self.rows.len() - 1;
+            self.hard_links.push((row_index, key));
+        }
+    }
+
+    /// Add how many of each hard-linked file's links are in the table to
+    /// its cell in the links column, now that every row has been added.
+    pub fn add_link_siblings(&mut self) {
+        let column_index = match self.columns.iter().position(|c| *c == Column::HardLinks) {
+            Some(index)  => index,
+            None         => return,
+        };
+
+        let counts = count_hard_links(self.hard_links.iter().map(|&(_, key)| key));
+
+        for &(row_index, key) in self.hard_links.iter() {
+            let note = self.link_siblings_note(counts[&key]);
+            if let Some(ref mut cells) = self.rows[row_index].cells {
+                cells[column_index].append(&note);
+            }
+        }
+    }
+
+    /// Add how many of each hard-linked file's links are in the listing to
+    /// the cells of the given files, before they've been added to a table.
+    /// The grid view needs this, as it spreads its files across several.
+    pub fn add_link_siblings_to_cells(&self, files: &[File], cells: &mut [Vec<Cell>]) {
+        let column_index = match self.columns.iter().position(|c| *c == Column::HardLinks) {
+            Some(index)  => index,
+            None         => return,
+        };
+
+        let keys: Vec<_> = files.iter().map(hard_link_key).collect();
+        let counts = count_hard_links(keys.iter().filter_map(|key| *key));
+
+        for (key, row) in keys.iter().zip(cells.iter_mut()) {
+            if let Some(key) = *key {
+                row[column_index].append(&self.link_siblings_note(counts[&key]));
+            }
+        }
+    }
+
+    fn link_siblings_note(&self, here: usize) -> Cell {
+        Cell::paint(self.colours.punctuation, &format!(" ({} here)", self.numeric.format_int(here)))
+    }
+
+    /// Count a file that's been added to this table, by its type.
+    fn count_file(&mut self, file: &File) {
+        match file.permissions().file_type {
+            f::Type::File       => self.counts.files += 1,
+            f::Type::Directory  => self.counts.directories += 1,
+            f::Type::Link       => self.counts.links += 1,
+            f::Type::Pipe       => self.counts.pipes += 1,
+            f::Type::Special    => self.counts.specials += 1,
+        }
+    }
+
+    /// A one-line summary of how many files of each type have been added
+    /// to the table, at any depth, such as `5 files, 2 directories`.
+    pub fn summary(&self) -> String {
+        let counts = [
+            (self.counts.files,       "file",      "files"),
+            (self.counts.directories, "directory", "directories"),
+            (self.counts.links,       "symlink",   "symlinks"),
+            (self.counts.pipes,       "pipe",      "pipes"),
+            (self.counts.specials,    "special file", "special files"),
+        ];
+
+        let parts: Vec<String> = counts.iter()
+            .filter(|&&(count, _, _)| count > 0)
+            .map(|&(count, singular, plural)| {
+                let word = if count == 1 { singular } else { plural };
+                format!("{} {}", self.colours.size.numbers.paint(&count.to_string()), word)
+            })
+            .collect();
+
+        if parts.is_empty() {
+            format!("{} files", self.colours.size.numbers.paint("0"))
+        }
+        else {
+            parts.join(", ")
+        }
+    }
+
+    /// Turn a file's cells into one line of JSON, alongside its name, its
+    /// path, and how deep into the tree it is, which is enough for the tree
+    /// to be put back together from the lines in any order.
+    ///
+    /// The cells are used as they are, so they should have been rendered
+    /// without colours.
+    pub fn json_line(&self, file: &File, depth: usize, cells: &[Cell]) -> String {
+        let mut fields = vec![
+            ("depth".to_string(), depth.to_string()),
+            ("path".to_string(),  json::string(&file.path.to_string_lossy())),
+            ("name".to_string(),  json::string(&file.name)),
+        ];
+
+        for (column, cell) in self.columns.iter().zip(cells.iter()) {
+            fields.push((json_key(column), json::string(&cell.text)));
+        }
+
+        json::object(&fields)
     }
 
     /// Use the list of columns to find which cells should be produced for
     /// this file, per-column.
     pub fn cells_for_file(&mut self, file: &File, xattrs: bool) -> Vec<Cell> {
+        // Dimmed cells get rendered with the dimmed colours standing in for
+        // the table's own, which get put back afterwards.
+        match self.dimmed.take() {
+            Some(dimmed) => {
+                let colours = mem::replace(&mut self.colours, dimmed);
+                let cells = self.cells_for_target(file, xattrs);
+                self.dimmed = Some(mem::replace(&mut self.colours, colours));
+                cells
+            },
+            None => self.cells_for_target(file, xattrs),
+        }
+    }
+
+    /// Produce the cells for a file, or for whatever it links to if links
+    /// are being dereferenced.
+    fn cells_for_target(&mut self, file: &File, xattrs: bool) -> Vec<Cell> {
+        if !self.dereference || !file.is_link() {
+            return self.cells_for_metadata(file, xattrs);
+        }
+
+        // The whole chain of links gets followed, each one relative to
+        // where it is. Links that point nowhere can only describe
+        // themselves, so they get a marker after their permissions to show
+        // that's happened.
+        match fs::metadata(&file.path) {
+            Ok(metadata) => self.cells_for_metadata(&File::with_metadata(metadata, &file.path, file.dir), xattrs),
+            Err(_)       => {
+                let mut cells = self.cells_for_metadata(file, xattrs);
+                let permissions = self.columns.iter().position(|c| match *c { Column::Permissions(_) => true, _ => false });
+                if let Some(n) = permissions {
+                    cells[n].append(&Cell::paint(self.colours.broken_arrow, "!"));
+                }
+                cells
+            },
+        }
+    }
+
+    fn cells_for_metadata(&mut self, file: &File, xattrs: bool) -> Vec<Cell> {
         self.columns.clone().iter()
                     .map(|c| self.display(file, c, xattrs))
                     .collect()
@@ -485,36 +2018,115 @@ impl<U> Table<U> where U: Users {
 
     fn display(&mut self, file: &File, column: &Column, xattrs: bool) -> Cell {
         match *column {
-            Column::Permissions    => self.render_permissions(file.permissions(), xattrs),
-            Column::FileSize(fmt)  => self.render_size(file.size(), fmt),
+            Column::Permissions(f) => self.render_permissions_of(file, f, xattrs),
+            Column::FileSize(opts) if opts.dir_entries && file.is_directory()
+                                   => self.render_entry_count(file.entry_count()),
+            Column::FileSize(opts) if opts.dir_totals && file.is_directory()
+                                   => self.render_dir_size(file.recursive_size(), opts),
+            Column::FileSize(opts) => self.render_size(file.size(), file.allocated_size(), opts),
             Column::Timestamp(t)   => self.render_time(file.timestamp(t)),
+            Column::LastActivity   => self.render_time(file.last_activity()),
+            Column::MetaChanged    => self.render_meta_changed(file.timestamp(TimeType::FileModified), file.timestamp(TimeType::FileCreated)),
+            Column::Lifetime       => self.render_lifetime(file.birth_time(), file.timestamp(TimeType::FileModified)),
+            Column::AgeDays        => self.render_age_days(file.timestamp(TimeType::FileModified)),
+            Column::AgeGradient    => self.render_age_gradient(file.timestamp(TimeType::FileModified)),
             Column::HardLinks      => self.render_links(file.links()),
+            Column::LinkDepth      => self.render_link_depth(file.link_depth()),
             Column::Inode          => self.render_inode(file.inode()),
+            Column::ParentInode    => match file.parent_inode() {
+                Some(inode)  => self.render_inode(inode),
+                None         => self.placeholder(),
+            },
+            Column::Mount          => self.render_mount(file.device()),
             Column::Blocks         => self.render_blocks(file.blocks()),
+            Column::XattrCount     => self.render_xattr_count(file.xattr_count()),
+            Column::Capabilities   => self.render_capabilities(file.capabilities()),
+            Column::Descendants    => self.placeholder(),
+            Column::Rank           => self.placeholder(),
+            Column::SubdirCount    => self.render_entry_count(file.subdir_count()),
+            Column::SizeBar        => Cell::blank(SIZE_BAR_WIDTH),
+            Column::SizePercent    => Cell::blank(SIZE_PERCENT_WIDTH),
+            Column::FsPercent      => {
+                let size = match file.size() {
+                    f::Size::None if file.is_directory() && self.dir_totals() => Some(file.recursive_size().bytes),
+                    f::Size::Some(size)  => Some(size),
+                    f::Size::None        => None,
+                };
+
+                let fs_size = self.lookup_fs_size(file);
+                self.render_fs_percent(size, fs_size)
+            },
+            Column::Sparseness     => self.render_sparseness(file.size(), file.allocated_size()),
+            Column::LargeFlag(threshold) => self.render_large_flag(file.size(), threshold),
+            Column::LineCount      => self.render_line_count(file.line_count()),
+            Column::Checksum(a)    => self.render_checksum(file.checksum(a)),
+            Column::MimeType       => self.render_mime(mime::mime_type(file)),
+            Column::Interpreter    => self.render_interpreter(file.interpreter()),
             Column::User           => self.render_user(file.user()),
+            Column::Access         => self.render_access(file.permissions(), file.user(), file.group()),
             Column::Group          => self.render_group(file.group()),
             Column::GitStatus      => self.render_git_status(file.git_status()),
+            Column::GitSummary     => self.render_git_summary(file.git_status(), file.git_ignored()),
+            Column::GitIgnored     => self.render_git_ignored(file.git_ignored()),
+            Column::GitLastCommit  => self.render_git_commit(file.git_last_commit()),
+        }
+    }
+
+    fn render_permissions_of(&self, file: &File, format: PermissionsFormat, xattrs: bool) -> Cell {
+        let acl = format != PermissionsFormat::TypeOnly && xattr::has_acl(&file.path);
+        self.render_permissions(file.permissions(), format, acl, xattrs)
+    }
+
+    /// Render a file's permissions in the given format, followed by a `+`
+    /// if it has an access control list and an `@` if it has extended
+    /// attributes.
+    fn render_permissions(&self, permissions: f::Permissions, format: PermissionsFormat, acl: bool, xattrs: bool) -> Cell {
+        if format == PermissionsFormat::TypeOnly {
+            return Cell {
+                text: self.render_file_type(&permissions.file_type).to_string(),
+                length: 1,
+            };
+        }
+
+        let mut cell = Cell::empty();
+
+        if format != PermissionsFormat::Symbolic {
+            cell.append(&Cell::paint(self.colours.perms.octal, &octal_permissions(&permissions)));
+        }
+
+        if format == PermissionsFormat::Both {
+            cell.add_spaces(1);
+        }
+
+        if format != PermissionsFormat::Octal {
+            cell.append(&self.render_symbolic_permissions(&permissions, format != PermissionsFormat::Bits));
         }
+
+        if acl {
+            cell.append(&Cell::paint(self.colours.perms.attribute, "+"));
+        }
+
+        if xattrs {
+            cell.append(&Cell::paint(self.colours.perms.attribute, "@"));
+        }
+
+        cell
     }
 
-    fn render_permissions(&self, permissions: f::Permissions, xattrs: bool) -> Cell {
+    /// Render the string of permission flags, optionally preceded by the
+    /// character for the file's type.
+    fn render_symbolic_permissions(&self, permissions: &f::Permissions, with_type: bool) -> Cell {
         let c = self.colours.perms;
         let bit = |bit, chr: &'static str, style: Style| {
             if bit { style.paint(chr) } else { self.colours.punctuation.paint("-") }
         };
 
-        let file_type = match permissions.file_type {
-            f::Type::File       => self.colours.filetypes.normal.paint("."),
-            f::Type::Directory  => self.colours.filetypes.directory.paint("d"),
-            f::Type::Pipe       => self.colours.filetypes.special.paint("|"),
-            f::Type::Link       => self.colours.filetypes.symlink.paint("l"),
-            f::Type::Special    => self.colours.filetypes.special.paint("?"),
-        };
+        let file_type = self.render_file_type(&permissions.file_type);
 
         let x_colour = if let f::Type::File = permissions.file_type { c.user_execute_file }
                                                                else { c.user_execute_other };
 
-        let mut columns = vec![
+        let columns = vec![
             file_type,
             bit(permissions.user_read,     "r", c.user_read),
             bit(permissions.user_write,    "w", c.user_write),
@@ -527,63 +2139,402 @@ impl<U> Table<U> where U: Users {
             bit(permissions.other_execute, "x", c.other_execute),
         ];
 
-        if xattrs {
-            columns.push(c.attribute.paint("@"));
-        }
+        let columns = if with_type { &columns[..] } else { &columns[1..] };
 
         Cell {
-            text: ANSIStrings(&columns).to_string(),
+            text: ANSIStrings(columns).to_string(),
             length: columns.len(),
         }
     }
 
-    fn render_links(&self, links: f::Links) -> Cell {
-        let style = if links.multiple { self.colours.links.multi_link_file }
-                                 else { self.colours.links.normal };
-
-        Cell::paint(style, &self.numeric.format_int(links.count))
-    }
-
-    fn render_blocks(&self, blocks: f::Blocks) -> Cell {
-        match blocks {
-            f::Blocks::Some(blocks)  => Cell::paint(self.colours.blocks, &blocks.to_string()),
-            f::Blocks::None          => Cell::paint(self.colours.punctuation, "-"),
+    /// The single character at the start of the permissions that shows
+    /// what type of file this is.
+    fn render_file_type(&self, file_type: &f::Type) -> ANSIString<'static> {
+        match *file_type {
+            f::Type::File       => self.colours.filetypes.normal.paint("."),
+            f::Type::Directory  => self.colours.filetypes.directory.paint("d"),
+            f::Type::Pipe       => self.colours.filetypes.special.paint("|"),
+            f::Type::Link       => self.colours.filetypes.symlink.paint("l"),
+            f::Type::Special    => self.colours.filetypes.special.paint("?"),
         }
     }
 
-    fn render_inode(&self, inode: f::Inode) -> Cell {
-        Cell::paint(self.colours.inode, &inode.0.to_string())
-    }
+    /// Draw a bar as long as the given size is, relative to the largest size
+    /// in the table, using eighth-width blocks for the fractional part.
+    /// Rows without a size get an empty bar.
+    fn render_size_bar(&self, size: Option<u64>, largest_size: u64) -> Cell {
+        const PARTS: [&'static str; 8] = [ "", "▏", "▎", "▍", "▌", "▋", "▊", "▉" ];
 
-    fn render_size(&self, size: f::Size, size_format: SizeFormat) -> Cell {
-        if let f::Size::Some(offset) = size {
-            let result = match size_format {
-                SizeFormat::DecimalBytes  => decimal_prefix(offset as f64),
-                SizeFormat::BinaryBytes   => binary_prefix(offset as f64),
-                SizeFormat::JustBytes     => return Cell::paint(self.colours.size.numbers, &self.numeric.format_int(offset)),
-            };
+        let size = match size {
+            Some(size) if largest_size > 0 => size,
+            _                              => return Cell::blank(SIZE_BAR_WIDTH),
+        };
 
-            match result {
-                Standalone(bytes)    => Cell::paint(self.colours.size.numbers, &*bytes.to_string()),
-                Prefixed(prefix, n)  => {
-                    let number = if n < 10f64 { self.numeric.format_float(n, 1) } else { self.numeric.format_int(n as isize) };
-                    let symbol = prefix.symbol();
+        let eighths = ((size as f64 / largest_size as f64) * (SIZE_BAR_WIDTH * 8) as f64).round() as usize;
 
-                    Cell {
-                        text: ANSIStrings( &[ self.colours.size.numbers.paint(&number[..]), self.colours.size.unit.paint(symbol) ]).to_string(),
-                        length: number.len() + symbol.len(),
-                    }
-                }
+        let mut bar = String::new();
+        for _ in 0 .. eighths / 8 {
+            bar.push('█');
+        }
+        bar.push_str(PARTS[eighths % 8]);
+
+        let mut cell = Cell::paint(self.metadata_colours().size.numbers, &bar);
+        cell.add_spaces(SIZE_BAR_WIDTH - cell.length);
+        cell
+    }
+
+    /// Show the given size as a percentage of the total size of the files
+    /// in the table, to one decimal place. Rows without a size are left
+    /// blank.
+    fn render_size_percent(&self, size: Option<u64>, total_size: u64) -> Cell {
+        let size = match size {
+            Some(size) if total_size > 0 => size,
+            _                            => return Cell::blank(SIZE_PERCENT_WIDTH),
+        };
+
+        let percent = size as f64 * 100.0 / total_size as f64;
+        Cell::paint(self.metadata_colours().size.numbers, &format!("{}%", self.numeric.format_float(percent, 1)))
+    }
+
+    /// Show the given size as a percentage of the capacity of the
+    /// filesystem it's on, to one decimal place. Files without a size, and
+    /// those on filesystems of unknown size, get a dash.
+    fn render_fs_percent(&self, size: Option<u64>, fs_size: Option<u64>) -> Cell {
+        match (size, fs_size) {
+            (Some(size), Some(total)) if total > 0 => {
+                let percent = size as f64 * 100.0 / total as f64;
+                Cell::paint(self.colours.size.numbers, &format!("{}%", self.numeric.format_float(percent, 1)))
+            },
+            _ => self.placeholder(),
+        }
+    }
+
+    /// Whether directories' sizes are the totals of the files under them.
+    fn dir_totals(&self) -> bool {
+        self.columns.iter().any(|c| match *c {
+            Column::FileSize(opts)  => opts.dir_totals,
+            _                       => false,
+        })
+    }
+
+    /// The capacity of the filesystem the given file is on, which only
+    /// gets looked up for the first file on each device.
+    fn lookup_fs_size(&mut self, file: &File) -> Option<u64> {
+        let device = file.device().0 as u64;
+        *self.fs_sizes.entry(device).or_insert_with(|| filesystem_size(&file.path))
+    }
+
+    /// Show how much of a file's apparent size isn't backed by blocks on
+    /// disk, as a percentage. Files that are mostly holes get highlighted.
+    /// Empty files, and those without a size, have no sparseness to show.
+    fn render_sparseness(&self, size: f::Size, allocated: f::Size) -> Cell {
+        let (size, allocated) = match (size, allocated) {
+            (f::Size::Some(size), f::Size::Some(allocated)) if size > 0 => (size, allocated),
+            _  => return self.placeholder(),
+        };
+
+        let percent = if allocated >= size { 0 }
+                                       else { ((size - allocated) as f64 * 100.0 / size as f64).round() as isize };
+
+        let style = if percent >= 50 { self.colours.size.allocated }
+                                else { self.colours.size.numbers };
+
+        Cell::paint(style, &format!("{}%", self.numeric.format_int(percent)))
+    }
+
+    /// Mark files bigger than the given number of bytes with a `!`.
+    fn render_large_flag(&self, size: f::Size, threshold: u64) -> Cell {
+        match size {
+            f::Size::Some(size) if size > threshold => Cell::paint(self.colours.size.large, "!"),
+            _                                       => Cell::empty(),
+        }
+    }
+
+    fn render_links(&self, links: f::Links) -> Cell {
+        let style = if links.multiple { self.colours.links.multi_link_file }
+                                 else { self.colours.links.normal };
+
+        Cell::paint(style, &self.numeric.format_int(links.count))
+    }
+
+    /// Chains that loop get an infinity sign, and ones that end at a file
+    /// that doesn't exist get an exclamation mark.
+    fn render_link_depth(&self, depth: f::LinkDepth) -> Cell {
+        match depth {
+            f::LinkDepth::Some(hops)  => Cell::paint(self.colours.links.normal, &self.numeric.format_int(hops)),
+            f::LinkDepth::Cyclic      => Cell::paint(self.colours.broken_arrow, "∞"),
+            f::LinkDepth::Broken      => Cell::paint(self.colours.broken_arrow, "!"),
+            f::LinkDepth::NotLink     => self.placeholder(),
+        }
+    }
+
+    fn render_blocks(&self, blocks: f::Blocks) -> Cell {
+        match blocks {
+            f::Blocks::Some(blocks)  => Cell::paint(self.colours.blocks, &blocks.to_string()),
+            f::Blocks::None          => self.placeholder(),
+        }
+    }
+
+    /// Show a file's capabilities the way `getcap` does, with the ones
+    /// granted in the same way grouped together.
+    fn render_capabilities(&self, sets: Vec<CapabilitySet>) -> Cell {
+        if sets.is_empty() {
+            return self.placeholder();
+        }
+
+        let mut cell = Cell::empty();
+        for (index, set) in sets.iter().enumerate() {
+            if index > 0 {
+                cell.add_spaces(1);
+            }
+
+            cell.append(&Cell::paint(self.colours.perms.user_execute_file, &set.names.join(",")));
+            cell.append(&Cell::paint(self.colours.punctuation, &format!("={}", set.flags)));
+        }
+
+        cell
+    }
+
+    fn render_xattr_count(&self, count: f::XattrCount) -> Cell {
+        match count {
+            f::XattrCount::Some(0)      => self.placeholder(),
+            f::XattrCount::Some(count)  => Cell::paint(self.colours.perms.attribute, &count.to_string()),
+            f::XattrCount::Unreadable   => Cell::paint(self.colours.punctuation, "?"),
+        }
+    }
+
+    fn render_inode(&self, inode: f::Inode) -> Cell {
+        if self.hex_inodes {
+            Cell::paint(self.colours.inode, &format!("0x{:x}", inode.0))
+        }
+        else {
+            Cell::paint(self.colours.inode, &inode.0.to_string())
+        }
+    }
+
+    /// Render the mount point of the given device, reading the mount table
+    /// the first time it's needed.
+    fn render_mount(&mut self, device: f::Device) -> Cell {
+        if self.mounts.is_none() {
+            self.mounts = Some(mount_points());
+        }
+
+        match self.mounts.as_ref().and_then(|m| m.get(&(device.0 as u64))) {
+            Some(path)  => Cell::paint(self.colours.symlink_path, path),
+            None        => self.placeholder(),
+        }
+    }
+
+    fn render_size(&self, size: f::Size, allocated: f::Size, size_options: SizeOptions) -> Cell {
+        match (size, allocated) {
+            (f::Size::Some(offset), f::Size::Some(blocks)) if size_options.allocated => {
+                let mut cell = self.render_size_in(offset, size_options, self.colours.size.numbers, self.colours.size.unit);
+                cell.append(&Cell::paint(self.colours.punctuation, "/"));
+                cell.append(&self.render_size_in(blocks, size_options, self.colours.size.allocated, self.colours.size.allocated));
+                cell
+            },
+            (f::Size::Some(offset), _) => self.render_size_in(offset, size_options, self.colours.size.numbers, self.colours.size.unit),
+            (f::Size::None, _)         => self.placeholder(),
+        }
+    }
+
+    /// Render a directory's total size, followed by a `+` if the walk was
+    /// cut short by the depth limit, or a `?` if some of it couldn't be read.
+    fn render_dir_size(&self, total: f::DirSize, size_options: SizeOptions) -> Cell {
+        let mut cell = self.render_size(f::Size::Some(total.bytes), f::Size::None, size_options);
+
+        if total.truncated {
+            cell.append(&Cell::paint(self.colours.punctuation, "+"));
+        }
+        else if total.approximate {
+            cell.append(&Cell::paint(self.colours.punctuation, "?"));
+        }
+
+        cell
+    }
+
+    fn render_entry_count(&self, count: f::EntryCount) -> Cell {
+        match count {
+            f::EntryCount::Some(count)  => Cell::paint(self.colours.size.numbers, &self.numeric.format_int(count)),
+            f::EntryCount::Unreadable   => self.placeholder(),
+        }
+    }
+
+    /// Only the start of each checksum is shown, which is enough to tell
+    /// files apart by eye.
+    fn render_checksum(&self, checksum: f::Checksum) -> Cell {
+        match checksum {
+            f::Checksum::Some(hex)  => Cell::paint(self.colours.inode, &hex[.. cmp::min(hex.len(), CHECKSUM_WIDTH)]),
+            f::Checksum::Unknown    => self.placeholder(),
+        }
+    }
+
+    fn render_line_count(&self, count: f::LineCount) -> Cell {
+        match count {
+            f::LineCount::Some(count)  => Cell::paint(self.colours.size.numbers, &self.numeric.format_int(count)),
+            f::LineCount::Unknown      => self.placeholder(),
+        }
+    }
+
+    /// Render a number of bytes using the given size format, painting the
+    /// number and its unit in the given styles.
+    fn render_size_in(&self, offset: u64, size_options: SizeOptions, numbers: Style, unit: Style) -> Cell {
+        let result = match size_options.format {
+            SizeFormat::DecimalBytes  => decimal_prefix(offset as f64),
+            SizeFormat::BinaryBytes   => binary_prefix(offset as f64),
+            SizeFormat::JustBytes     => return self.render_bytes(self.numeric.format_int(offset), size_options, numbers, unit),
+            SizeFormat::BothPrefixes  => return self.render_both_prefixes(offset, size_options, numbers, unit),
+            SizeFormat::FixedUnit(u, decimals) => {
+                let number = self.numeric.format_float(offset as f64 / u.bytes(), decimals);
+                return render_size_with_unit(number, &size_options.unit_symbol(u.symbol()), numbers, unit);
+            },
+        };
+
+        match result {
+            Standalone(bytes)    => self.render_bytes(bytes.to_string(), size_options, numbers, unit),
+            Prefixed(prefix, n)  => {
+                let number = match size_options.decimals {
+                    Some(0)                  => self.numeric.format_int(n.round() as isize),
+                    Some(decimals)           => self.numeric.format_float(n, decimals),
+                    None if n < 10f64        => self.numeric.format_float(n, 1),
+                    None                     => self.numeric.format_int(n as isize),
+                };
+                render_size_with_unit(number, &size_options.unit_symbol(prefix.symbol()), numbers, unit)
             }
         }
+    }
+
+    /// Render a number of bytes with decimal prefixes, then with binary
+    /// ones, separated by a slash. Small sizes come out the same either
+    /// way, so they only get shown once.
+    fn render_both_prefixes(&self, offset: u64, size_options: SizeOptions, numbers: Style, unit: Style) -> Cell {
+        let decimal = self.render_size_in(offset, SizeOptions { format: SizeFormat::DecimalBytes, ..size_options }, numbers, unit);
+        let binary  = self.render_size_in(offset, SizeOptions { format: SizeFormat::BinaryBytes,  ..size_options }, self.colours.size.binary, self.colours.size.binary);
+
+        if decimal.plain_text() == binary.plain_text() {
+            return decimal;
+        }
+
+        let mut cell = decimal;
+        cell.append(&Cell::paint(self.colours.punctuation, "/"));
+        cell.append(&binary);
+        cell
+    }
+
+    /// Render a size that's a plain number of bytes, which may or may not be
+    /// followed by a `B` suffix.
+    fn render_bytes(&self, number: String, size_options: SizeOptions, numbers: Style, unit: Style) -> Cell {
+        if size_options.byte_suffix {
+            render_size_with_unit(number, " B", numbers, unit)
+        }
         else {
-            Cell::paint(self.colours.punctuation, "-")
+            Cell::paint(numbers, &number)
         }
     }
 
     fn render_time(&self, timestamp: f::Time) -> Cell {
-        let date = self.tz.at(LocalDateTime::at(timestamp.0));
+        let date = LocalDateTime::at(timestamp.0);
+
+        let mut text = match self.tz {
+            Some(ref tz)  => self.format_time(&tz.at(date), timestamp),
+            None          => self.format_time(&date, timestamp),
+        };
+
+        if self.utc {
+            text.push_str(" UTC");
+        }
+
+        Cell::paint(self.colours.date, &text)
+    }
+
+    /// Render how long after a file's contents were last modified that its
+    /// metadata changed, such as by a `chmod` or `chown`, or nothing if it
+    /// hasn't changed since. The change time is the one that the
+    /// `FileCreated` time type reads on Unix.
+    fn render_meta_changed(&self, modified: f::Time, changed: f::Time) -> Cell {
+        if (changed.0, changed.1) <= (modified.0, modified.1) {
+            return Cell::empty();
+        }
+
+        let delta = format!("+{}", format_duration((changed.0 - modified.0) as i64));
+        Cell::paint(self.colours.date, &delta)
+    }
+
+    /// Render how long a file went on being modified after it was created.
+    /// Files without a birth time, or that were last modified before they
+    /// were created, which happens when clocks are wrong, get the
+    /// placeholder.
+    fn render_lifetime(&self, born: Option<f::Time>, modified: f::Time) -> Cell {
+        match born {
+            Some(born) if (born.0, born.1) <= (modified.0, modified.1) => {
+                Cell::paint(self.colours.date, &format_duration((modified.0 - born.0) as i64))
+            },
+            _ => self.placeholder(),
+        }
+    }
+
+    /// Render the number of whole days since the given time. Times in the
+    /// future have negative ages, rounding away from zero, so anything from
+    /// the last 24 hours is `0` but anything ahead of now is at least `-1`.
+    fn render_age_days(&self, timestamp: f::Time) -> Cell {
+        const SECONDS_PER_DAY: i64 = 60 * 60 * 24;
+
+        let seconds = self.now - timestamp.0;
+        let days = if seconds >= 0 { seconds / SECONDS_PER_DAY }
+                              else { (seconds + 1) / SECONDS_PER_DAY - 1 };
+
+        Cell::paint(self.colours.date, &days.to_string())
+    }
+
+    /// Draw a dot in the colour of the first stop that the file is younger
+    /// than, or the last stop if it's older than all of them. Without any
+    /// stops, the dot is painted like a date.
+    fn render_age_gradient(&self, timestamp: f::Time) -> Cell {
+        let age = self.now - timestamp.0;
+
+        let style = match self.age_stops.iter().find(|stop| age < stop.age).or(self.age_stops.last()) {
+            Some(stop)  => Fixed(stop.colour).normal(),
+            None        => self.colours.date,
+        };
+
+        Cell::paint(style, "●")
+    }
+
+    fn render_mime(&self, mime: Option<&str>) -> Cell {
+        match mime {
+            Some(mime)  => Cell::paint(self.colours.filetypes.normal, mime),
+            None        => self.placeholder(),
+        }
+    }
+
+    fn render_interpreter(&self, interpreter: f::Interpreter) -> Cell {
+        match interpreter.0 {
+            Some(name)  => Cell::paint(self.colours.filetypes.executable, &name),
+            None        => self.placeholder(),
+        }
+    }
+
+    /// Format a date in the table's time style, given the timestamp it was
+    /// made from, which carries the nanoseconds the date itself doesn't.
+    fn format_time<D: DatePiece + TimePiece>(&self, date: &D, timestamp: f::Time) -> String {
+        if self.precise_times {
+            return format_precise_date(date, timestamp.1);
+        }
+
+        let month = date.month().months_from_january() + 1;
 
+        match self.time_style {
+            TimeStyle::Default  => self.format_date(date),
+            TimeStyle::FullISO  => format!("{} {}", format_precise_date(date, timestamp.1), format_offset(utc_offset(date, timestamp.0))),
+            TimeStyle::LongISO  => format!("{:04}-{:02}-{:02} {:02}:{:02}", date.year(), month, date.day(), date.hour(), date.minute()),
+            TimeStyle::ISO if date.year() == self.current_year
+                                => format!("{:02}-{:02} {:02}:{:02}", month, date.day(), date.hour(), date.minute()),
+            TimeStyle::ISO      => format!("{:04}-{:02}-{:02}", date.year(), month, date.day()),
+            TimeStyle::Custom(ref format) => format_custom_date(format, date, timestamp, &self.time),
+        }
+    }
+
+    fn format_date<D: DatePiece + TimePiece>(&self, date: &D) -> String {
         let format = if date.year() == self.current_year {
                 DateFormat::parse("{2>:D} {:M} {2>:h}:{02>:m}").unwrap()
             }
@@ -591,20 +2542,31 @@ impl<U> Table<U> where U: Users {
                 DateFormat::parse("{2>:D} {:M} {5>:Y}").unwrap()
             };
 
-        Cell::paint(self.colours.date, &format.format(&date, &self.time))
+        format.format(date, &self.time)
     }
 
     fn render_git_status(&self, git: f::Git) -> Cell {
+        let length = self.git_char_width(&git.staged) + self.git_char_width(&git.unstaged);
+
         Cell {
             text: ANSIStrings(&[ self.render_git_char(git.staged),
                                  self.render_git_char(git.unstaged) ]).to_string(),
-            length: 2,
+            length: length,
+        }
+    }
+
+    /// How wide a Git status character is: one, unless the placeholder
+    /// shows in its place.
+    fn git_char_width(&self, status: &f::GitStatus) -> usize {
+        match *status {
+            f::GitStatus::NotModified  => UnicodeWidthStr::width(&self.placeholder[..]),
+            _                          => 1,
         }
     }
 
     fn render_git_char(&self, status: f::GitStatus) -> ANSIString {
         match status {
-            f::GitStatus::NotModified  => self.colours.punctuation.paint("-"),
+            f::GitStatus::NotModified  => self.colours.punctuation.paint(&self.placeholder),
             f::GitStatus::New          => self.colours.git.new.paint("N"),
             f::GitStatus::Modified     => self.colours.git.modified.paint("M"),
             f::GitStatus::Deleted      => self.colours.git.deleted.paint("D"),
@@ -613,10 +2575,45 @@ impl<U> Table<U> where U: Users {
         }
     }
 
+    /// Render a file's Git status as a single character: `✗` if it's
+    /// ignored, `?` if it's untracked, `●` if it has unstaged changes, `+`
+    /// if all its changes are staged, and `✓` if it has none.
+    fn render_git_summary(&self, git: f::Git, ignored: f::GitIgnored) -> Cell {
+        use file::fields::GitStatus::*;
+
+        if ignored.0 {
+            return Cell::paint(self.colours.git.ignored, "✗");
+        }
+
+        match (git.staged, git.unstaged) {
+            (_, New)                    => Cell::paint(self.colours.git.new, "?"),
+            (NotModified, NotModified)  => Cell::paint(self.colours.punctuation, "✓"),
+            (_, NotModified)            => Cell::paint(self.colours.git.new, "+"),
+            _                           => Cell::paint(self.colours.git.modified, "●"),
+        }
+    }
+
+    fn render_git_ignored(&self, ignored: f::GitIgnored) -> Cell {
+        if ignored.0 {
+            Cell::paint(self.colours.git.ignored, "!")
+        }
+        else {
+            Cell::empty()
+        }
+    }
+
+    fn render_git_commit(&self, commit: Option<f::GitCommit>) -> Cell {
+        match commit {
+            Some(commit)  => Cell::paint(self.colours.git.commit, &commit.id),
+            None          => self.placeholder(),
+        }
+    }
+
     fn render_user(&mut self, user: f::User) -> Cell {
-        let user_name = match self.users.get_user_by_uid(user.0) {
-            Some(user)  => user.name,
-            None        => user.0.to_string(),
+        let user_name = match (self.lookup_full_name(user.0), self.users.get_user_by_uid(user.0)) {
+            (Some(name), _)     => name,
+            (None, Some(user))  => user.name,
+            (None, None)        => user.0.to_string(),
         };
 
         let style = if self.users.get_current_uid() == user.0 { self.colours.users.user_you }
@@ -627,6 +2624,8 @@ impl<U> Table<U> where U: Users {
     fn render_group(&mut self, group: f::Group) -> Cell {
         let mut style = self.colours.users.group_not_yours;
 
+        let mut member_count = None;
+
         let group_name = match self.users.get_group_by_gid(group.0) {
             Some(group) => {
                 let current_uid = self.users.get_current_uid();
@@ -635,123 +2634,803 @@ impl<U> Table<U> where U: Users {
                         style = self.colours.users.group_yours;
                     }
                 }
+
+                if self.group_members {
+                    member_count = Some(self.count_group_members(group.gid, &group.members));
+                }
+
                 group.name
             },
             None => group.0.to_string(),
         };
 
-        Cell::paint(style, &*group_name)
+        let mut cell = match self.group_prefix {
+            Some(ref prefix) => {
+                let mut cell = Cell::paint(self.colours.punctuation, prefix);
+                cell.append(&Cell::paint(style, &*group_name));
+                cell
+            },
+            None => Cell::paint(style, &*group_name),
+        };
+
+        if let Some(count) = member_count {
+            cell.append(&Cell::paint(self.colours.punctuation, &format!(" ({})", count)));
+        }
+
+        cell
     }
 
-    /// Render the table as a vector of Cells, to be displayed on standard output.
-    pub fn print_table(&self) -> Vec<Cell> {
-        let mut stack = Vec::new();
-        let mut cells = Vec::new();
+    /// The full name of a user, if full names are being shown and they have
+    /// one, looking it up the first time each user is seen.
+    fn lookup_full_name(&mut self, uid: uid_t) -> Option<String> {
+        match self.full_names {
+            Some(ref mut names) => names.entry(uid).or_insert_with(|| full_name(uid)).clone(),
+            None                => None,
+        }
+    }
 
-        // Work out the list of column widths by finding the longest cell for
-        // each column, then formatting each cell in that column to be the
-        // width of that one.
-        let column_widths: Vec<usize> = (0 .. self.columns.len())
-            .map(|n| self.rows.iter().map(|row| row.column_width(n)).max().unwrap_or(0))
-            .collect();
+    /// Count the members of a group: those listed in its entry, plus those
+    /// whose primary group it is, without counting anyone twice. The
+    /// password file gets read the first time this is needed.
+    fn count_group_members(&mut self, gid: gid_t, listed: &[String]) -> usize {
+        if self.primary_members.is_none() {
+            self.primary_members = Some(primary_members());
+        }
 
-        let total_width: usize = self.columns.len() + column_widths.iter().sum::<usize>();
+        let primary = match self.primary_members.as_ref().and_then(|m| m.get(&gid)) {
+            Some(names) => names.iter().filter(|name| !listed.contains(name)).count(),
+            None        => 0,
+        };
 
-        for row in self.rows.iter() {
-            let mut cell = Cell::empty();
+        listed.len() + primary
+    }
 
-            if let Some(ref cells) = row.cells {
-                for (n, width) in column_widths.iter().enumerate() {
-                    match self.columns[n].alignment() {
-                        Alignment::Left  => { cell.append(&cells[n]); cell.add_spaces(width - cells[n].length); }
-                        Alignment::Right => { cell.add_spaces(width - cells[n].length); cell.append(&cells[n]); }
-                    }
+    /// Render whether the current user can read, write, and execute a file,
+    /// using the owner's bits if they own it, the group's bits if they're in
+    /// its group, and the others' bits otherwise. The superuser can read and
+    /// write anything, and execute anything that anyone can execute.
+    fn render_access(&mut self, permissions: f::Permissions, user: f::User, group: f::Group) -> Cell {
+        let current_uid = self.users.get_current_uid();
+
+        let in_group = match (self.users.get_user_by_uid(current_uid), self.users.get_group_by_gid(group.0)) {
+            (Some(current_user), Some(group))  => current_user.primary_group == group.gid || group.members.contains(&current_user.name),
+            (Some(current_user), None)         => current_user.primary_group == group.0,
+            _                                  => false,
+        };
 
-                    cell.add_spaces(1);
-                }
-            }
-            else {
-                cell.add_spaces(total_width)
-            }
+        let (read, write, execute) = if current_uid == 0 {
+            (true, true, permissions.user_execute || permissions.group_execute || permissions.other_execute)
+        }
+        else if current_uid == user.0 {
+            (permissions.user_read, permissions.user_write, permissions.user_execute)
+        }
+        else if in_group {
+            (permissions.group_read, permissions.group_write, permissions.group_execute)
+        }
+        else {
+            (permissions.other_read, permissions.other_write, permissions.other_execute)
+        };
 
-            let mut filename = String::new();
-            let mut filename_length = 0;
+        let c = self.colours.perms;
+        let bit = |bit, chr: &'static str, style: Style| {
+            if bit { style.paint(chr) } else { self.colours.punctuation.paint("-") }
+        };
 
-            // A stack tracks which tree characters should be printed. It's
-            // necessary to maintain information about the previously-printed
-            // lines, as the output will change based on whether the
-            // *previous* entry was the last in its directory.
-            stack.resize(row.depth + 1, TreePart::Edge);
-            stack[row.depth] = if row.last { TreePart::Corner } else { TreePart::Edge };
+        Cell {
+            text: ANSIStrings(&[ bit(read,    "r", c.user_read),
+                                 bit(write,   "w", c.user_write),
+                                 bit(execute, "x", c.user_execute_other) ]).to_string(),
+            length: 3,
+        }
+    }
 
-            for i in 1 .. row.depth + 1 {
-                filename.push_str(&*self.colours.punctuation.paint(stack[i].ascii_art()).to_string());
-                filename_length += 4;
+    /// Render the table as lines of HTML. Normally this is a `<table>` with
+    /// one row per file, but in the tree view, where rows are nested inside
+    /// one another, it's a nested `<ul>` instead, with each file's cells
+    /// before its name in the list item.
+    pub fn print_html(&self, tree: bool) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        let cells_html = |row: &Row, tag: &str| -> String {
+            match row.cells {
+                Some(ref cells) => cells.iter().map(|c| format!("<{0}>{1}</{0}>", tag, html::ansi_to_html(&c.text))).collect(),
+                None            => format!("<{0} colspan=\"{1}\"></{0}>", tag, self.columns.len()),
             }
+        };
 
-            stack[row.depth] = if row.last { TreePart::Blank } else { TreePart::Line };
+        if !tree {
+            lines.push("<table>".to_string());
+            for row in self.rows.iter() {
+                lines.push(format!("<tr>{}<td>{}</td></tr>", cells_html(row, "td"), html::ansi_to_html(&row.name.text)));
+            }
+            lines.push("</table>".to_string());
+            return lines;
+        }
 
-            // If any tree characters have been printed, then add an extra
-            // space, which makes the output look much better.
-            if row.depth != 0 {
-                filename.push(' ');
-                filename_length += 1;
+        // A list gets opened whenever the tree goes a level deeper, and
+        // closed, along with the item it's inside, whenever it comes back
+        // up. Each level is at most one deeper than the last.
+        let mut previous_depth = None;
+
+        for row in self.rows.iter() {
+            match previous_depth {
+                None                         => lines.push("<ul>".to_string()),
+                Some(d) if row.depth > d     => lines.push("<ul>".to_string()),
+                Some(d) if row.depth == d    => lines.push("</li>".to_string()),
+                Some(d) => {
+                    for _ in row.depth .. d {
+                        lines.push("</li></ul>".to_string());
+                    }
+                    lines.push("</li>".to_string());
+                },
             }
 
-            // Print the name without worrying about padding.
-            filename.push_str(&*row.name.text);
-            filename_length += row.name.length;
+            let mut item = String::from("<li>");
+            if row.cells.is_some() {
+                item.push_str(&cells_html(row, "span"));
+                item.push(' ');
+            }
+            item.push_str(&html::ansi_to_html(&row.name.text));
+            lines.push(item);
 
-            cell.append(&Cell { text: filename, length: filename_length });
-            cells.push(cell);
+            previous_depth = Some(row.depth);
         }
 
-        cells
+        if let Some(d) = previous_depth {
+            for _ in 0 .. d + 1 {
+                lines.push("</li></ul>".to_string());
+            }
+        }
+
+        lines
     }
-}
 
+    /// Render the table with lines around it and between its columns, with
+    /// the file names as the last column. The columns don't get truncated,
+    /// and there's no tree to draw.
+    fn print_bordered_table(&self) -> Vec<Cell> {
+        let mut widths = self.column_widths();
+        widths.push(self.rows.iter().map(|row| row.name.length).max().unwrap_or(0));
+
+        let largest_size = self.rows.iter().filter_map(|row| row.size).max().unwrap_or(0);
+        let total_size = self.rows.iter().filter_map(|row| row.size).sum::<u64>();
+
+        let rule = |left: &str, middle: &str, right: &str| {
+            let mut text = left.to_string();
+            for (n, width) in widths.iter().enumerate() {
+                if n > 0 { text.push_str(middle) }
+                text.extend(repeat('─').take(width + 2));
+            }
+            text.push_str(right);
 
-#[derive(PartialEq, Debug, Clone)]
-enum TreePart {
+            Cell { length: UnicodeWidthStr::width(&text[..]), text: self.colours.punctuation.paint(&text).to_string() }
+        };
 
-    /// Rightmost column, *not* the last in the directory.
-    Edge,
+        let bar = Cell::paint(self.colours.punctuation, "│");
+        let mut cells = vec![ rule("┌", "┬", "┐") ];
 
-    /// Not the rightmost column, and the directory has not finished yet.
-    Line,
+        for (index, row) in self.rows.iter().enumerate() {
+            let mut cell = bar.clone();
 
-    /// Rightmost column, and the last in the directory.
-    Corner,
+            for (n, width) in widths.iter().enumerate() {
+                let (contents, alignment) = if n == self.columns.len() {
+                    (row.name.clone(), Alignment::Left)
+                }
+                else {
+                    let mut contents = match (self.columns[n], row.cells.as_ref()) {
+                        (_, None)                     => Cell::empty(),
+                        (Column::SizeBar, _)          => self.render_size_bar(row.size, largest_size),
+                        (Column::SizePercent, _)      => self.render_size_percent(row.size, total_size),
+                        (_, Some(row_cells))          => row_cells[n].clone(),
+                    };
 
-    /// Not the rightmost column, and the directory *has* finished.
-    Blank,
-}
+                    // A column can be narrower than its widest cell if its
+                    // width has been limited.
+                    contents.truncate(*width);
 
-impl TreePart {
-    fn ascii_art(&self) -> &'static str {
-        match *self {
-            TreePart::Edge    => "├──",
-            TreePart::Line    => "│  ",
-            TreePart::Corner  => "└──",
-            TreePart::Blank   => "   ",
+                    (contents, self.columns[n].alignment())
+                };
+
+                cell.add_spaces(1);
+                match alignment {
+                    Alignment::Left  => { cell.append(&contents); cell.add_spaces(width - contents.length); }
+                    Alignment::Right => { cell.add_spaces(width - contents.length); cell.append(&contents); }
+                }
+                cell.add_spaces(1);
+                cell.append(&bar);
+            }
+
+            cells.push(cell);
+
+            if index == 0 && self.has_header {
+                cells.push(rule("├", "┼", "┤"));
+            }
         }
+
+        cells.push(rule("└", "┴", "┘"));
+        cells
     }
-}
 
+    /// Render just the names of the files' rows, one to a line, with no
+    /// padding in front of them.
+    fn print_flat_tree(&self) -> Vec<Cell> {
+        let skip = if self.has_header { 1 } else { 0 };
 
-#[cfg(test)]
-pub mod test {
-    pub use super::Table;
-    pub use file::File;
-    pub use file::fields as f;
+        let names = self.rows.iter().skip(skip)
+                        .filter(|row| row.cells.is_some())
+                        .map(|row| row.name.clone())
+                        .collect();
 
-    pub use column::{Cell, Column};
+        if self.line_numbers { number_lines(names, 0, self.colours.punctuation) }
+                        else { names }
+    }
 
-    pub use users::{User, Group, uid_t, gid_t};
-    pub use users::mock::MockUsers;
+    /// Render the table as a vector of Cells, to be displayed on standard output.
+    pub fn print_table(&self) -> Vec<Cell> {
+        if self.flat_tree {
+            return self.print_flat_tree();
+        }
 
-    pub use ansi_term::Style;
-    pub use ansi_term::Colour::*;
+        if self.borders {
+            return self.print_bordered_table();
+        }
+
+        let mut stack = Vec::new();
+        let mut cells = Vec::new();
+        let mut header_lines = 0;
+
+        // If any row has a marker, every line gets a space for one, so the
+        // rest of the lines stay lined up.
+        let has_markers = self.rows.iter().any(|row| row.marker.is_some());
+
+        // Find the width of each column, then format each cell in that
+        // column to be that wide.
+        let mut column_widths = self.column_widths();
+
+        // In compact mode, whole columns get left out, least important
+        // first, until the widest row fits. Their widths become zero, and
+        // they aren't followed by a space.
+        let mut shown = vec![ true; self.columns.len() ];
+        if let Some(max_width) = self.compact_width {
+            let widest_name = self.rows.iter().map(|row| row.name_width(self.tree_indent)).max().unwrap_or(0);
+
+            for column in self.truncation_order.iter() {
+                let total_width: usize = column_widths.iter().zip(shown.iter()).filter(|&(_, s)| *s).map(|(w, _)| w + 1).sum();
+                if total_width + widest_name <= max_width { break }
+
+                if let Column::Permissions(_) = *column { continue }
+
+                if let Some(n) = self.columns.iter().position(|c| c == column) {
+                    shown[n] = false;
+                    column_widths[n] = 0;
+                }
+            }
+        }
+
+        let shown_count = shown.iter().filter(|s| **s).count();
+
+        // If the widest row doesn't fit, take the excess away from the
+        // columns in truncation order, until either it fits or there are no
+        // columns left to take from.
+        if let Some(max_width) = self.max_width {
+            let widest_name = self.rows.iter().map(|row| row.name_width(self.tree_indent)).max().unwrap_or(0);
+            let total_width: usize = shown_count + column_widths.iter().sum::<usize>();
+            let mut overflow = if self.two_lines { (TWO_LINE_INDENT + total_width).saturating_sub(max_width) }
+                                            else { (total_width + widest_name).saturating_sub(max_width) };
+
+            for column in self.truncation_order.iter() {
+                if overflow == 0 { break }
+
+                if let Some(n) = self.columns.iter().position(|c| c == column) {
+                    let cut = cmp::min(overflow, column_widths[n]);
+                    column_widths[n] -= cut;
+                    overflow -= cut;
+                }
+            }
+        }
+
+        let total_width: usize = shown_count + column_widths.iter().sum::<usize>();
+
+        // The size bars and percentages depend on every row's size, so
+        // they only get drawn now that all the rows are known.
+        let largest_size = self.rows.iter().filter_map(|row| row.size).max().unwrap_or(0);
+        let total_size = self.rows.iter().filter_map(|row| row.size).sum::<u64>();
+
+        // The previous row's depth and cells, for the ditto marks. Rows
+        // without cells, such as errors, start the comparison afresh.
+        let mut previous: Option<(usize, &Vec<Cell>)> = None;
+
+        // With the tree mirrored, the names get lined up on the right, so
+        // each level of the tree stays in the same column on every row.
+        let name_offset = if self.two_lines { 0 } else { total_width };
+        let mirrored_width = if self.mirror_tree {
+            let widest_name = self.rows.iter().map(|row| row.name_width(self.tree_indent)).max().unwrap_or(0);
+            match self.max_width {
+                Some(max_width)  => cmp::min(widest_name, max_width.saturating_sub(name_offset)),
+                None             => widest_name,
+            }
+        }
+        else {
+            0
+        };
+
+        for (index, row) in self.rows.iter().enumerate() {
+            let mut cell = Cell::empty();
+
+            if let Some(ref cells) = row.cells {
+                let above = match previous {
+                    Some((depth, above)) if self.ditto_owners && depth == row.depth => Some(above),
+                    _ => None,
+                };
+
+                for (n, width) in column_widths.iter().enumerate() {
+                    if !shown[n] { continue }
+
+                    let mut contents = match self.columns[n] {
+                        Column::SizeBar      => self.render_size_bar(row.size, largest_size),
+                        Column::SizePercent  => self.render_size_percent(row.size, total_size),
+                        Column::User | Column::Group if above.map_or(false, |a| a[n] == cells[n]) => Cell::paint(self.metadata_colours().punctuation, "\""),
+                        _                    => cells[n].clone(),
+                    };
+                    contents.truncate(*width);
+
+                    match self.columns[n].alignment() {
+                        Alignment::Left  => { cell.append(&contents); cell.add_spaces(width - contents.length); }
+                        Alignment::Right => { cell.add_spaces(width - contents.length); cell.append(&contents); }
+                    }
+
+                    cell.add_spaces(1);
+                }
+            }
+            else if !self.two_lines {
+                cell.add_spaces(total_width)
+            }
+
+            previous = row.cells.as_ref().map(|cells| (row.depth, cells));
+
+            let mut art = String::new();
+            let mut tree_length = 0;
+
+            // A stack tracks which tree characters should be printed. It's
+            // necessary to maintain information about the previously-printed
+            // lines, as the output will change based on whether the
+            // *previous* entry was the last in its directory.
+            stack.resize(row.depth + 1, TreePart::Edge);
+            stack[row.depth] = if row.last { TreePart::Corner } else { TreePart::Edge };
+
+            // Mirrored, the deepest level goes next to the name, and the
+            // outermost one furthest from it, on the right.
+            if self.mirror_tree {
+                for i in (1 .. row.depth + 1).rev() {
+                    art.push_str(&*self.colours.tree.paint(&stack[i].mirrored_ascii_art(self.tree_style, self.tree_indent)).to_string());
+                    tree_length += self.tree_indent;
+                }
+            }
+            else {
+                for i in 1 .. row.depth + 1 {
+                    art.push_str(&*self.colours.tree.paint(&stack[i].ascii_art(self.tree_style, self.tree_indent)).to_string());
+                    tree_length += self.tree_indent;
+                }
+            }
+
+            stack[row.depth] = if row.last { TreePart::Blank } else { TreePart::Line };
+
+            // If any tree characters have been printed, then add an extra
+            // space between them and the name, which makes the output look
+            // much better.
+            let separator = if row.depth != 0 { " " } else { "" };
+            tree_length += separator.len();
+
+            // Print the name without worrying about padding, unless it has
+            // to be elided to fit into the maximum width.
+            let mut name = row.name.clone();
+            if let Some(max_width) = self.max_width {
+                name.truncate(max_width.saturating_sub(name_offset + tree_length));
+            }
+
+            let mut filename = String::new();
+            let filename_length = if self.mirror_tree {
+                let padding = mirrored_width.saturating_sub(name.length + tree_length);
+                filename.extend(repeat(' ').take(padding));
+                filename.push_str(&*name.text);
+                filename.push_str(separator);
+                filename.push_str(&art);
+                padding + name.length + tree_length
+            }
+            else {
+                filename.push_str(&art);
+                filename.push_str(separator);
+                filename.push_str(&*name.text);
+                tree_length + name.length
+            };
+
+            let mut filename = Cell { text: filename, length: filename_length };
+
+            // The padding only counts towards the width of the line it's on,
+            // which in two-line mode is just the name's.
+            if let (true, Some(width)) = (row.highlighted, self.pad_width) {
+                let line_width = if self.two_lines { filename.length } else { cell.length + filename.length };
+                if line_width < width {
+                    let padding: String = repeat(' ').take(width - line_width).collect();
+                    filename.append(&Cell::paint(self.colours.highlight, &padding));
+                }
+            }
+
+            // In two-line mode, the name goes on its own line, with the
+            // metadata indented underneath it. Rows without any metadata
+            // still only take up the one line.
+            let first_line = cells.len();
+
+            if self.two_lines {
+                cells.push(filename);
+
+                if row.cells.is_some() {
+                    let mut details = Cell::empty();
+                    details.add_spaces(TWO_LINE_INDENT);
+                    details.append(&cell);
+                    cells.push(details);
+                }
+            }
+            else {
+                cell.append(&filename);
+                cells.push(cell);
+            }
+
+            if has_markers {
+                for (n, line) in cells[first_line ..].iter_mut().enumerate() {
+                    let mut marked = match row.marker {
+                        Some(ref marker) if n == 0  => marker.clone(),
+                        _                           => Cell::blank(1),
+                    };
+
+                    marked.add_spaces(1);
+                    marked.append(line);
+                    *line = marked;
+                }
+            }
+
+            if index == 0 && self.has_header {
+                header_lines = cells.len();
+            }
+        }
+
+        if self.line_numbers {
+            cells = number_lines(cells, header_lines, self.colours.punctuation);
+        }
+
+        cells
+    }
+}
+
+
+/// Put each line's number in front of it, right-aligned, leaving the
+/// header's lines blank. The header's lines still get counted, so the
+/// numbers match the lines of the output, however many there are.
+fn number_lines(lines: Vec<Cell>, header_lines: usize, style: Style) -> Vec<Cell> {
+    let width = lines.len().to_string().len();
+
+    lines.into_iter().enumerate().map(|(index, line)| {
+        let mut numbered = if index < header_lines {
+            Cell::blank(width)
+        }
+        else {
+            let number = (index + 1).to_string();
+            let mut cell = Cell::blank(width - number.len());
+            cell.append(&Cell::paint(style, &number));
+            cell
+        };
+
+        numbered.add_spaces(1);
+        numbered.append(&line);
+        numbered
+    }).collect()
+}
+
+
+/// The number of blocks used by the given files, not counting the contents
+/// of any directories. Files without a block count add nothing.
+fn total_blocks(files: &[File]) -> u64 {
+    files.iter().map(|file| match file.blocks() {
+        f::Blocks::Some(blocks)  => blocks as u64,
+        f::Blocks::None          => 0,
+    }).fold(0, |sum, blocks| sum + blocks)
+}
+
+/// The absolute path to display in the banner above a table. This is the
+/// directory being listed, if there is one; in the tree view, directories
+/// are passed in as files, so a lone directory is used instead.
+fn banner_path(dir: Option<&Dir>, files: &[File]) -> Option<PathBuf> {
+    match dir {
+        Some(d) => match current_dir() {
+            Ok(cwd) => Some(cwd.join(&d.path)),
+            Err(_)  => Some(d.path.clone()),
+        },
+        None if files.len() == 1 && files[0].is_directory() => Some(files[0].absolute_path()),
+        None => None,
+    }
+}
+
+
+/// Use the given time zone, or fall back to UTC if it couldn't be loaded,
+/// which happens on systems without `/etc/localtime`. A warning gets printed
+/// the first time this happens, rather than once per table.
+fn zone_or_utc<E: fmt::Display>(result: Result<TimeZone, E>) -> Option<TimeZone> {
+    static WARNING: Once = ONCE_INIT;
+
+    match result {
+        Ok(tz)  => Some(tz),
+        Err(e)  => {
+            WARNING.call_once(|| {
+                let _ = writeln!(io::stderr(), "exa: couldn't determine the local time zone, using UTC ({})", e);
+            });
+
+            None
+        },
+    }
+}
+
+
+/// Render a file's permission bits as a four-digit octal number, such as
+/// `0755`.
+fn octal_permissions(permissions: &f::Permissions) -> String {
+    let digit = |read: bool, write: bool, execute: bool| {
+        (if read { 4 } else { 0 }) + (if write { 2 } else { 0 }) + (if execute { 1 } else { 0 })
+    };
+
+    format!("0{}{}{}",
+            digit(permissions.user_read,  permissions.user_write,  permissions.user_execute),
+            digit(permissions.group_read, permissions.group_write, permissions.group_execute),
+            digit(permissions.other_read, permissions.other_write, permissions.other_execute))
+}
+
+
+/// The width of the widest name out of a file's extended attributes.
+fn xattr_name_width(xattrs: &[Attribute]) -> usize {
+    xattrs.iter().map(|x| UnicodeWidthStr::width(&x.name[..])).max().unwrap_or(0)
+}
+
+
+/// How far the metadata line is indented under the name line in two-line
+/// mode.
+const TWO_LINE_INDENT: usize = 4;
+
+/// How many columns wide the size bar column is.
+const SIZE_BAR_WIDTH: usize = 10;
+
+/// How many columns wide the size percentage column is, which is enough
+/// for `100.0%`.
+const SIZE_PERCENT_WIDTH: usize = 6;
+
+/// How many hexadecimal digits of each checksum get shown.
+const CHECKSUM_WIDTH: usize = 8;
+
+/// The size of a file to use when drawing the size bar. Directories don't
+/// get a bar.
+fn bar_size(size: f::Size) -> Option<u64> {
+    match size {
+        f::Size::Some(size)  => Some(size),
+        f::Size::None        => None,
+    }
+}
+
+/// A file's name as a cell, followed by a slash if `slash` is set and it's
+/// a directory, then by an arrow to the given target, if there is one.
+/// The slash goes straight after the name, as it's the file itself that
+/// it's describing, not the target.
+fn name_cell(file: &File, colours: &Colours, colouring: NameColouring, target: Option<Result<File, String>>, slash: bool, quote: bool) -> Cell {
+    let mut name = Cell::paint(file_colour(colours, colouring, file), &display_name(&file.name, quote));
+
+    if slash {
+        if let Some(slash) = dir_slash(file, colours) {
+            name.append(&slash);
+        }
+    }
+
+    // The arrow doesn't count towards the width, as names with arrows
+    // always come last in their rows.
+    if let Some(target) = target {
+        name.text.push_str(&link_arrow(colours, colouring, target, quote));
+    }
+
+    name
+}
+
+/// The slash to put after the name of a directory, or of a symlink to
+/// one, or `None` if the file isn't either of those.
+fn dir_slash(file: &File, colours: &Colours) -> Option<Cell> {
+    let is_dir = file.is_directory()
+              || (file.is_link() && file.link_target().map(|t| t.is_directory()).unwrap_or(false));
+
+    if is_dir { Some(Cell::paint(colours.punctuation, "/")) }
+         else { None }
+}
+
+/// Format a date in full, from the year down to the nanosecond, so files
+/// changed within the same second can still be told apart.
+fn format_precise_date<D: DatePiece + TimePiece>(date: &D, nanoseconds: i64) -> String {
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:09}",
+            date.year(), date.month().months_from_january() + 1, date.day(),
+            date.hour(), date.minute(), date.second(), nanoseconds)
+}
+
+/// Format a date using a `strftime`-style format string, as given to
+/// `--time-style=+FORMAT`. Only the common fields are supported; anything
+/// else after a `%` is left as it is.
+fn format_custom_date<D: DatePiece + TimePiece>(format: &str, date: &D, timestamp: f::Time, time: &locale::Time) -> String {
+    let mut text = String::new();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('Y')    => text.push_str(&date.year().to_string()),
+            Some('y')    => text.push_str(&format!("{:02}", date.year() % 100)),
+            Some('m')    => text.push_str(&format!("{:02}", date.month().months_from_january() + 1)),
+            Some('b')    => text.push_str(&DateFormat::parse("{:M}").unwrap().format(date, time)),
+            Some('d')    => text.push_str(&format!("{:02}", date.day())),
+            Some('e')    => text.push_str(&format!("{:2}", date.day())),
+            Some('H')    => text.push_str(&format!("{:02}", date.hour())),
+            Some('M')    => text.push_str(&format!("{:02}", date.minute())),
+            Some('S')    => text.push_str(&format!("{:02}", date.second())),
+            Some('N')    => text.push_str(&format!("{:09}", timestamp.1)),
+            Some('z')    => text.push_str(&format_offset(utc_offset(date, timestamp.0))),
+            Some('F')    => text.push_str(&format_custom_date("%Y-%m-%d", date, timestamp, time)),
+            Some('T')    => text.push_str(&format_custom_date("%H:%M:%S", date, timestamp, time)),
+            Some('R')    => text.push_str(&format_custom_date("%H:%M", date, timestamp, time)),
+            Some('%')    => text.push('%'),
+            Some(other)  => { text.push('%'); text.push(other); },
+            None         => text.push('%'),
+        }
+    }
+
+    text
+}
+
+/// How far ahead of UTC a date's time zone is, in seconds, found by
+/// comparing the date's fields with the timestamp it was made from. The
+/// number of days since the epoch gets worked out from the date using
+/// Howard Hinnant's `days_from_civil` algorithm.
+fn utc_offset<D: DatePiece + TimePiece>(date: &D, timestamp: i64) -> i64 {
+    let month = date.month().months_from_january() as i64 + 1;
+    let year = if month <= 2 { date.year() - 1 } else { date.year() };
+
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + date.day() as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let seconds = days * 86400 + date.hour() as i64 * 3600 + date.minute() as i64 * 60 + date.second() as i64;
+    seconds - timestamp
+}
+
+/// Format an offset from UTC as a sign, then hours and minutes, such as
+/// `+0100`.
+fn format_offset(offset: i64) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let minutes = offset.abs() / 60;
+    format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60)
+}
+
+/// The key to use for a column's value in a line of JSON. Timestamp
+/// columns share a name, so they get told apart by which time they show.
+fn json_key(column: &Column) -> String {
+    match *column {
+        Column::Timestamp(t)  => t.header().to_lowercase().replace(" ", "-"),
+        _                     => column.name().to_string(),
+    }
+}
+
+/// How many columns each level of the tree takes up by default.
+pub const TREE_INDENT: usize = 3;
+
+/// How many files a directory needs to have before it's worth examining
+/// them in parallel.
+pub const SEQUENTIAL_LIMIT: usize = 16;
+
+
+fn render_size_with_unit(number: String, symbol: &str, numbers: Style, unit: Style) -> Cell {
+    Cell {
+        text: ANSIStrings( &[ numbers.paint(&number[..]), unit.paint(symbol) ]).to_string(),
+        length: number.len() + symbol.len(),
+    }
+}
+
+
+#[derive(PartialEq, Debug, Clone)]
+enum TreePart {
+
+    /// Rightmost column, *not* the last in the directory.
+    Edge,
+
+    /// Not the rightmost column, and the directory has not finished yet.
+    Line,
+
+    /// Rightmost column, and the last in the directory.
+    Corner,
+
+    /// Not the rightmost column, and the directory *has* finished.
+    Blank,
+}
+
+impl TreePart {
+
+    /// The characters to draw for this part of the tree in the given
+    /// style, padded out to the given width: `├──` at the default width,
+    /// or `├─` at a width of two. Every style's characters are one column
+    /// wide, so the width is the same whichever is used.
+    fn ascii_art(&self, style: TreeStyle, width: usize) -> String {
+        let (edge, line, corner, horizontal) = match style {
+            TreeStyle::Light    => ('├', '│', '└', '─'),
+            TreeStyle::Heavy    => ('┣', '┃', '┗', '━'),
+            TreeStyle::Double   => ('╠', '║', '╚', '═'),
+            TreeStyle::Rounded  => ('├', '│', '╰', '─'),
+            TreeStyle::Ascii    => ('|', '|', '`', '-'),
+        };
+
+        let (first, rest) = match *self {
+            TreePart::Edge    => (edge,   horizontal),
+            TreePart::Line    => (line,   ' '),
+            TreePart::Corner  => (corner, horizontal),
+            TreePart::Blank   => (' ',    ' '),
+        };
+
+        let mut art = String::new();
+        if width > 0 {
+            art.push(first);
+            for _ in 1 .. width {
+                art.push(rest);
+            }
+        }
+
+        art
+    }
+
+    /// The characters to draw for this part of the tree when it's mirrored
+    /// onto the right of the names, for right-to-left languages: `──┤`
+    /// rather than `├──`.
+    fn mirrored_ascii_art(&self, style: TreeStyle, width: usize) -> String {
+        let (edge, corner) = match style {
+            TreeStyle::Light    => ('┤', '┘'),
+            TreeStyle::Heavy    => ('┫', '┛'),
+            TreeStyle::Double   => ('╣', '╝'),
+            TreeStyle::Rounded  => ('┤', '╯'),
+            TreeStyle::Ascii    => ('|', '\''),
+        };
+
+        let mut chars: Vec<char> = self.ascii_art(style, width).chars().collect();
+        if let Some(first) = chars.first_mut() {
+            match *self {
+                TreePart::Edge    => *first = edge,
+                TreePart::Corner  => *first = corner,
+                _                 => {},
+            }
+        }
+
+        chars.into_iter().rev().collect()
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    pub use super::Table;
+    pub use file::File;
+    pub use file::fields as f;
+
+    pub use column::{Cell, Column};
+
+    pub use users::{User, Group, uid_t, gid_t};
+    pub use users::mock::MockUsers;
+
+    pub use ansi_term::Style;
+    pub use ansi_term::Colour::*;
+
+    pub use test_support::TempDir;
 
     pub fn newser(uid: uid_t, name: &str, group: gid_t) -> User {
         User {
@@ -763,6 +3442,25 @@ pub mod test {
         }
     }
 
+    impl super::Row {
+
+        /// A row for a file with the given name at the given depth, with a
+        /// plain cell for each of the given strings. Tests that need any of
+        /// the row's other fields set can change them afterwards.
+        fn test(name: &str, depth: usize, cells: &[&str]) -> super::Row {
+            super::Row {
+                depth:  depth,
+                cells:  Some(cells.iter().map(|c| Cell::paint(Style::default(), c)).collect()),
+                name:   Cell::paint(Style::default(), name),
+                last:   false,
+                is_dir: false,
+                size:   None,
+                highlighted: false,
+                marker: None,
+            }
+        }
+    }
+
     // These tests create a new, default Table object, then fill in the
     // expected style in a certain way. This means we can check that the
     // right style is being used, as otherwise, it would just be plain.
@@ -773,6 +3471,7 @@ pub mod test {
     mod users {
         #![allow(unused_results)]
         use super::*;
+        use std::collections::HashMap;
 
         #[test]
         fn named() {
@@ -822,6 +3521,36 @@ pub mod test {
             assert_eq!(expected, table.render_user(user));
         }
 
+        #[test]
+        fn full_name() {
+            let mut table = Table::default();
+            table.colours.users.user_someone_else = Green.bold();
+            table.users.add_user(newser(1000, "enoch", 100));
+
+            let mut names = HashMap::new();
+            names.insert(1000, Some("Enoch Root".to_string()));
+            table.full_names = Some(names);
+
+            let user = f::User(1000);
+            let expected = Cell::paint(Green.bold(), "Enoch Root");
+            assert_eq!(expected, table.render_user(user));
+        }
+
+        #[test]
+        fn no_full_name() {
+            let mut table = Table::default();
+            table.colours.users.user_someone_else = Green.bold();
+            table.users.add_user(newser(1000, "enoch", 100));
+
+            let mut names = HashMap::new();
+            names.insert(1000, None);
+            table.full_names = Some(names);
+
+            let user = f::User(1000);
+            let expected = Cell::paint(Green.bold(), "enoch");
+            assert_eq!(expected, table.render_user(user));
+        }
+
         #[test]
         fn overflow() {
             let mut table = Table::default();
@@ -836,6 +3565,7 @@ pub mod test {
     mod groups {
         #![allow(unused_results)]
         use super::*;
+        use std::collections::HashMap;
 
         #[test]
         fn named() {
@@ -865,37 +3595,78 @@ pub mod test {
         }
 
         #[test]
-        fn primary() {
+        fn prefixed() {
             let mut table = Table::default();
-            table.colours.users.group_yours = Fixed(64).normal();
+            table.set_group_prefix("@".to_string());
 
-            let mut users = MockUsers::with_current_uid(2);
-            users.add_user(newser(2, "eve", 100));
+            let mut users = MockUsers::with_current_uid(1000);
             users.add_group(Group { gid: 100, name: "folk".to_string(), members: vec![] });
             table.users = users;
 
-            let group = f::Group(100);
-            let expected = Cell::paint(Fixed(64).normal(), "folk");
-            assert_eq!(expected, table.render_group(group))
+            let cell = table.render_group(f::Group(100));
+            assert_eq!(cell.text, "@folk");
+            assert_eq!(cell.length, 5);
         }
 
         #[test]
-        fn secondary() {
+        fn member_count() {
             let mut table = Table::default();
-            table.colours.users.group_yours = Fixed(31).normal();
+            table.show_group_members();
 
-            let mut users = MockUsers::with_current_uid(2);
-            users.add_user(newser(2, "eve", 666));
-            users.add_group(Group { gid: 100, name: "folk".to_string(), members: vec![ "eve".to_string() ] });
+            let mut primary = HashMap::new();
+            primary.insert(100, vec![ "ann".to_string(), "bob".to_string() ]);
+            table.primary_members = Some(primary);
+
+            let mut users = MockUsers::with_current_uid(1000);
+            users.add_group(Group { gid: 100, name: "folk".to_string(), members: vec![ "bob".to_string(), "cat".to_string() ] });
             table.users = users;
 
-            let group = f::Group(100);
-            let expected = Cell::paint(Fixed(31).normal(), "folk");
-            assert_eq!(expected, table.render_group(group))
+            let cell = table.render_group(f::Group(100));
+            assert_eq!(cell.text, "folk (3)");
+            assert_eq!(cell.length, 8);
         }
 
         #[test]
-        fn overflow() {
+        fn member_count_unnamed() {
+            let mut table = Table::default();
+            table.show_group_members();
+            table.users = MockUsers::with_current_uid(1000);
+
+            assert_eq!(table.render_group(f::Group(100)).text, "100");
+        }
+
+        #[test]
+        fn primary() {
+            let mut table = Table::default();
+            table.colours.users.group_yours = Fixed(64).normal();
+
+            let mut users = MockUsers::with_current_uid(2);
+            users.add_user(newser(2, "eve", 100));
+            users.add_group(Group { gid: 100, name: "folk".to_string(), members: vec![] });
+            table.users = users;
+
+            let group = f::Group(100);
+            let expected = Cell::paint(Fixed(64).normal(), "folk");
+            assert_eq!(expected, table.render_group(group))
+        }
+
+        #[test]
+        fn secondary() {
+            let mut table = Table::default();
+            table.colours.users.group_yours = Fixed(31).normal();
+
+            let mut users = MockUsers::with_current_uid(2);
+            users.add_user(newser(2, "eve", 666));
+            users.add_group(Group { gid: 100, name: "folk".to_string(), members: vec![ "eve".to_string() ] });
+            table.users = users;
+
+            let group = f::Group(100);
+            let expected = Cell::paint(Fixed(31).normal(), "folk");
+            assert_eq!(expected, table.render_group(group))
+        }
+
+        #[test]
+        fn overflow() {
             let mut table = Table::default();
             table.colours.users.group_not_yours = Blue.underline();
 
@@ -904,4 +3675,2003 @@ pub mod test {
             assert_eq!(expected, table.render_group(group));
         }
     }
+
+    mod git_summary {
+        #![allow(unused_results)]
+        use super::*;
+
+        fn summary(staged: f::GitStatus, unstaged: f::GitStatus, ignored: bool) -> String {
+            let table = Table::default();
+            table.render_git_summary(f::Git { staged: staged, unstaged: unstaged }, f::GitIgnored(ignored)).text
+        }
+
+        #[test]
+        fn states() {
+            use file::fields::GitStatus::*;
+
+            assert_eq!(summary(NotModified, NotModified, false), "✓");
+            assert_eq!(summary(New, NotModified, false), "+");
+            assert_eq!(summary(Modified, NotModified, false), "+");
+            assert_eq!(summary(Modified, Modified, false), "●");
+            assert_eq!(summary(NotModified, Deleted, false), "●");
+            assert_eq!(summary(NotModified, New, false), "?");
+            assert_eq!(summary(NotModified, NotModified, true), "✗");
+        }
+    }
+
+    mod git_ignored {
+        #![allow(unused_results)]
+        use super::*;
+
+        #[test]
+        fn ignored() {
+            let mut table = Table::default();
+            table.colours.git.ignored = Fixed(244).normal();
+
+            let expected = Cell::paint(Fixed(244).normal(), "!");
+            assert_eq!(expected, table.render_git_ignored(f::GitIgnored(true)));
+        }
+
+        #[test]
+        fn not_ignored() {
+            let table = Table::default();
+            assert_eq!(Cell::empty(), table.render_git_ignored(f::GitIgnored(false)));
+        }
+    }
+
+    mod sizes {
+        #![allow(unused_results)]
+        use super::*;
+        use ansi_term::ANSIStrings;
+        use options::{SizeFormat, SizeOptions, UnitCase};
+
+        #[test]
+        fn bytes_with_suffix() {
+            let mut table = Table::default();
+            table.colours.size.numbers = Green.bold();
+            table.colours.size.unit = Green.normal();
+
+            let size = SizeOptions { format: SizeFormat::JustBytes, byte_suffix: true, ..SizeOptions::default() };
+            let expected = Cell {
+                text: ANSIStrings(&[ Green.bold().paint("547"), Green.normal().paint(" B") ]).to_string(),
+                length: 5,
+            };
+
+            assert_eq!(expected, table.render_size(f::Size::Some(547), f::Size::None, size));
+        }
+
+        #[test]
+        fn directory_without_suffix() {
+            let mut table = Table::default();
+            table.colours.punctuation = Fixed(244).normal();
+
+            let size = SizeOptions { format: SizeFormat::JustBytes, byte_suffix: true, ..SizeOptions::default() };
+            assert_eq!(Cell::paint(Fixed(244).normal(), "-"), table.render_size(f::Size::None, f::Size::None, size));
+        }
+
+        #[test]
+        fn spaced_upper_case_unit() {
+            let table = Table::default();
+
+            let size = SizeOptions { unit_space: true, unit_case: UnitCase::Upper, ..SizeOptions::default() };
+            let cell = table.render_size(f::Size::Some(9_600), f::Size::None, size);
+            assert_eq!(cell, Cell { text: "9.6 K".to_string(), length: 5 });
+        }
+
+        #[test]
+        fn both_prefixes() {
+            let mut table = Table::default();
+            table.colours.size.numbers = Green.bold();
+            table.colours.size.unit = Green.normal();
+            table.colours.size.binary = Cyan.normal();
+            table.colours.punctuation = Fixed(244).normal();
+
+            let size = SizeOptions { format: SizeFormat::BothPrefixes, ..SizeOptions::default() };
+            let expected = Cell {
+                text: format!("{}{}{}", ANSIStrings(&[ Green.bold().paint("1.0"), Green.normal().paint("G") ]),
+                                        Fixed(244).normal().paint("/"),
+                                        ANSIStrings(&[ Cyan.normal().paint("953"), Cyan.normal().paint("Mi") ])),
+                length: 10,
+            };
+
+            assert_eq!(expected, table.render_size(f::Size::Some(1_000_000_000), f::Size::None, size));
+        }
+
+        #[test]
+        fn both_prefixes_when_the_same() {
+            let table = Table::default();
+
+            let size = SizeOptions { format: SizeFormat::BothPrefixes, ..SizeOptions::default() };
+            assert_eq!(table.render_size(f::Size::Some(547), f::Size::None, size).text, "547");
+        }
+
+        #[test]
+        fn two_decimals() {
+            let table = Table::default();
+
+            let size = SizeOptions { decimals: Some(2), ..SizeOptions::default() };
+            assert_eq!(table.render_size(f::Size::Some(9_640), f::Size::None, size).text, "9.64k");
+            assert_eq!(table.render_size(f::Size::Some(10_000_000), f::Size::None, size).text, "10.00M");
+        }
+
+        #[test]
+        fn no_decimals() {
+            let table = Table::default();
+
+            let size = SizeOptions { decimals: Some(0), ..SizeOptions::default() };
+            assert_eq!(table.render_size(f::Size::Some(9_600), f::Size::None, size).text, "10k");
+        }
+
+        #[test]
+        fn aligned_decimals() {
+            let mut table = Table::default();
+            table.columns = vec![ Column::FileSize(SizeOptions::default()) ];
+
+            let mut cells: Vec<Vec<Cell>> = [ 9_600, 547, 12_000 ].iter().map(|&size| vec![ table.render_size(f::Size::Some(size), f::Size::None, SizeOptions::default()) ]).collect();
+            cells.push(vec![ Cell::paint(Style::default(), "-") ]);
+
+            table.align_size_decimals_in_cells(&mut cells);
+            let texts: Vec<String> = cells.iter().map(|row| row[0].text.clone()).collect();
+            assert_eq!(texts, vec![ "9.6k", "547   ", "12k  ", "-" ]);
+        }
+
+        #[test]
+        fn default_unit() {
+            let table = Table::default();
+
+            let cell = table.render_size(f::Size::Some(9_600), f::Size::None, SizeOptions::default());
+            assert_eq!(cell, Cell { text: "9.6k".to_string(), length: 4 });
+        }
+
+        #[test]
+        fn apparent_and_allocated() {
+            let mut table = Table::default();
+            table.colours.size.numbers = Green.bold();
+            table.colours.size.allocated = Yellow.normal();
+
+            let size = SizeOptions { format: SizeFormat::JustBytes, allocated: true, ..SizeOptions::default() };
+            let cell = table.render_size(f::Size::Some(1000), f::Size::Some(4096), size);
+            assert_eq!(cell.length, "1,000/4,096".len());
+        }
+    }
+
+    mod xattrs {
+        #![allow(unused_results)]
+        use super::*;
+        use super::super::xattr_name_width;
+        use feature::xattr::Attribute;
+
+        #[test]
+        fn names_line_up() {
+            let mut table = Table::default();
+            let xattrs = vec![
+                Attribute { name: "user.greeting".to_string(), size: 5 },
+                Attribute { name: "user.a".to_string(), size: 12 },
+            ];
+
+            let width = xattr_name_width(&xattrs);
+            for xattr in xattrs {
+                table.add_xattr(xattr, 1, false, width);
+            }
+
+            assert_eq!(table.rows[0].name.text, "user.greeting (len 5)");
+            assert_eq!(table.rows[1].name.text, "user.a        (len 12)");
+        }
+    }
+
+    mod descendants {
+        #![allow(unused_results)]
+        use super::*;
+        use std::fs;
+        use std::path::Path;
+        use options::RecurseOptions;
+        use output::details::Details;
+
+        /// List a fixed tree, going no further down than the given depth,
+        /// and return the table along with what adding the files returned.
+        fn tree(name: &str, max_depth: Option<usize>) -> (Table<MockUsers>, usize, bool) {
+            let temp = TempDir::new(name);
+            for dir in &[ "root/one", "root/two/three" ] {
+                fs::create_dir_all(temp.path.join(dir)).unwrap();
+            }
+            for file in &[ "root/a", "root/one/b", "root/one/c", "root/two/d", "root/two/three/e" ] {
+                fs::File::create(temp.path.join(file)).unwrap();
+            }
+
+            let mut table = Table::default();
+            table.columns = vec![ Column::Descendants ];
+
+            let details = Details {
+                recurse: Some(RecurseOptions { tree: true, max_depth: max_depth, max_children: None }),
+                ..Details::default()
+            };
+
+            let root = File::from_path(&temp.path.join("root"), None).unwrap();
+            let (count, too_deep) = details.add_files_to_table(&mut table, vec![ root ], 0, Path::new(""));
+            (table, count, too_deep)
+        }
+
+        fn descendants(table: &Table<MockUsers>, index: usize) -> String {
+            table.rows[index].cells.as_ref().unwrap()[0].text.clone()
+        }
+
+        #[test]
+        fn count_matches_rows() {
+            let (table, count, too_deep) = tree("descendants", None);
+
+            assert_eq!(count, 9);
+            assert_eq!(table.rows.len(), 9);
+            assert!(!too_deep);
+
+            assert_eq!(descendants(&table, 0), "8");  // root
+            assert_eq!(descendants(&table, 2), "2");  // one
+            assert_eq!(descendants(&table, 5), "3");  // two
+            assert_eq!(descendants(&table, 7), "1");  // two/three
+        }
+
+        #[test]
+        fn too_deep() {
+            let (table, count, too_deep) = tree("descendants-too-deep", Some(1));
+
+            assert_eq!(count, 4);
+            assert_eq!(table.rows.len(), 4);
+            assert!(too_deep);
+
+            assert_eq!(descendants(&table, 0), "3+");  // root
+            assert_eq!(descendants(&table, 2), "0+");  // one
+            assert_eq!(descendants(&table, 3), "0+");  // two
+        }
+    }
+
+    mod error_style {
+        #![allow(unused_results)]
+        use super::*;
+        use super::super::Row;
+        use colours::Colours;
+        use options::{ErrorStyle, RecurseOptions};
+        use output::details::Details;
+        use std::fs;
+        use std::io;
+        use std::os::unix::fs::PermissionsExt;
+        use std::path::Path;
+
+        fn denied() -> io::Error {
+            io::Error::new(io::ErrorKind::PermissionDenied, "Permission denied")
+        }
+
+        fn table_with_dir() -> Table<MockUsers> {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            table.columns = Vec::new();
+            table.rows.push(Row { last: true, is_dir: true, ..Row::test("secret", 0, &[]) });
+            table
+        }
+
+        #[test]
+        fn rows() {
+            let mut table = table_with_dir();
+            table.add_error(&denied(), 1, true, None);
+
+            assert_eq!(table.rows.len(), 2);
+            assert_eq!(table.rows[0].name.text, "secret");
+            assert_eq!(table.rows[1].name.text, "<Permission denied>");
+            assert_eq!(table.rows[1].depth, 1);
+        }
+
+        #[test]
+        fn inline() {
+            let mut table = table_with_dir();
+            let mut errors = vec![ (denied(), None) ];
+            table.add_inline_errors(0, &mut errors);
+
+            assert!(errors.is_empty());
+            assert_eq!(table.rows.len(), 1);
+            assert_eq!(table.rows[0].name.text, "secret <Permission denied>");
+            assert_eq!(table.rows[0].name.length, 26);
+        }
+
+        /// Add a directory that can't be read to a tree with the given
+        /// error style, returning the plain text of each row's name, or
+        /// `None` if the directory can be read anyway, as it can by root.
+        fn unreadable_dir_names(error_style: ErrorStyle) -> Option<Vec<String>> {
+            let temp = TempDir::new("error-style");
+            let path = temp.path.join("secret");
+            fs::create_dir(&path).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o000)).unwrap();
+
+            let readable = fs::read_dir(&path).is_ok();
+            let mut table = Table::default();
+
+            if !readable {
+                table.colours = Colours::plain();
+                table.columns = Vec::new();
+
+                let details = Details {
+                    recurse: Some(RecurseOptions { tree: true, max_depth: None, max_children: None }),
+                    error_style: error_style,
+                    ..Details::default()
+                };
+
+                let dir = File::from_path(&path, None).unwrap();
+                details.add_files_to_table(&mut table, vec![ dir ], 0, Path::new(""));
+            }
+
+            // The permissions have to be put back for the directory to be
+            // removed, so the checks happen afterwards.
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+            if readable { None }
+                   else { Some(table.rows.iter().map(|row| row.name.plain_text()).collect()) }
+        }
+
+        #[test]
+        fn permission_denied_rows() {
+            if let Some(names) = unreadable_dir_names(ErrorStyle::Rows) {
+                assert_eq!(names.len(), 2);
+                assert_eq!(names[0], "secret");
+                assert!(names[1].starts_with("<Permission denied"));
+            }
+        }
+
+        #[test]
+        fn permission_denied_inline() {
+            if let Some(names) = unreadable_dir_names(ErrorStyle::Inline) {
+                assert_eq!(names.len(), 1);
+                assert!(names[0].starts_with("secret <Permission denied"));
+            }
+        }
+    }
+
+    mod group_by {
+        #![allow(unused_results)]
+        use super::*;
+        use super::super::group_files;
+        use std::fs;
+        use std::os::unix::fs::symlink;
+        use libc;
+        use options::GroupBy;
+        use users::{OSUsers, Users};
+
+        fn headings(group_by: GroupBy) -> Vec<(String, Vec<String>)> {
+            let temp = TempDir::new(&format!("group-by-{:?}", group_by));
+            let path = temp.path.clone();
+            fs::create_dir_all(path.join("d")).unwrap();
+            for name in &[ "a.rs", "b.txt", "c" ] {
+                fs::File::create(path.join(name)).unwrap();
+            }
+            symlink("c", path.join("e")).unwrap();
+
+            let files = [ "a.rs", "b.txt", "c", "d", "e" ].iter().map(|name| File::from_path(&path.join(name), None).unwrap()).collect();
+            let groups = group_files(files, group_by).into_iter().map(|(heading, files)| (heading, files.iter().map(|f| f.name.clone()).collect())).collect();
+            groups
+        }
+
+        #[test]
+        fn by_type() {
+            assert_eq!(headings(GroupBy::Type), vec![
+                ("Directories".to_string(), vec![ "d".to_string() ]),
+                ("Files".to_string(),       vec![ "a.rs".to_string(), "b.txt".to_string(), "c".to_string() ]),
+                ("Symlinks".to_string(),    vec![ "e".to_string() ]),
+            ]);
+        }
+
+        #[test]
+        fn by_extension() {
+            assert_eq!(headings(GroupBy::Extension), vec![
+                (".rs".to_string(),          vec![ "a.rs".to_string() ]),
+                (".txt".to_string(),         vec![ "b.txt".to_string() ]),
+                ("No extension".to_string(), vec![ "c".to_string(), "d".to_string(), "e".to_string() ]),
+            ]);
+        }
+
+        #[test]
+        fn by_owner() {
+            let uid = unsafe { libc::geteuid() };
+            let owner = match OSUsers::empty_cache().get_user_by_uid(uid) {
+                Some(user)  => user.name,
+                None        => uid.to_string(),
+            };
+
+            assert_eq!(headings(GroupBy::Owner), vec![
+                (owner, vec![ "a.rs".to_string(), "b.txt".to_string(), "c".to_string(), "d".to_string(), "e".to_string() ]),
+            ]);
+        }
+    }
+
+    mod placeholder {
+        use super::*;
+        use colours::Colours;
+
+        #[test]
+        fn default_dash() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            assert_eq!(table.render_blocks(f::Blocks::None).text, "-");
+        }
+
+        #[test]
+        fn custom() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            table.set_placeholder("n/a".to_string());
+            assert_eq!(table.render_blocks(f::Blocks::None), Cell { text: "n/a".to_string(), length: 3 });
+        }
+
+        #[test]
+        fn git_status_width() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            table.set_placeholder("".to_string());
+
+            let git = f::Git { staged: f::GitStatus::NotModified, unstaged: f::GitStatus::Modified };
+            assert_eq!(table.render_git_status(git), Cell { text: "M".to_string(), length: 1 });
+        }
+    }
+
+    mod fs_percent {
+        use super::*;
+        use colours::Colours;
+
+        #[test]
+        fn tiny_file() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            assert_eq!(table.render_fs_percent(Some(10), Some(1_000_000_000)).text, "0.0%");
+        }
+
+        #[test]
+        fn quarter() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            assert_eq!(table.render_fs_percent(Some(250), Some(1_000)).text, "25.0%");
+        }
+
+        #[test]
+        fn no_size() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            assert_eq!(table.render_fs_percent(None, Some(1_000)).text, "-");
+            assert_eq!(table.render_fs_percent(Some(250), None).text, "-");
+        }
+    }
+
+    mod capabilities {
+        use super::*;
+        use colours::Colours;
+        use feature::capabilities::CapabilitySet;
+
+        #[test]
+        fn grouped() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+
+            let sets = vec![
+                CapabilitySet { names: vec![ "cap_net_raw".to_string(), "cap_net_admin".to_string() ], flags: "ep".to_string() },
+                CapabilitySet { names: vec![ "cap_chown".to_string() ], flags: "i".to_string() },
+            ];
+
+            assert_eq!(table.render_capabilities(sets).text, "cap_net_raw,cap_net_admin=ep cap_chown=i");
+        }
+
+        #[test]
+        fn none() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            assert_eq!(table.render_capabilities(Vec::new()).text, "-");
+        }
+    }
+
+    mod link_siblings {
+        #![allow(unused_results)]
+        use super::*;
+        use std::fs;
+        use colours::Colours;
+        use output::details::Details;
+
+        #[test]
+        fn counted_in_listing() {
+            let temp = TempDir::new("link-siblings");
+            let path = temp.path.clone();
+            fs::File::create(path.join("a")).unwrap();
+            fs::File::create(path.join("c")).unwrap();
+            fs::hard_link(path.join("a"), path.join("b")).unwrap();
+            fs::hard_link(path.join("a"), path.join("d")).unwrap();
+
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            table.columns = vec![ Column::HardLinks ];
+            table.use_link_siblings();
+
+            let files = [ "a", "b", "c" ].iter().map(|name| File::from_path(&path.join(name), None).unwrap()).collect();
+            Details::default().add_files_to_table(&mut table, files, 0, &path);
+
+            table.add_link_siblings();
+            let links: Vec<String> = table.rows.iter().map(|row| row.cells.as_ref().unwrap()[0].text.clone()).collect();
+            assert_eq!(links, vec![ "3 (2 here)", "3 (2 here)", "1" ]);
+        }
+    }
+
+    mod rank {
+        #![allow(unused_results)]
+        use super::*;
+        use std::fs;
+        use colours::Colours;
+        use options::RecurseOptions;
+        use output::details::Details;
+
+        #[test]
+        fn counted_per_directory() {
+            let temp = TempDir::new("rank");
+            let path = temp.path.clone();
+            fs::create_dir_all(path.join("d")).unwrap();
+            for name in &[ "a", "b", "c", "d/x", "d/y" ] {
+                fs::File::create(path.join(name)).unwrap();
+            }
+
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            table.columns = vec![ Column::Rank ];
+
+            let details = Details {
+                recurse: Some(RecurseOptions { tree: true, max_depth: None, max_children: None }),
+                ..Details::default()
+            };
+
+            let dir = File::from_path(&path, None).unwrap();
+            details.add_files_to_table(&mut table, vec![ dir ], 0, &path);
+
+            let ranks: Vec<String> = table.rows.iter().map(|row| row.cells.as_ref().unwrap()[0].text.clone()).collect();
+            assert_eq!(ranks, vec![ "1", "1", "2", "3", "4", "1", "2" ]);
+            assert!(table.rows[5].name.text.ends_with("x"));
+        }
+    }
+
+    mod max_children {
+        #![allow(unused_results)]
+        use super::*;
+        use std::fs;
+        use options::{Options, RecurseOptions};
+        use output::details::Details;
+
+        #[test]
+        fn capped_at_five() {
+            let temp = TempDir::new("max-children");
+            let path = temp.path.clone();
+            for n in 0 .. 100 {
+                fs::File::create(path.join(format!("file{:03}", n))).unwrap();
+            }
+
+            let mut table = Table::default();
+            table.columns = Vec::new();
+
+            let details = Details {
+                recurse: Some(RecurseOptions { tree: true, max_depth: None, max_children: Some(5) }),
+                ..Details::default()
+            };
+
+            let dir = File::from_path(&path, None).unwrap();
+            let (count, _) = details.add_files_to_table(&mut table, vec![ dir ], 0, &path);
+
+            assert_eq!(count, 6);
+            assert_eq!(table.rows.len(), 7);
+            assert!(table.rows[1].name.text.ends_with("file000"));
+            assert!(table.rows[5].name.text.ends_with("file004"));
+            assert!(!table.rows[5].last);
+
+            assert_eq!(table.rows[6].name.text, "… (95 more)");
+            assert_eq!(table.rows[6].depth, 1);
+            assert!(table.rows[6].last);
+        }
+
+        #[test]
+        fn capped_after_reversing() {
+            let temp = TempDir::new("max-children-reversed");
+            let path = temp.path.clone();
+            for n in 0 .. 10 {
+                fs::File::create(path.join(format!("file{:03}", n))).unwrap();
+            }
+
+            let mut table = Table::default();
+            table.columns = Vec::new();
+
+            let (options, _) = Options::getopts(&[ "--reverse".to_string() ]).unwrap();
+            let details = Details {
+                recurse: Some(RecurseOptions { tree: true, max_depth: None, max_children: Some(3) }),
+                filter: options.filter,
+                ..Details::default()
+            };
+
+            let dir = File::from_path(&path, None).unwrap();
+            details.add_files_to_table(&mut table, vec![ dir ], 0, &path);
+
+            assert_eq!(table.rows.len(), 5);
+            assert!(table.rows[1].name.text.ends_with("file009"));
+            assert!(table.rows[2].name.text.ends_with("file008"));
+            assert!(table.rows[3].name.text.ends_with("file007"));
+            assert_eq!(table.rows[4].name.text, "… (7 more)");
+        }
+    }
+
+    mod meta_changed {
+        use super::*;
+
+        #[test]
+        fn unchanged() {
+            let table = Table::default();
+            assert_eq!(table.render_meta_changed(f::Time(1000, 5), f::Time(1000, 5)), Cell::empty());
+            assert_eq!(table.render_meta_changed(f::Time(1000, 5), f::Time(900, 0)), Cell::empty());
+        }
+
+        #[test]
+        fn changed_later() {
+            let table = Table::default();
+            assert_eq!(table.render_meta_changed(f::Time(1000, 0), f::Time(1000, 1)).text, "+0s");
+            assert_eq!(table.render_meta_changed(f::Time(1000, 0), f::Time(1045, 0)).text, "+45s");
+            assert_eq!(table.render_meta_changed(f::Time(1000, 0), f::Time(1000 + 90 * 60, 0)).text, "+1h");
+            assert_eq!(table.render_meta_changed(f::Time(1000, 0), f::Time(1000 + 3 * 86400, 0)).text, "+3d");
+        }
+    }
+
+    mod link_depth {
+        use super::*;
+        use std::fs;
+        use std::os::unix::fs::symlink;
+
+        /// Make the given symlinks, each pointing at its target relative to
+        /// where it is, and render the depth of the first one.
+        fn depth_of(name: &str, links: &[(&str, &str)]) -> String {
+            let temp = TempDir::new(name);
+            fs::create_dir_all(temp.path.join("sub")).unwrap();
+            fs::File::create(temp.path.join("target")).unwrap();
+            for &(link, target) in links {
+                symlink(target, temp.path.join(link)).unwrap();
+            }
+
+            let file = File::from_path(&temp.path.join(links[0].0), None).unwrap();
+            Table::default().render_link_depth(file.link_depth()).text
+        }
+
+        #[test]
+        fn relative_chain() {
+            assert_eq!(depth_of("link-depth-chain", &[ ("first", "sub/second"), ("sub/second", "third"), ("sub/third", "../target") ]), "3");
+        }
+
+        #[test]
+        fn loop_of_links() {
+            assert_eq!(depth_of("link-depth-loop", &[ ("a", "b"), ("b", "a") ]), "∞");
+        }
+
+        #[test]
+        fn loop_in_the_path() {
+            assert_eq!(depth_of("link-depth-eloop", &[ ("a", "dir/file"), ("dir", "dir") ]), "∞");
+        }
+
+        #[test]
+        fn dangling() {
+            assert_eq!(depth_of("link-depth-dangling", &[ ("a", "missing") ]), "!");
+        }
+
+        #[test]
+        fn hops() {
+            let table = Table::default();
+            assert_eq!(table.render_link_depth(f::LinkDepth::Some(3)).text, "3");
+        }
+
+        #[test]
+        fn cyclic_and_broken() {
+            let table = Table::default();
+            assert_eq!(table.render_link_depth(f::LinkDepth::Cyclic), Cell { text: "∞".to_string(), length: 1 });
+            assert_eq!(table.render_link_depth(f::LinkDepth::Broken).text, "!");
+        }
+
+        #[test]
+        fn not_a_link() {
+            let table = Table::default();
+            assert_eq!(table.render_link_depth(f::LinkDepth::NotLink).text, "-");
+        }
+    }
+
+    mod lifetime {
+        use super::*;
+
+        #[test]
+        fn modified_after_birth() {
+            let table = Table::default();
+            assert_eq!(table.render_lifetime(Some(f::Time(1000, 0)), f::Time(1000, 0)).text, "0s");
+            assert_eq!(table.render_lifetime(Some(f::Time(1000, 0)), f::Time(1000 + 2 * 3600, 0)).text, "2h");
+            assert_eq!(table.render_lifetime(Some(f::Time(1000, 0)), f::Time(1000 + 3 * 86400, 0)).text, "3d");
+        }
+
+        #[test]
+        fn no_birth_time() {
+            let table = Table::default();
+            assert_eq!(table.render_lifetime(None, f::Time(1000, 0)).text, "-");
+        }
+
+        #[test]
+        fn clock_skew() {
+            let table = Table::default();
+            assert_eq!(table.render_lifetime(Some(f::Time(1000, 0)), f::Time(900, 0)).text, "-");
+        }
+    }
+
+    mod time_styles {
+        #![allow(unused_results)]
+        use super::*;
+        use super::super::format_offset;
+        use options::TimeStyle;
+
+        fn render(style: TimeStyle, current_year: i64) -> String {
+            let mut table = Table::default();
+            table.tz = None;
+            table.current_year = current_year;
+            table.set_time_style(style);
+            table.render_time(f::Time(1000000000, 123456789)).text
+        }
+
+        #[test]
+        fn default() {
+            assert_eq!(render(TimeStyle::Default, 2001), " 9 Sep  1:46");
+        }
+
+        #[test]
+        fn full_iso() {
+            assert_eq!(render(TimeStyle::FullISO, 2001), "2001-09-09 01:46:40.123456789 +0000");
+        }
+
+        #[test]
+        fn long_iso() {
+            assert_eq!(render(TimeStyle::LongISO, 2015), "2001-09-09 01:46");
+        }
+
+        #[test]
+        fn iso() {
+            assert_eq!(render(TimeStyle::ISO, 2001), "09-09 01:46");
+            assert_eq!(render(TimeStyle::ISO, 2015), "2001-09-09");
+        }
+
+        #[test]
+        fn custom() {
+            let style = TimeStyle::Custom("%d/%m/%y %T.%N %b %z %% %q".to_string());
+            assert_eq!(render(style, 2001), "09/09/01 01:46:40.123456789 Sep +0000 % %q");
+        }
+
+        #[test]
+        fn offsets() {
+            assert_eq!(format_offset(3600), "+0100");
+            assert_eq!(format_offset(-19800), "-0530");
+        }
+    }
+
+    mod time_zones {
+        #![allow(unused_results)]
+        use super::*;
+        use super::super::zone_or_utc;
+        use datetime::local::LocalDateTime;
+        use datetime::zoned::TimeZone;
+
+        #[test]
+        fn falls_back_to_utc() {
+            let result: Result<TimeZone, &str> = Err("no /etc/localtime");
+            assert!(zone_or_utc(result).is_none());
+        }
+
+        #[test]
+        fn renders_in_utc() {
+            let mut table = Table::default();
+            table.tz = None;
+            table.set_now(LocalDateTime::at(1000000000));
+
+            assert!(table.time_zone().is_none());
+            assert_eq!(Cell::paint(Style::default(), " 1 Jan  1970"), table.render_time(f::Time(0, 0)));
+        }
+
+        #[test]
+        fn marked_as_utc() {
+            let mut table = Table::default();
+            table.use_utc();
+            table.current_year = 2001;
+            assert_eq!(Cell::paint(Style::default(), " 9 Sep  1:46 UTC"), table.render_time(f::Time(1000000000, 0)));
+
+            table.current_year = 2015;
+            assert_eq!(Cell::paint(Style::default(), " 9 Sep  2001 UTC"), table.render_time(f::Time(1000000000, 0)));
+        }
+
+        #[test]
+        fn nanoseconds() {
+            let mut table = Table::default();
+            table.use_utc();
+            table.use_precise_times();
+            assert_eq!(table.render_time(f::Time(1000000000, 123456789)).text, "2001-09-09 01:46:40.123456789 UTC");
+            assert_eq!(table.render_time(f::Time(1000000000, 5)).text, "2001-09-09 01:46:40.000000005 UTC");
+        }
+    }
+
+    mod permissions {
+        #![allow(unused_results)]
+        use super::*;
+        use options::PermissionsFormat;
+
+        fn rwxr_xr_x() -> f::Permissions {
+            f::Permissions {
+                file_type:      f::Type::File,
+                user_read:      true,
+                user_write:     true,
+                user_execute:   true,
+                group_read:     true,
+                group_write:    false,
+                group_execute:  true,
+                other_read:     true,
+                other_write:    false,
+                other_execute:  true,
+            }
+        }
+
+        #[test]
+        fn octal() {
+            let mut table = Table::default();
+            table.colours.perms.octal = Purple.normal();
+
+            let expected = Cell::paint(Purple.normal(), "0755");
+            assert_eq!(expected, table.render_permissions(rwxr_xr_x(), PermissionsFormat::Octal, false, false));
+        }
+
+        #[test]
+        fn both_with_xattrs() {
+            let table = Table::default();
+            let cell = table.render_permissions(rwxr_xr_x(), PermissionsFormat::Both, false, true);
+
+            assert_eq!(cell.text, "0755 .rwxr-xr-x@");
+            assert_eq!(cell.length, 16);
+        }
+
+        #[test]
+        fn type_only() {
+            let mut table = Table::default();
+            table.colours.filetypes.normal = Blue.normal();
+
+            let expected = Cell::paint(Blue.normal(), ".");
+            assert_eq!(expected, table.render_permissions(rwxr_xr_x(), PermissionsFormat::TypeOnly, false, true));
+        }
+
+        #[test]
+        fn bits_without_type() {
+            let table = Table::default();
+            let cell = table.render_permissions(rwxr_xr_x(), PermissionsFormat::Bits, false, true);
+
+            assert_eq!(cell.text, "rwxr-xr-x@");
+            assert_eq!(cell.length, 10);
+        }
+
+        #[test]
+        fn acl() {
+            let mut table = Table::default();
+            table.colours.perms.attribute = Cyan.normal();
+
+            let cell = table.render_permissions(rwxr_xr_x(), PermissionsFormat::Symbolic, true, false);
+            assert!(cell.text.ends_with(&Cyan.paint("+").to_string()));
+            assert_eq!(cell.length, 11);
+        }
+
+        #[test]
+        fn acl_with_xattrs() {
+            let table = Table::default();
+            let cell = table.render_permissions(rwxr_xr_x(), PermissionsFormat::Symbolic, true, true);
+
+            assert_eq!(cell.text, ".rwxr-xr-x+@");
+            assert_eq!(cell.length, 12);
+        }
+    }
+
+    mod age_days {
+        #![allow(unused_results)]
+        use super::*;
+        use options::AgeStop;
+
+        use datetime::local::LocalDateTime;
+
+        const DAY: i64 = 60 * 60 * 24;
+
+        #[test]
+        fn today() {
+            let mut table = Table::default();
+            table.set_now(LocalDateTime::at(100 * DAY));
+            assert_eq!(table.render_age_days(f::Time(100 * DAY - 60, 0)).text, "0");
+        }
+
+        #[test]
+        fn days_ago() {
+            let mut table = Table::default();
+            table.set_now(LocalDateTime::at(100 * DAY));
+            assert_eq!(table.render_age_days(f::Time(97 * DAY - 60, 0)).text, "3");
+        }
+
+        #[test]
+        fn future() {
+            let mut table = Table::default();
+            table.set_now(LocalDateTime::at(100 * DAY));
+            assert_eq!(table.render_age_days(f::Time(100 * DAY + 60, 0)).text, "-1");
+            assert_eq!(table.render_age_days(f::Time(101 * DAY, 0)).text, "-1");
+            assert_eq!(table.render_age_days(f::Time(101 * DAY + 1, 0)).text, "-2");
+        }
+
+        fn stops() -> Vec<AgeStop> {
+            vec![ AgeStop { age: DAY, colour: 46 }, AgeStop { age: 30 * DAY, colour: 196 } ]
+        }
+
+        #[test]
+        fn gradient_fresh() {
+            let mut table = Table::default();
+            table.set_now(LocalDateTime::at(100 * DAY));
+            table.set_age_stops(stops());
+            assert_eq!(table.render_age_gradient(f::Time(100 * DAY - 60, 0)), Cell::paint(Fixed(46).normal(), "●"));
+        }
+
+        #[test]
+        fn gradient_stale() {
+            let mut table = Table::default();
+            table.set_now(LocalDateTime::at(100 * DAY));
+            table.set_age_stops(stops());
+            assert_eq!(table.render_age_gradient(f::Time(DAY, 0)), Cell::paint(Fixed(196).normal(), "●"));
+        }
+
+        #[test]
+        fn gradient_without_stops() {
+            let table = Table::default();
+            assert_eq!(table.render_age_gradient(f::Time(0, 0)), Cell::paint(Style::default(), "●"));
+        }
+
+        #[test]
+        fn epoch() {
+            let mut table = Table::default();
+            table.set_now(LocalDateTime::at(100 * DAY));
+            assert_eq!(table.render_age_days(f::Time(0, 0)).text, "100");
+        }
+    }
+
+    mod collapse {
+        use super::*;
+        use super::super::Row;
+
+        #[test]
+        fn merges_chain() {
+            let mut table = Table::default();
+            table.rows = vec![ Row { last: true, is_dir: true, ..Row::test("a", 0, &[ "a" ]) },
+                               Row { last: true, is_dir: true, ..Row::test("b", 1, &[ "b" ]) },
+                               Row { last: true, is_dir: true, ..Row::test("c", 2, &[ "c" ]) },
+                               Row { last: true, ..Row::test("d", 3, &[ "d" ]) } ];
+            table.collapse_directory_chains();
+
+            assert_eq!(table.rows.len(), 2);
+            assert_eq!(table.rows[0].name.text, "a/b/c");
+            assert_eq!(table.rows[0].cells.as_ref().unwrap()[0].text, "c");
+            assert_eq!(table.rows[1].depth, 1);
+        }
+
+        #[test]
+        fn keeps_siblings() {
+            let mut table = Table::default();
+            table.rows = vec![ Row { last: true, is_dir: true, ..Row::test("a", 0, &[ "a" ]) },
+                               Row { is_dir: true, ..Row::test("b", 1, &[ "b" ]) },
+                               Row { last: true, is_dir: true, ..Row::test("c", 1, &[ "c" ]) } ];
+            table.collapse_directory_chains();
+
+            assert_eq!(table.rows.len(), 3);
+        }
+    }
+
+    mod tree_indent {
+        use super::*;
+        use super::super::{Row, TREE_INDENT};
+        use options::TreeStyle;
+
+        #[test]
+        fn narrow() {
+            let mut table = Table::default();
+            table.columns = Vec::new();
+            table.set_tree_indent(2);
+            table.rows = vec![ Row { last: true, ..Row::test("a", 0, &[]) },
+                               Row { last: true, ..Row::test("b", 1, &[]) },
+                               Row::test("c", 2, &[]),
+                               Row { last: true, ..Row::test("d", 3, &[]) } ];
+
+            let cells = table.print_table();
+            assert_eq!(cells[3].text, "  │ └─ d");
+            assert_eq!(cells[3].length, 3 * 2 + 1 + 1);
+            assert_eq!(cells[3].length, table.rows[3].name_width(2));
+        }
+
+        #[test]
+        fn mirrored() {
+            let mut table = Table::default();
+            table.columns = Vec::new();
+            table.use_mirrored_tree();
+            table.rows = vec![ Row { last: true, ..Row::test("a", 0, &[]) },
+                               Row::test("b", 1, &[]),
+                               Row { last: true, ..Row::test("c", 2, &[]) },
+                               Row { last: true, ..Row::test("d", 1, &[]) } ];
+
+            let cells = table.print_table();
+            let lines: Vec<&str> = cells.iter().map(|c| &*c.text).collect();
+            assert_eq!(lines, vec![ "       a",
+                                    "   b ──┤",
+                                    "c ──┘  │",
+                                    "   d ──┘" ]);
+            assert!(cells.iter().all(|c| c.length == 8));
+        }
+
+        #[test]
+        fn ascii_style() {
+            let mut table = Table::default();
+            table.columns = Vec::new();
+            table.set_tree_style(TreeStyle::Ascii);
+            table.rows = vec![ Row { last: true, ..Row::test("a", 0, &[]) },
+                               Row::test("b", 1, &[]),
+                               Row { last: true, ..Row::test("c", 2, &[]) } ];
+
+            let cells = table.print_table();
+            assert_eq!(cells[1].text, "|-- b");
+            assert_eq!(cells[2].text, "|  `-- c");
+            assert_eq!(cells[2].length, 2 * TREE_INDENT + 1 + 1);
+        }
+
+        #[test]
+        fn rounded_style() {
+            let mut table = Table::default();
+            table.columns = Vec::new();
+            table.set_tree_style(TreeStyle::Rounded);
+            table.rows = vec![ Row { last: true, ..Row::test("a", 0, &[]) },
+                               Row { last: true, ..Row::test("b", 1, &[]) } ];
+
+            assert_eq!(table.print_table()[1].text, "╰── b");
+        }
+    }
+
+    mod inodes {
+        use super::*;
+
+        #[test]
+        fn decimal() {
+            let table = Table::default();
+            assert_eq!(table.render_inode(f::Inode(6699)).text, "6699");
+        }
+
+        #[test]
+        fn hexadecimal() {
+            let mut table = Table::default();
+            table.use_hex_inodes();
+            assert_eq!(table.render_inode(f::Inode(6699)).text, "0x1a2b");
+        }
+    }
+
+    mod dim_metadata {
+        use super::*;
+        use super::super::Details;
+        use colours::Colours;
+        use std::fs;
+        use options::HeaderStyle;
+
+        fn lines(dimmed: bool) -> Vec<String> {
+            let dir = TempDir::new("dim_metadata");
+            let path = dir.path.join("a");
+            fs::File::create(&path).unwrap();
+            let file = File::from_path(&path, None).unwrap();
+
+            let mut table = Table::default();
+            table.colours = Colours::colourful();
+            table.columns = vec![ Column::HardLinks ];
+            if dimmed { table.use_dimmed_metadata() }
+            table.add_header(HeaderStyle::Plain);
+
+            let cells = table.cells_for_file(&file, false);
+            table.add_file_with_cells(cells, &file, 0, false, false);
+            table.print_table().into_iter().map(|c| c.text).collect()
+        }
+
+        #[test]
+        fn names_and_header_stay_bright() {
+            let bright = lines(false);
+            let dimmed = lines(true);
+
+            let bright_count = Red.bold().paint("1").to_string();
+            let dimmed_count = Red.bold().dimmed().paint("1").to_string();
+
+            assert_eq!(dimmed[0], bright[0]);
+            assert!(dimmed[1].contains(&dimmed_count));
+            assert_eq!(dimmed[1].replace(&dimmed_count, &bright_count), bright[1]);
+        }
+
+        #[test]
+        fn shared_with_grid_details() {
+            let temp = TempDir::new("dim_metadata");
+            let path = temp.path.join("a");
+            fs::File::create(&path).unwrap();
+            let file = File::from_path(&path, None).unwrap();
+
+            let details = Details {
+                colours: Colours::colourful(),
+                dim_metadata: true,
+                ..Details::default()
+            };
+
+            let mut table = details.cells_table(vec![ Column::HardLinks ]);
+            assert_eq!(table.cells_for_file(&file, false)[0].text, Red.bold().dimmed().paint("1").to_string());
+        }
+    }
+
+    mod diff {
+        #![allow(unused_results)]
+        use super::*;
+        use super::super::{diff_files, Difference, Row};
+        use std::fs;
+        use std::io::Write;
+        use dir::Dir;
+
+        #[test]
+        fn added_removed_changed() {
+            let temp = TempDir::new("diff");
+            let path = temp.path.clone();
+            fs::create_dir_all(path.join("old")).unwrap();
+            fs::create_dir_all(path.join("new")).unwrap();
+            fs::File::create(path.join("old/gone")).unwrap();
+            fs::File::create(path.join("old/grown")).unwrap();
+            fs::File::create(path.join("new/grown")).unwrap().write_all(b"more").unwrap();
+            fs::File::create(path.join("new/fresh")).unwrap();
+
+            let old = Dir::read_dir(&path.join("old"), false, false, false).unwrap();
+            let new = Dir::read_dir(&path.join("new"), false, false, false).unwrap();
+            let old_files = old.files().map(|f| f.unwrap()).collect();
+            let new_files = new.files().map(|f| f.unwrap()).collect();
+
+            let mut diffs: Vec<(Difference, String)> = diff_files(old_files, new_files).into_iter().map(|(d, f)| (d, f.name.clone())).collect();
+
+            diffs.sort_by(|a, b| a.1.cmp(&b.1));
+            assert_eq!(diffs, vec![ (Difference::Added,   "fresh".to_string()),
+                                    (Difference::Removed, "gone".to_string()),
+                                    (Difference::Changed, "grown".to_string()) ]);
+        }
+
+        #[test]
+        fn markers_stay_with_their_rows() {
+            let mut table = Table::default();
+            table.columns = Vec::new();
+
+            for &(name, marker) in &[ ("a", Some("+")), ("b", None), ("c", Some("-")) ] {
+                table.rows.push(Row { marker: marker.map(|m| Cell::paint(Style::default(), m)), ..Row::test(name, 0, &[]) });
+            }
+
+            let lines: Vec<String> = table.print_table().into_iter().map(|c| c.text).collect();
+            assert_eq!(lines, vec![ "+ a", "  b", "- c" ]);
+        }
+    }
+
+    mod flat_tree {
+        use super::*;
+        use super::super::{Details, Row};
+        use colours::Colours;
+        use options::{HeaderStyle, RecurseOptions};
+        use std::fs;
+        use std::path::Path;
+
+        #[test]
+        fn names_only() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            table.columns = vec![ Column::User ];
+            table.use_flat_tree();
+            table.add_header(HeaderStyle::Plain);
+            table.rows.push(Row::test("src", 0, &[ "ann" ]));
+            table.rows.push(Row::test("src/main.rs", 1, &[ "ann" ]));
+            table.rows.push(Row { cells: None, ..Row::test("<Permission denied>", 1, &[]) });
+            table.rows.push(Row::test("README.md", 0, &[ "ann" ]));
+
+            let lines: Vec<String> = table.print_table().into_iter().map(|c| c.text).collect();
+            assert_eq!(lines, vec![ "src", "src/main.rs", "README.md" ]);
+        }
+
+        #[test]
+        fn paths_from_the_root() {
+            let temp = TempDir::new("flat-tree");
+            let src = temp.path.join("src");
+            fs::create_dir_all(src.join("output")).unwrap();
+            fs::File::create(src.join("main.rs")).unwrap();
+            fs::File::create(src.join("output/details.rs")).unwrap();
+
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            table.columns = Vec::new();
+            table.use_flat_tree();
+
+            let details = Details {
+                recurse: Some(RecurseOptions { tree: true, max_depth: None, max_children: None }),
+                flat_tree: true,
+                ..Details::default()
+            };
+
+            let root = File::from_path(&src, None).unwrap();
+            let _ = details.add_files_to_table(&mut table, vec![ root ], 0, Path::new(""));
+
+            let lines: Vec<String> = table.print_table().into_iter().map(|c| c.text).collect();
+            assert_eq!(lines, vec![ format!("{}", src.display()),
+                                    format!("{}/main.rs", src.display()),
+                                    format!("{}/output", src.display()),
+                                    format!("{}/output/details.rs", src.display()) ]);
+        }
+    }
+
+    mod line_numbers {
+        use super::*;
+        use super::super::Row;
+        use colours::Colours;
+        use options::HeaderStyle;
+
+        #[test]
+        fn after_header() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            table.columns = vec![ Column::User ];
+            table.use_line_numbers();
+            table.add_header(HeaderStyle::Plain);
+            for _ in 0 .. 10 {
+                table.rows.push(Row::test("a", 0, &[ "ann" ]));
+            }
+
+            let lines: Vec<String> = table.print_table().into_iter().map(|c| c.text).collect();
+            assert_eq!(lines[0], "   User Name");
+            assert_eq!(lines[1], " 2 ann  a");
+            assert_eq!(lines[10], "11 ann  a");
+        }
+
+        #[test]
+        fn continuous_through_tree() {
+            let mut table = Table::default();
+            table.colours = Colours::plain();
+            table.columns = vec![ Column::User ];
+            table.use_line_numbers();
+            table.rows = vec![ Row::test("d", 0, &[ "ann" ]), Row::test("x", 1, &[ "ann" ]), Row::test("e", 0, &[ "ann" ]) ];
+
+            let numbers: Vec<String> = table.print_table().into_iter().map(|c| c.text[.. 1].to_string()).collect();
+            assert_eq!(numbers, vec![ "1", "2", "3" ]);
+        }
+    }
+
+    mod borders {
+        use super::*;
+        use super::super::Row;
+        use options::{HeaderStyle, WidthLimit};
+        use std::collections::HashMap;
+
+        #[test]
+        fn boxed() {
+            let mut table = Table::default();
+            table.columns = vec![ Column::User ];
+            table.use_borders();
+            table.rows = vec![ Row::test("a", 0, &[ "ann" ]), Row::test("file", 0, &[ "bo" ]) ];
+
+            let lines: Vec<String> = table.print_table().into_iter().map(|c| c.text).collect();
+            assert_eq!(lines, vec![ "┌─────┬──────┐",
+                                    "│ ann │ a    │",
+                                    "│ bo  │ file │",
+                                    "└─────┴──────┘" ]);
+        }
+
+        #[test]
+        fn header_rule() {
+            let mut table = Table::default();
+            table.columns = vec![ Column::User ];
+            table.use_borders();
+            table.add_header(HeaderStyle::Plain);
+            table.rows.push(Row::test("a", 0, &[ "ann" ]));
+
+            let cells = table.print_table();
+            assert_eq!(cells[1].text, "│ User │ Name │");
+            assert_eq!(cells[2].text, "├──────┼──────┤");
+            assert_eq!(cells[2].length, 15);
+        }
+
+        #[test]
+        fn width_limit() {
+            let mut limits = HashMap::new();
+            let _ = limits.insert("user".to_string(), WidthLimit { min: None, max: Some(3) });
+
+            let mut table = Table::default();
+            table.columns = vec![ Column::User ];
+            table.use_borders();
+            table.set_width_limits(limits);
+            table.rows = vec![ Row::test("a", 0, &[ "annabel" ]), Row::test("file", 0, &[ "bo" ]) ];
+
+            let lines: Vec<String> = table.print_table().into_iter().map(|c| c.text).collect();
+            assert_eq!(lines, vec![ "┌─────┬──────┐",
+                                    "│ an… │ a    │",
+                                    "│ bo  │ file │",
+                                    "└─────┴──────┘" ]);
+        }
+    }
+
+    mod width_limits {
+        use super::*;
+        use super::super::Row;
+        use options::WidthLimit;
+        use std::collections::HashMap;
+
+        #[test]
+        fn widest_cell_truncated() {
+            let mut limits = HashMap::new();
+            let _ = limits.insert("user".to_string(), WidthLimit { min: None, max: Some(3) });
+
+            let mut table = Table::default();
+            table.columns = vec![ Column::User ];
+            table.set_width_limits(limits);
+            table.rows = vec![ Row::test("a", 0, &[ "annabel" ]), Row::test("b", 0, &[ "bo" ]) ];
+
+            let lines: Vec<String> = table.print_table().into_iter().map(|c| c.text).collect();
+            assert_eq!(lines, vec![ "an… a", "bo  b" ]);
+        }
+
+        #[test]
+        fn narrow_cells_padded() {
+            let mut limits = HashMap::new();
+            let _ = limits.insert("user".to_string(), WidthLimit { min: Some(5), max: None });
+
+            let mut table = Table::default();
+            table.columns = vec![ Column::User ];
+            table.set_width_limits(limits);
+            table.rows = vec![ Row::test("b", 0, &[ "bo" ]) ];
+
+            let lines: Vec<String> = table.print_table().into_iter().map(|c| c.text).collect();
+            assert_eq!(lines, vec![ "bo    b" ]);
+        }
+    }
+
+    mod subdir_count {
+        use super::*;
+        use std::fs;
+
+        #[test]
+        fn counts_only_directories() {
+            let temp = TempDir::new("subdir-count");
+            let path = temp.path.clone();
+            fs::create_dir_all(path.join("one")).unwrap();
+            fs::create_dir_all(path.join("two")).unwrap();
+            let _ = fs::File::create(path.join("file")).unwrap();
+
+            let table = Table::default();
+            let dir = File::from_path(&path, None).unwrap();
+            let file = File::from_path(&path.join("file"), None).unwrap();
+            assert_eq!(table.render_entry_count(dir.subdir_count()).text, "2");
+            assert_eq!(table.render_entry_count(file.subdir_count()).text, "-");
+
+        }
+    }
+
+    mod dereference {
+        use super::*;
+        use options::PermissionsFormat;
+        use std::fs;
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        fn permissions(dereference: bool, link: &str) -> String {
+            let temp = TempDir::new(&format!("dereference-{}-{}", link, dereference));
+            let path = temp.path.clone();
+
+            let target = path.join("target");
+            let _ = fs::File::create(&target).unwrap();
+            fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+            symlink(&path.join(link), &path.join("link")).unwrap();
+
+            let mut table = Table::default();
+            table.columns = vec![ Column::Permissions(PermissionsFormat::Symbolic) ];
+            if dereference { table.use_dereference() }
+
+            let file = File::from_path(&path.join("link"), None).unwrap();
+            let text = table.cells_for_file(&file, false)[0].text.clone();
+            text
+        }
+
+        #[test]
+        fn link_itself() {
+            assert!(permissions(false, "target").starts_with("l"));
+        }
+
+        #[test]
+        fn target() {
+            assert_eq!(permissions(true, "target"), ".rw-------");
+        }
+
+        #[test]
+        fn relative_chain() {
+            let temp = TempDir::new("dereference-relative");
+            let path = temp.path.clone();
+
+            // Both links are relative to the temporary directory, rather
+            // than the current one.
+            let target = path.join("target");
+            let _ = fs::File::create(&target).unwrap();
+            fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+            symlink("target", &path.join("second")).unwrap();
+            symlink("second", &path.join("first")).unwrap();
+
+            let mut table = Table::default();
+            table.columns = vec![ Column::Permissions(PermissionsFormat::Symbolic) ];
+            table.use_dereference();
+
+            let file = File::from_path(&path.join("first"), None).unwrap();
+            assert_eq!(table.cells_for_file(&file, false)[0].text, ".rw-------");
+        }
+
+        #[test]
+        fn dangling() {
+            let text = permissions(true, "nowhere");
+            assert!(text.starts_with("l"));
+            assert!(text.ends_with("!"));
+        }
+    }
+
+    mod compact_columns {
+        use super::*;
+        use super::super::Row;
+        use options::PermissionsFormat;
+
+        fn table(width: usize) -> Table<MockUsers> {
+            let mut table = Table::default();
+            table.columns = vec![ Column::Inode, Column::Permissions(PermissionsFormat::Symbolic), Column::User ];
+            table.truncation_order = Column::truncation_order(&table.columns);
+            table.set_compact_width(width);
+            table.rows = vec![ Row::test("file", 0, &[ "1234", ".rw-r--r--", "ann" ]) ];
+            table
+        }
+
+        #[test]
+        fn fits_already() {
+            assert_eq!(table(80).print_table()[0].text, "1234 .rw-r--r-- ann file");
+        }
+
+        #[test]
+        fn drops_inode_first() {
+            assert_eq!(table(20).print_table()[0].text, ".rw-r--r-- ann file");
+        }
+
+        #[test]
+        fn keeps_permissions() {
+            assert_eq!(table(5).print_table()[0].text, ".rw-r--r-- file");
+        }
+    }
+
+    mod ditto_owners {
+        use super::*;
+        use super::super::Row;
+
+        fn users(table: &Table<MockUsers>) -> Vec<String> {
+            table.print_table().into_iter().map(|c| c.text.split(' ').next().unwrap().to_string()).collect()
+        }
+
+        #[test]
+        fn repeats_are_dittoed() {
+            let mut table = Table::default();
+            table.columns = vec![ Column::User ];
+            table.use_ditto_owners();
+            table.rows = vec![ Row::test("file", 0, &[ "ann" ]), Row::test("file", 0, &[ "ann" ]), Row::test("file", 0, &[ "bob" ]), Row::test("file", 0, &[ "bob" ]) ];
+            assert_eq!(users(&table), vec![ "ann", "\"", "bob", "\"" ]);
+        }
+
+        #[test]
+        fn reset_across_depths() {
+            let mut table = Table::default();
+            table.columns = vec![ Column::User ];
+            table.use_ditto_owners();
+            table.rows = vec![ Row::test("file", 0, &[ "ann" ]), Row::test("file", 1, &[ "ann" ]), Row::test("file", 1, &[ "ann" ]), Row::test("file", 0, &[ "ann" ]) ];
+            assert_eq!(users(&table), vec![ "ann", "ann", "\"", "ann" ]);
+        }
+
+        #[test]
+        fn off_by_default() {
+            let mut table = Table::default();
+            table.columns = vec![ Column::User ];
+            table.rows = vec![ Row::test("file", 0, &[ "ann" ]), Row::test("file", 0, &[ "ann" ]) ];
+            assert_eq!(users(&table), vec![ "ann", "ann" ]);
+        }
+    }
+
+    mod full_width {
+        use super::*;
+        use super::super::Row;
+
+        #[test]
+        fn pads_highlighted_rows() {
+            let mut table = Table::default();
+            table.columns = Vec::new();
+            table.set_pad_width(10);
+            table.rows = vec![ Row { highlighted: true, ..Row::test("ab", 0, &[]) }, Row::test("cd", 0, &[]) ];
+
+            let cells = table.print_table();
+            assert_eq!(cells[0].text, "ab        ");
+            assert_eq!(cells[0].length, 10);
+            assert_eq!(cells[1].text, "cd");
+        }
+    }
+
+    mod access {
+        #![allow(unused_results)]
+        use super::*;
+
+        fn perms(user: (bool, bool, bool), group: (bool, bool, bool), other: (bool, bool, bool)) -> f::Permissions {
+            f::Permissions {
+                file_type:      f::Type::File,
+                user_read:      user.0,
+                user_write:     user.1,
+                user_execute:   user.2,
+                group_read:     group.0,
+                group_write:    group.1,
+                group_execute:  group.2,
+                other_read:     other.0,
+                other_write:    other.1,
+                other_execute:  other.2,
+            }
+        }
+
+        #[test]
+        fn owner() {
+            let mut table = Table::default();
+            table.users = MockUsers::with_current_uid(1000);
+
+            let p = perms((true, true, false), (true, false, false), (false, false, false));
+            assert_eq!(table.render_access(p, f::User(1000), f::Group(100)).text, "rw-");
+        }
+
+        #[test]
+        fn group_member() {
+            let mut table = Table::default();
+            let mut users = MockUsers::with_current_uid(2);
+            users.add_user(newser(2, "eve", 50));
+            users.add_group(Group { gid: 100, name: "folk".to_string(), members: vec![ "eve".to_string() ] });
+            table.users = users;
+
+            let p = perms((true, true, true), (true, false, true), (false, false, false));
+            assert_eq!(table.render_access(p, f::User(1000), f::Group(100)).text, "r-x");
+        }
+
+        #[test]
+        fn other() {
+            let mut table = Table::default();
+            table.users = MockUsers::with_current_uid(2);
+
+            let p = perms((true, true, true), (true, true, true), (true, false, false));
+            assert_eq!(table.render_access(p, f::User(1000), f::Group(100)).text, "r--");
+        }
+
+        #[test]
+        fn superuser() {
+            let mut table = Table::default();
+            table.users = MockUsers::with_current_uid(0);
+
+            let p = perms((false, false, false), (false, false, false), (false, false, true));
+            assert_eq!(table.render_access(p, f::User(1000), f::Group(100)).text, "rwx");
+        }
+    }
+
+    mod summary {
+        use super::*;
+
+        #[test]
+        fn plurals() {
+            let mut table = Table::default();
+            table.counts.files = 5;
+            table.counts.directories = 2;
+            table.counts.links = 1;
+
+            assert_eq!(table.summary(), "5 files, 2 directories, 1 symlink");
+        }
+
+        #[test]
+        fn empty() {
+            let table = Table::default();
+            assert_eq!(table.summary(), "0 files");
+        }
+    }
+
+    mod size_bar {
+        use super::*;
+
+        #[test]
+        fn largest_is_full() {
+            let table = Table::default();
+            assert_eq!(table.render_size_bar(Some(100), 100).text, "██████████");
+        }
+
+        #[test]
+        fn partial() {
+            let table = Table::default();
+            let cell = table.render_size_bar(Some(25), 100);
+
+            assert_eq!(cell.text, "██▌       ");
+            assert_eq!(cell.length, 10);
+        }
+
+        #[test]
+        fn no_size() {
+            let table = Table::default();
+            assert_eq!(table.render_size_bar(None, 100), Cell::blank(10));
+        }
+    }
+
+    mod size_percent {
+        use super::*;
+
+        #[test]
+        fn share_of_total() {
+            let table = Table::default();
+            assert_eq!(table.render_size_percent(Some(421), 1000).text, "42.1%");
+        }
+
+        #[test]
+        fn no_size() {
+            let table = Table::default();
+            assert_eq!(table.render_size_percent(None, 1000), Cell::blank(6));
+        }
+
+        #[test]
+        fn nothing_listed() {
+            let table = Table::default();
+            assert_eq!(table.render_size_percent(Some(0), 0), Cell::blank(6));
+        }
+    }
+
+    mod sparseness {
+        use super::*;
+
+        #[test]
+        fn mostly_holes() {
+            let mut table = Table::default();
+            table.colours.size.allocated = Yellow.normal();
+
+            let expected = Cell::paint(Yellow.normal(), "75%");
+            assert_eq!(expected, table.render_sparseness(f::Size::Some(16384), f::Size::Some(4096)));
+        }
+
+        #[test]
+        fn fully_allocated() {
+            let table = Table::default();
+            assert_eq!(table.render_sparseness(f::Size::Some(100), f::Size::Some(4096)).text, "0%");
+        }
+
+        #[test]
+        fn empty_file() {
+            let table = Table::default();
+            assert_eq!(table.render_sparseness(f::Size::Some(0), f::Size::Some(0)).text, "-");
+        }
+    }
+
+    mod dir_totals {
+        use super::*;
+        use options::{SizeFormat, SizeOptions};
+        use std::fs;
+        use std::io::Write;
+
+        fn bytes() -> SizeOptions {
+            SizeOptions { format: SizeFormat::JustBytes, ..SizeOptions::default() }
+        }
+
+        #[test]
+        fn sums_nested_files() {
+            let temp = TempDir::new("dir-totals");
+            let path = temp.path.clone();
+            fs::create_dir_all(path.join("inner")).unwrap();
+            fs::File::create(path.join("outer.txt")).unwrap().write_all(&[0; 100]).unwrap();
+            fs::File::create(path.join("inner").join("inner.txt")).unwrap().write_all(&[0; 23]).unwrap();
+
+            let dir = File::from_path(&path, None).unwrap();
+            let table = Table::default();
+            assert_eq!(table.render_dir_size(dir.recursive_size(), bytes()).text, "123");
+
+        }
+
+        #[test]
+        fn truncated() {
+            let table = Table::default();
+            let total = f::DirSize { bytes: 4, truncated: true, approximate: true };
+            assert_eq!(table.render_dir_size(total, bytes()).text, "4+");
+        }
+
+        #[test]
+        fn approximate() {
+            let table = Table::default();
+            let total = f::DirSize { bytes: 4, truncated: false, approximate: true };
+            assert_eq!(table.render_dir_size(total, bytes()).text, "4?");
+        }
+    }
+
+    mod checksums {
+        use super::*;
+
+        #[test]
+        fn truncated() {
+            let table = Table::default();
+            let checksum = f::Checksum::Some("d41d8cd98f00b204e9800998ecf8427e".to_string());
+            assert_eq!(table.render_checksum(checksum).text, "d41d8cd9");
+        }
+
+        #[test]
+        fn unknown() {
+            let table = Table::default();
+            assert_eq!(table.render_checksum(f::Checksum::Unknown).text, "-");
+        }
+    }
+
+    mod large_flag {
+        use super::*;
+
+        #[test]
+        fn over_threshold() {
+            let mut table = Table::default();
+            table.colours.size.large = Red.bold();
+            assert_eq!(table.render_large_flag(f::Size::Some(2_000_000), 1_000_000), Cell::paint(Red.bold(), "!"));
+        }
+
+        #[test]
+        fn under_threshold() {
+            let table = Table::default();
+            assert_eq!(table.render_large_flag(f::Size::Some(1_000_000), 1_000_000), Cell::empty());
+            assert_eq!(table.render_large_flag(f::Size::None, 0), Cell::empty());
+        }
+    }
+
+    mod html {
+        use super::*;
+        use super::super::Row;
+
+        #[test]
+        fn flat() {
+            let mut table = Table::default();
+            table.columns = vec![ Column::Blocks ];
+            table.rows = vec![ Row::test("a&b", 0, &[ "1" ]) ];
+
+            assert_eq!(table.print_html(false), vec![ "<table>", "<tr><td>1</td><td>a&amp;b</td></tr>", "</table>" ]);
+        }
+
+        #[test]
+        fn tree() {
+            let mut table = Table::default();
+            table.columns = vec![ Column::Blocks ];
+            table.rows = vec![ Row::test("a", 0, &[ "1" ]), Row::test("b", 1, &[ "1" ]), Row::test("c", 0, &[ "1" ]) ];
+
+            assert_eq!(table.print_html(true), vec![
+                "<ul>", "<li><span>1</span> a",
+                "<ul>", "<li><span>1</span> b",
+                "</li></ul>", "</li>", "<li><span>1</span> c",
+                "</li></ul>",
+            ]);
+        }
+    }
+
+    mod dir_slash {
+        use super::*;
+        use super::super::dir_slash;
+        use colours::Colours;
+        use output::details::Details;
+        use std::fs::{self, File as FsFile};
+        use std::os::unix::fs::symlink;
+        use std::path::Path;
+
+        fn fixture() -> TempDir {
+            let temp = TempDir::new("dir-slash");
+            fs::create_dir(temp.path.join("dir")).unwrap();
+            let _ = FsFile::create(temp.path.join("file")).unwrap();
+            symlink(temp.path.join("dir"), temp.path.join("link")).unwrap();
+            temp
+        }
+
+        #[test]
+        fn directory() {
+            let temp = fixture();
+            let dir = File::from_path(&temp.path.join("dir"), None).unwrap();
+            assert_eq!(dir_slash(&dir, &Colours::plain()), Some(Cell::paint(Style::default(), "/")));
+        }
+
+        #[test]
+        fn file() {
+            let temp = fixture();
+            let file = File::from_path(&temp.path.join("file"), None).unwrap();
+            assert_eq!(dir_slash(&file, &Colours::plain()), None);
+        }
+
+        #[test]
+        fn before_link_arrow() {
+            let temp = fixture();
+            let link = File::from_path(&temp.path.join("link"), None).unwrap();
+
+            let mut table = Table::default();
+            table.columns = Vec::new();
+
+            let details = Details { dir_slash: true, colours: Colours::plain(), ..Details::default() };
+            let _ = details.add_files_to_table(&mut table, vec![ link ], 0, Path::new(""));
+
+            let target = temp.path.join("dir");
+            assert_eq!(table.rows[0].name.plain_text(), format!("link/ -> {}", target.display()));
+            assert_eq!(table.rows[0].name.length, 5);
+        }
+    }
+
+    mod json_lines {
+        use super::*;
+        use std::fs;
+        use options::TimeType;
+
+        #[test]
+        fn flat_line() {
+            let mut table = Table::default();
+            table.columns = vec![ Column::Blocks, Column::Timestamp(TimeType::FileModified) ];
+
+            let temp = TempDir::new("json-lines");
+            let path = temp.path.join("src");
+            fs::create_dir(&path).unwrap();
+
+            let src = File::from_path(&path, None).unwrap();
+            let cells = vec![ Cell::paint(Style::default(), "8"), Cell::paint(Style::default(), "1 Jan 00:00") ];
+
+            assert_eq!(table.json_line(&src, 1, &cells),
+                       format!("{{\"depth\":1,\"path\":\"{}\",\"name\":\"src\",\"blocks\":\"8\",\"date-modified\":\"1 Jan 00:00\"}}", path.display()));
+        }
+    }
+
+    mod sequential {
+        use super::*;
+        use std::fs;
+        use std::path::Path;
+        use options::RecurseOptions;
+        use output::details::Details;
+
+        fn tree(sequential_limit: usize) -> Vec<String> {
+            let temp = TempDir::new("sequential");
+            for dir in &[ "root/one", "root/two/three" ] {
+                fs::create_dir_all(temp.path.join(dir)).unwrap();
+            }
+            for file in &[ "root/a", "root/one/b", "root/one/c", "root/two/d", "root/two/three/e" ] {
+                fs::File::create(temp.path.join(file)).unwrap();
+            }
+
+            let mut table = Table::default();
+            let details = Details {
+                recurse: Some(RecurseOptions { tree: true, max_depth: None, max_children: None }),
+                sequential_limit: sequential_limit,
+                ..Details::default()
+            };
+
+            let root = File::from_path(&temp.path.join("root"), None).unwrap();
+            let _ = details.add_files_to_table(&mut table, vec![ root ], 0, Path::new(""));
+            table.print_table().into_iter().map(|cell| cell.text).collect()
+        }
+
+        #[test]
+        fn same_as_parallel() {
+            let sequential = tree(::std::usize::MAX);
+            assert_eq!(sequential.len(), 9);
+            assert_eq!(sequential, tree(0));
+        }
+    }
+
+    mod xattr_marker {
+        use super::*;
+        use feature::xattr::Attribute;
+        use output::details::Details;
+
+        fn attributes() -> Vec<Attribute> {
+            vec![ Attribute { name: "user.tag".to_string(), size: 3 } ]
+        }
+
+        #[test]
+        fn shown() {
+            let details = Details { xattr: true, ..Details::default() };
+            assert!(details.xattr_marker(&attributes()));
+            assert!(!details.xattr_marker(&[]));
+        }
+
+        #[test]
+        fn hidden() {
+            let details = Details { xattr: true, hide_xattr_marker: true, ..Details::default() };
+            assert!(!details.xattr_marker(&attributes()));
+            assert!(!details.xattr_marker(&[]));
+        }
+    }
+
+    mod total_blocks {
+        use super::*;
+        use super::super::total_blocks;
+        use std::fs;
+        use std::io::Write;
+        use std::os::unix::fs::MetadataExt;
+
+        #[test]
+        fn empty() {
+            assert_eq!(total_blocks(&[]), 0);
+        }
+
+        #[test]
+        fn directories_add_nothing() {
+            let temp = TempDir::new("total-blocks-dirs");
+            fs::create_dir(temp.path.join("dir")).unwrap();
+
+            let dir = File::from_path(&temp.path.join("dir"), None).unwrap();
+            assert_eq!(total_blocks(&[ dir ]), 0);
+        }
+
+        #[test]
+        fn files_add_their_blocks() {
+            let temp = TempDir::new("total-blocks-files");
+            fs::create_dir(temp.path.join("dir")).unwrap();
+            for &(name, size) in &[ ("small", 100), ("large", 100_000) ] {
+                fs::File::create(temp.path.join(name)).unwrap().write_all(&vec![ b'x'; size ]).unwrap();
+            }
+
+            let expected = [ "small", "large" ].iter()
+                .map(|name| fs::metadata(temp.path.join(name)).unwrap().blocks() as u64)
+                .fold(0, |sum, blocks| sum + blocks);
+
+            let files: Vec<File> = [ "small", "large", "dir" ].iter()
+                .map(|name| File::from_path(&temp.path.join(name), None).unwrap())
+                .collect();
+
+            assert!(expected > 0);
+            assert_eq!(total_blocks(&files), expected);
+        }
+    }
+
+    mod unreadable_dirs {
+        use super::*;
+        use std::fs;
+        use std::path::Path;
+        use options::RecurseOptions;
+        use output::details::Details;
+
+        /// Add the files with the given names in a new temporary directory
+        /// to a tree, one level down so the tree gets drawn for them, and
+        /// return the table's lines. The ones marked `true` are directories
+        /// that get removed after they've been found, so opening them fails.
+        fn tree_lines(name: &str, files: &[(&str, bool)]) -> Vec<String> {
+            let temp = TempDir::new(name);
+            let mut src = Vec::new();
+
+            for &(name, vanishes) in files {
+                let path = temp.path.join(name);
+                if vanishes { fs::create_dir(&path).unwrap(); }
+                       else { let _ = fs::File::create(&path).unwrap(); }
+
+                src.push(File::from_path(&path, None).unwrap());
+
+                if vanishes {
+                    fs::remove_dir(&path).unwrap();
+                }
+            }
+
+            let mut table = Table::default();
+            table.columns = Vec::new();
+
+            let details = Details {
+                recurse: Some(RecurseOptions { tree: true, max_depth: None, max_children: None }),
+                ..Details::default()
+            };
+
+            let _ = details.add_files_to_table(&mut table, src, 1, Path::new(""));
+            table.print_table().into_iter().map(|c| c.plain_text()).collect()
+        }
+
+        #[test]
+        fn error_is_last_child() {
+            let lines = tree_lines("vanished-last", &[ ("a", false), ("b", true) ]);
+
+            assert_eq!(lines.len(), 3);
+            assert_eq!(lines[0], "├── a");
+            assert_eq!(lines[1], "└── b");
+            assert!(lines[2].starts_with("   └── <"));
+        }
+
+        #[test]
+        fn error_before_next_file() {
+            let lines = tree_lines("vanished-first", &[ ("a", true), ("b", false) ]);
+
+            assert_eq!(lines.len(), 3);
+            assert_eq!(lines[0], "├── a");
+            assert!(lines[1].starts_with("│  └── <"));
+            assert_eq!(lines[2], "└── b");
+        }
+    }
 }
@@ -1,15 +1,18 @@
 use colours::Colours;
 use file::File;
-use filetype::file_colour;
+use filetype::{file_colour, NameColouring};
+use super::{display_name, filename_width};
 
 use term_grid as grid;
 
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct Grid {
     pub across: bool,
     pub console_width: usize,
     pub colours: Colours,
+    pub name_colouring: NameColouring,
+    pub quote_names: bool,
 }
 
 impl Grid {
@@ -26,8 +29,8 @@ impl Grid {
 
         for file in files.iter() {
             grid.add(grid::Cell {
-                contents:  file_colour(&self.colours, file).paint(&file.name).to_string(),
-                width:     file.file_name_width(),
+                contents:  file_colour(&self.colours, self.name_colouring, file).paint(&display_name(&file.name, self.quote_names)).to_string(),
+                width:     filename_width(file, self.quote_names),
             });
         }
 
@@ -37,7 +40,7 @@ impl Grid {
         else {
             // File names too long for a grid - drop down to just listing them!
             for file in files.iter() {
-                println!("{}", file_colour(&self.colours, file).paint(&file.name));
+                println!("{}", file_colour(&self.colours, self.name_colouring, file).paint(&display_name(&file.name, self.quote_names)));
             }
         }
     }
@@ -1,3 +1,4 @@
+use std::cmp;
 use std::iter::repeat;
 
 use users::OSUsers;
@@ -10,7 +11,7 @@ use file::File;
 use output::details::{Details, Table};
 use output::grid::Grid;
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct GridDetails {
     pub grid: Grid,
     pub details: Details,
@@ -30,8 +31,11 @@ impl GridDetails {
             None => Vec::new(),
         };
 
-        let mut first_table = Table::with_options(self.details.colours, columns_for_dir.clone());
-        let cells: Vec<_> = files.iter().map(|file| first_table.cells_for_file(file, file_has_xattrs(file))).collect();
+        let mut first_table = self.details.cells_table(columns_for_dir.clone());
+
+        let mut cells: Vec<_> = files.iter().map(|file| first_table.cells_for_file(file, self.details.xattr && !self.details.hide_xattr_marker && file_has_xattrs(file))).collect();
+        if self.details.link_siblings { first_table.add_link_siblings_to_cells(files, &mut cells) }
+        if self.details.align_decimals { first_table.align_size_decimals_in_cells(&mut cells) }
 
         let mut last_working_table = self.make_grid(1, &*columns_for_dir, files, cells.clone());
 
@@ -54,8 +58,12 @@ impl GridDetails {
     }
 
     fn make_table(&self, columns_for_dir: &[Column]) -> Table<OSUsers> {
-        let mut table = Table::with_options(self.details.colours, columns_for_dir.into());
-        if self.details.header { table.add_header() }
+        let mut table = self.details.cells_table(columns_for_dir.into());
+        if self.details.header { table.add_header(self.details.header_style) }
+        if self.details.dir_slash { table.use_dir_slashes() }
+        if self.details.quote_names { table.use_quoted_names() }
+        table.set_name_colouring(self.details.name_colouring);
+        table.set_width_limits(self.details.width_limits.clone());
         table
     }
 
@@ -67,20 +75,25 @@ impl GridDetails {
             num_cells += column_count;
         }
 
-        let original_height = divide_rounding_up(cells.len(), column_count);
+        let file_count = cells.len();
         let height = divide_rounding_up(num_cells, column_count);
 
-        for (i, (file, row)) in files.iter().zip(cells.into_iter()).enumerate() {
-            let index = if self.grid.across {
-                    i % column_count
-                }
-                else {
-                    i / original_height
-                };
-
+        for (i, (file, mut row)) in files.iter().zip(cells.into_iter()).enumerate() {
+            let index = table_index(i, file_count, column_count, self.grid.across);
+            tables[index].set_rank(&mut row, i + 1);
             tables[index].add_file_with_cells(row, file, 0, false, false);
         }
 
+        // Each table is sized independently, so line their columns up by
+        // making every table as wide as the widest one, column by column.
+        let widths = tables.iter().map(|t| t.column_widths()).fold(Vec::new(), |acc: Vec<usize>, widths| {
+            widths.iter().enumerate().map(|(n, &w)| cmp::max(w, acc.get(n).cloned().unwrap_or(0))).collect()
+        });
+
+        for table in tables.iter_mut() {
+            table.set_min_widths(widths.clone());
+        }
+
         let columns: Vec<_> = tables.iter().map(|t| t.print_table()).collect();
 
         let direction = if self.grid.across { grid::Direction::LeftToRight }
@@ -127,4 +140,44 @@ fn divide_rounding_up(a: usize, b: usize) -> usize {
     let mut result = a / b;
     if a % b != 0 { result += 1; }
     result
+}
+
+
+/// Which of the tables the file at the given position in the listing goes
+/// into. Across, the files get dealt out to the tables in turn, as `ls -x`
+/// does; otherwise, each table gets filled up before the next one starts,
+/// as `ls -C` does.
+fn table_index(index: usize, file_count: usize, column_count: usize, across: bool) -> usize {
+    if across {
+        index % column_count
+    }
+    else {
+        index / divide_rounding_up(file_count, column_count)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::table_index;
+
+    fn layout(names: &[&'static str], column_count: usize, across: bool) -> Vec<Vec<&'static str>> {
+        let mut tables = vec![ Vec::new(); column_count ];
+        for (i, name) in names.iter().enumerate() {
+            tables[table_index(i, names.len(), column_count, across)].push(*name);
+        }
+        tables
+    }
+
+    const NAMES: &'static [&'static str] = &[ "a", "b", "c", "d", "e", "f", "g" ];
+
+    #[test]
+    fn across() {
+        assert_eq!(layout(NAMES, 3, true), vec![ vec![ "a", "d", "g" ], vec![ "b", "e" ], vec![ "c", "f" ] ]);
+    }
+
+    #[test]
+    fn down() {
+        assert_eq!(layout(NAMES, 3, false), vec![ vec![ "a", "b", "c" ], vec![ "d", "e", "f" ], vec![ "g" ] ]);
+    }
 }
\ No newline at end of file
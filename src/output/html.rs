@@ -0,0 +1,180 @@
+//! Converting cells of terminal output into HTML.
+//!
+//! The cells in a table have already had their colours applied as ANSI
+//! escape codes by the time they're printed, so rather than rendering each
+//! column twice, the escape codes get turned into `<span>` elements with
+//! inline styles.
+
+
+/// Escape the characters that have special meanings in HTML.
+pub fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&'  => result.push_str("&amp;"),
+            '<'  => result.push_str("&lt;"),
+            '>'  => result.push_str("&gt;"),
+            '"'  => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c    => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// Convert a string containing ANSI style escape codes into HTML, with each
+/// styled run of text wrapped in a `<span>` and everything else escaped.
+pub fn ansi_to_html(text: &str) -> String {
+    let mut result = String::new();
+    let mut style = SpanStyle::default();
+    let mut span_open = false;
+    let mut plain = String::new();
+
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1B' {
+            plain.push(c);
+            continue;
+        }
+
+        // Only SGR sequences, of the form `ESC [ codes m`, get produced by
+        // ansi_term, so anything else is dropped.
+        let codes: String = chars.by_ref().skip(1).take_while(|c| *c != 'm').collect();
+
+        result.push_str(&escape(&plain));
+        plain.clear();
+
+        style.apply(&codes);
+
+        if span_open {
+            result.push_str("</span>");
+            span_open = false;
+        }
+
+        let css = style.css();
+        if !css.is_empty() {
+            result.push_str(&format!("<span style=\"{}\">", css));
+            span_open = true;
+        }
+    }
+
+    result.push_str(&escape(&plain));
+    if span_open {
+        result.push_str("</span>");
+    }
+
+    result
+}
+
+
+/// The current state of the text's style, built up from escape codes.
+#[derive(Default)]
+struct SpanStyle {
+    bold:        bool,
+    dimmed:      bool,
+    underline:   bool,
+    foreground:  Option<String>,
+    background:  Option<String>,
+}
+
+impl SpanStyle {
+
+    /// Update this style with a list of semicolon-separated codes. Codes
+    /// that aren't understood are ignored.
+    fn apply(&mut self, codes: &str) {
+        let mut numbers = codes.split(';').map(|n| n.parse::<u8>().unwrap_or(0));
+
+        while let Some(number) = numbers.next() {
+            match number {
+                0          => *self = SpanStyle::default(),
+                1          => self.bold = true,
+                2          => self.dimmed = true,
+                4          => self.underline = true,
+                30 ... 37  => self.foreground = Some(colour(number - 30)),
+                40 ... 47  => self.background = Some(colour(number - 40)),
+                38 | 48    => {
+                    let colour = match (numbers.next(), numbers.next()) {
+                        (Some(5), Some(n))  => colour(n),
+                        _                   => continue,
+                    };
+
+                    if number == 38 { self.foreground = Some(colour) }
+                               else { self.background = Some(colour) }
+                },
+                _          => {},
+            }
+        }
+    }
+
+    /// The inline CSS for this style, which is empty for unstyled text.
+    fn css(&self) -> String {
+        let mut rules = Vec::new();
+
+        if let Some(ref colour) = self.foreground { rules.push(format!("color: {}", colour)) }
+        if let Some(ref colour) = self.background { rules.push(format!("background-color: {}", colour)) }
+        if self.bold      { rules.push("font-weight: bold".to_string()) }
+        if self.dimmed    { rules.push("opacity: 0.5".to_string()) }
+        if self.underline { rules.push("text-decoration: underline".to_string()) }
+
+        rules.join("; ")
+    }
+}
+
+/// The CSS colour for one of the 256 terminal colours, using the usual
+/// xterm palette.
+fn colour(number: u8) -> String {
+    const BASIC: [&'static str; 16] = [
+        "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+        "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+    ];
+
+    const LEVELS: [u8; 6] = [ 0, 95, 135, 175, 215, 255 ];
+
+    match number {
+        0 ... 15    => BASIC[number as usize].to_string(),
+        16 ... 231  => {
+            let n = number - 16;
+            format!("#{:02x}{:02x}{:02x}", LEVELS[(n / 36) as usize], LEVELS[(n / 6 % 6) as usize], LEVELS[(n % 6) as usize])
+        },
+        _           => {
+            let grey = 8 + (number - 232) * 10;
+            format!("#{:02x}{:02x}{:02x}", grey, grey, grey)
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::{ansi_to_html, colour, escape};
+    use ansi_term::Colour::{Blue, Fixed};
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape("<a & b>"), "&lt;a &amp; b&gt;")
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(ansi_to_html("file.txt"), "file.txt")
+    }
+
+    #[test]
+    fn styled() {
+        let text = Blue.bold().paint("src").to_string();
+        assert_eq!(ansi_to_html(&text), "<span style=\"color: #0000ee; font-weight: bold\">src</span>")
+    }
+
+    #[test]
+    fn fixed() {
+        let text = Fixed(244).paint("<x>").to_string();
+        assert_eq!(ansi_to_html(&text), "<span style=\"color: #808080\">&lt;x&gt;</span>")
+    }
+
+    #[test]
+    fn cube() {
+        assert_eq!(colour(208), "#ff8700")
+    }
+}
@@ -0,0 +1,59 @@
+//! Writing files' details as lines of JSON.
+//!
+//! Each file gets printed as one flat JSON object on its own line, rather
+//! than as part of one big document, so huge recursive listings can be
+//! streamed out without keeping the whole tree in memory.
+
+
+/// Quote a string as a JSON string literal, escaping the characters that
+/// aren't allowed to appear in one as they are.
+pub fn string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+
+    for c in text.chars() {
+        match c {
+            '"'   => result.push_str("\\\""),
+            '\\'  => result.push_str("\\\\"),
+            '\n'  => result.push_str("\\n"),
+            '\r'  => result.push_str("\\r"),
+            '\t'  => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c     => result.push(c),
+        }
+    }
+
+    result.push('"');
+    result
+}
+
+/// Write out an object from a list of keys and values, where the values
+/// have already been turned into JSON.
+pub fn object(fields: &[(String, String)]) -> String {
+    let fields: Vec<String> = fields.iter().map(|&(ref key, ref value)| format!("{}:{}", string(key), value)).collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::{object, string};
+
+    #[test]
+    fn escapes() {
+        assert_eq!(string("a \"b\"\\c\n"), "\"a \\\"b\\\"\\\\c\\n\"");
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(string("\x1B[0m"), "\"\\u001b[0m\"");
+    }
+
+    #[test]
+    fn flat_object() {
+        let fields = vec![ ("depth".to_string(), "1".to_string()),
+                           ("name".to_string(), string("a")) ];
+
+        assert_eq!(object(&fields), "{\"depth\":1,\"name\":\"a\"}");
+    }
+}
@@ -1,19 +1,22 @@
 use colours::Colours;
 use file::File;
+use filetype::NameColouring;
 
 use super::filename;
 
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Lines {
     pub colours: Colours,
+    pub name_colouring: NameColouring,
+    pub quote_names: bool,
 }
 
 /// The lines view literally just displays each file, line-by-line.
 impl Lines {
     pub fn view(&self, files: &[File]) {
         for file in files {
-            println!("{}", filename(file, &self.colours, true));
+            println!("{}", filename(file, &self.colours, self.name_colouring, true, self.quote_names));
         }
     }
 }
@@ -1,8 +1,9 @@
 use ansi_term::ANSIStrings;
+use unicode_width::UnicodeWidthStr;
 
 use colours::Colours;
 use file::File;
-use filetype::file_colour;
+use filetype::{file_colour, NameColouring};
 
 pub use self::details::Details;
 pub use self::grid::Grid;
@@ -11,30 +12,105 @@ pub use self::grid_details::GridDetails;
 
 mod grid;
 pub mod details;
+mod html;
+mod json;
 mod lines;
 mod grid_details;
 
-pub fn filename(file: &File, colours: &Colours, links: bool) -> String {
+pub fn filename(file: &File, colours: &Colours, colouring: NameColouring, links: bool, quote: bool) -> String {
     if links && file.is_link() {
-        symlink_filename(file, colours)
+        symlink_filename(file, colours, colouring, quote)
     }
     else {
-        let style = file_colour(colours, file);
-        style.paint(&file.name).to_string()
+        let style = file_colour(colours, colouring, file);
+        style.paint(&display_name(&file.name, quote)).to_string()
     }
 }
 
-fn symlink_filename(file: &File, colours: &Colours) -> String {
-    match file.link_target() {
-        Ok(target) => format!("{} {} {}",
-                              file_colour(colours, file).paint(&file.name),
+fn symlink_filename(file: &File, colours: &Colours, colouring: NameColouring, quote: bool) -> String {
+    format!("{}{}",
+            file_colour(colours, colouring, file).paint(&display_name(&file.name, quote)),
+            link_arrow(colours, colouring, file.link_target(), quote))
+}
+
+/// The arrow from a symlink's name to its target, and the target itself,
+/// which get displayed after the name, starting with a space.
+pub fn link_arrow(colours: &Colours, colouring: NameColouring, target: Result<File, String>, quote: bool) -> String {
+    match target {
+        Ok(target) => format!(" {} {}",
                               colours.punctuation.paint("->"),
                               ANSIStrings(&[ colours.symlink_path.paint(&target.path_prefix()),
-                                             file_colour(colours, &target).paint(&target.name) ])),
+                                             file_colour(colours, colouring, &target).paint(&display_name(&target.name, quote)) ])),
 
-        Err(filename) => format!("{} {} {}",
-                                 file_colour(colours, file).paint(&file.name),
+        Err(filename) => format!(" {} {}",
                                  colours.broken_arrow.paint("->"),
-                                 colours.broken_filename.paint(&filename)),
+                                 colours.broken_filename.paint(&display_name(&filename, quote))),
+    }
+}
+
+/// The width of a file's name as it gets displayed, which is wider when
+/// it has to be quoted.
+pub fn filename_width(file: &File, quote: bool) -> usize {
+    if quote {
+        UnicodeWidthStr::width(&display_name(&file.name, true)[..])
+    }
+    else {
+        file.file_name_width()
+    }
+}
+
+/// A name as it gets displayed: as it is, unless it's being quoted and
+/// has characters in it that a shell would treat specially.
+pub fn display_name(name: &str, quote: bool) -> String {
+    match quote_name(name) {
+        Some(quoted) if quote => quoted,
+        _                     => name.to_string(),
+    }
+}
+
+/// Quote a name the way a shell would need it to be quoted, so it can be
+/// copied and pasted into a command, or return `None` if it doesn't need
+/// quoting. Single quotes are used where possible, as nothing inside them
+/// is special; names with single quotes in get double quotes instead,
+/// unless they also have characters that are special inside those.
+fn quote_name(name: &str) -> Option<String> {
+    let needs_quotes = name.chars().any(|c| c.is_whitespace() || c.is_control() || "'\"\\$`!*?[]{}()<>|&;#~".contains(c));
+    if !needs_quotes {
+        None
+    }
+    else if !name.contains('\'') {
+        Some(format!("'{}'", name))
+    }
+    else if !name.chars().any(|c| "\"\\$`!".contains(c)) {
+        Some(format!("\"{}\"", name))
+    }
+    else {
+        Some(format!("'{}'", name.replace("'", "'\\''")))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::quote_name;
+
+    #[test]
+    fn plain() {
+        assert_eq!(quote_name("file.txt"), None);
+    }
+
+    #[test]
+    fn space() {
+        assert_eq!(quote_name("my file"), Some("'my file'".to_string()));
+    }
+
+    #[test]
+    fn single_quote() {
+        assert_eq!(quote_name("don't"), Some("\"don't\"".to_string()));
+    }
+
+    #[test]
+    fn single_quote_and_dollar() {
+        assert_eq!(quote_name("it's $5"), Some("'it'\\''s $5'".to_string()));
     }
 }
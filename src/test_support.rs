@@ -0,0 +1,36 @@
+//! Things that the tests in more than one module need.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+use libc;
+
+
+/// A directory for a test to make its files in. Each one gets a name
+/// of its own, so tests running at the same time can't get in each
+/// other's way, and it gets removed when it goes out of scope, even if
+/// the test fails.
+pub struct TempDir {
+    pub path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> TempDir {
+        static COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+        let count = COUNT.fetch_add(1, Ordering::SeqCst);
+        let pid = unsafe { libc::getpid() };
+        let path = env::temp_dir().join(format!("exa-test-{}-{}-{}", name, pid, count));
+
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path: path }
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}